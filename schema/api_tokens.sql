@@ -0,0 +1,17 @@
+CREATE TABLE `api_tokens` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `public_id` binary(16) NOT NULL,
+    `user_id` bigint unsigned NOT NULL,
+    `name` varchar(256) NOT NULL,
+    `token_hash` binary(32) NOT NULL,
+    `scopes` varchar(64) NOT NULL, -- comma separated, e.g. read,post,comment
+    `last_used` datetime NULL,
+    `is_revoked` boolean NOT NULL,
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `api_tokens_idx_public_id` (`public_id`),
+    UNIQUE KEY `api_tokens_idx_token_hash` (`token_hash`),
+    KEY `api_tokens_idx_user_id` (`user_id`)
+);
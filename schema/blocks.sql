@@ -0,0 +1,9 @@
+CREATE TABLE `blocks` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NOT NULL,
+    `blocked_id` bigint unsigned NOT NULL,
+    `created` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `blocks_idx_user_id_blocked_id` (`user_id`, `blocked_id`)
+);
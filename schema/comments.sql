@@ -5,6 +5,7 @@ CREATE TABLE `comments` (
     `post_id` bigint unsigned NOT NULL,
     `parent_id` bigint unsigned NULL,
     `content_id` bigint unsigned NOT NULL,
+    `is_removed` boolean NOT NULL,
     `created` datetime NOT NULL,
     `updated` datetime NOT NULL,
     
@@ -1,6 +1,7 @@
 CREATE TABLE `contents` (
     `id` bigint unsigned NOT NULL AUTO_INCREMENT,
     `body` mediumtext NOT NULL,
+    `body_html` mediumtext NULL, -- NULL for contents written before the HTML was stored
     `body_hash` binary(32) NOT NULL,
     `created` datetime NOT NULL,
     
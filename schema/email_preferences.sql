@@ -0,0 +1,14 @@
+CREATE TABLE `email_preferences` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NOT NULL,
+    `frequency` varchar(16) NOT NULL, -- immediate, daily, weekly or never
+    `unsubscribe_token` varchar(64) NOT NULL, -- stored as is, it's in every email and can only unsubscribe
+    `last_notification_id` bigint unsigned NOT NULL, -- the newest notification already emailed
+    `last_sent` datetime NULL,
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `email_preferences_idx_user_id` (`user_id`),
+    UNIQUE KEY `email_preferences_idx_unsubscribe_token` (`unsubscribe_token`)
+);
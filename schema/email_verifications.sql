@@ -0,0 +1,12 @@
+CREATE TABLE `email_verifications` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NOT NULL,
+    `email_id` bigint unsigned NOT NULL, -- the address the token was sent to
+    `token_hash` binary(32) NOT NULL,
+    `created` datetime NOT NULL,
+    `expires` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `email_verifications_idx_token_hash` (`token_hash`),
+    KEY `email_verifications_idx_user_id` (`user_id`)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE `identities` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NOT NULL,
+    `provider` varchar(64) NOT NULL,
+    `subject` varchar(255) NOT NULL, -- the provider's unique id for the user
+    `created` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `identities_idx_provider_subject` (`provider`, `subject`),
+    KEY `identities_idx_user_id` (`user_id`)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE `link_previews` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `post_id` bigint unsigned NOT NULL,
+    `url` varchar(1024) NOT NULL, -- the page the preview was taken from, after redirects
+    `title` varchar(512) NULL,
+    `description` varchar(1024) NULL,
+    `image_url` varchar(1024) NULL,
+    `site_name` varchar(255) NULL,
+    `created` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `link_previews_idx_post_id` (`post_id`)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE `login_attempts` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NULL,
+    `ip` varchar(64) NULL,
+    `is_success` boolean NOT NULL,
+    `created` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    KEY `login_attempts_idx_user_id_created` (`user_id`, `created`),
+    KEY `login_attempts_idx_ip_created` (`ip`, `created`)
+);
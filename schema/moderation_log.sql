@@ -0,0 +1,16 @@
+CREATE TABLE `moderation_log` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `actor_id` bigint unsigned NOT NULL,
+    `action` varchar(32) NOT NULL,
+    `target_user_id` bigint unsigned NULL, -- the user acted on, or the author of the post or comment
+    `post_id` bigint unsigned NULL,
+    `comment_id` bigint unsigned NULL,
+    `reason` varchar(1024) NULL,
+    `details` varchar(64) NULL, -- e.g. the new role
+    `created` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    KEY `moderation_log_idx_actor_id` (`actor_id`),
+    KEY `moderation_log_idx_target_user_id` (`target_user_id`),
+    KEY `moderation_log_idx_action` (`action`)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE `notifications` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `public_id` binary(16) NOT NULL,
+    `user_id` bigint unsigned NOT NULL, -- who is notified
+    `comment_id` bigint unsigned NOT NULL, -- the reply to one of their posts or comments
+    `is_read` boolean NOT NULL,
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `notifications_idx_public_id` (`public_id`),
+    UNIQUE KEY `notifications_idx_user_id_comment_id` (`user_id`, `comment_id`),
+    KEY `notifications_idx_user_id_is_read` (`user_id`, `is_read`)
+);
@@ -5,6 +5,8 @@ CREATE TABLE `posts` (
     `title` varchar(512) NOT NULL,
     `link` varchar(1024) NULL,
     `content_id` bigint unsigned NULL,
+    `is_removed` boolean NOT NULL,
+    `is_locked` boolean NOT NULL,
     `created` datetime NOT NULL,
     `updated` datetime NOT NULL,
 
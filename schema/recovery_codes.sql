@@ -0,0 +1,10 @@
+CREATE TABLE `recovery_codes` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NOT NULL,
+    `code_hash` binary(32) NOT NULL,
+    `is_used` boolean NOT NULL,
+    `created` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    KEY `recovery_codes_idx_user_id_code_hash` (`user_id`, `code_hash`)
+);
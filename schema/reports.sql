@@ -0,0 +1,17 @@
+CREATE TABLE `reports` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `reporter_id` bigint unsigned NULL, -- NULL when the spam filter is holding it for review
+    `post_id` bigint unsigned NOT NULL,
+    `comment_id` bigint unsigned NULL, -- NULL when the post itself is reported
+    `reason` varchar(16) NOT NULL,
+    `details` varchar(1024) NULL,
+    `resolution` varchar(16) NULL, -- NULL until a moderator resolves it
+    `resolver_id` bigint unsigned NULL,
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    KEY `reports_idx_resolution` (`resolution`),
+    KEY `reports_idx_post_id_comment_id` (`post_id`, `comment_id`),
+    KEY `reports_idx_reporter_id` (`reporter_id`)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE `spam_tokens` (
+    `token` varchar(64) NOT NULL,
+    `spam_count` bigint unsigned NOT NULL, -- how many spam posts and comments it appeared in
+    `ham_count` bigint unsigned NOT NULL,
+
+    PRIMARY KEY (`token`)
+);
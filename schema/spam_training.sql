@@ -0,0 +1,13 @@
+CREATE TABLE `spam_training` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `post_id` bigint unsigned NOT NULL,
+    `comment_id` bigint unsigned NULL, -- NULL when the post itself was trained on
+    `comment_key` bigint unsigned AS (IFNULL(`comment_id`, 0)) STORED, -- a unique key allows any number of NULLs
+    `is_spam` boolean NOT NULL,
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `spam_training_idx_post_id_comment_key` (`post_id`, `comment_key`),
+    KEY `spam_training_idx_is_spam` (`is_spam`)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE `two_factors` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `user_id` bigint unsigned NOT NULL,
+    `secret` varbinary(64) NOT NULL,
+    `is_enabled` boolean NOT NULL,
+    `last_used_step` bigint unsigned NULL, -- prevents a TOTP code being used twice
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `two_factors_idx_user_id` (`user_id`)
+);
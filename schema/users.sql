@@ -3,7 +3,12 @@ CREATE TABLE `users` (
     `public_id` binary(16) NOT NULL,
     `name` varchar(64) NOT NULL,
     `email_id` bigint unsigned NOT NULL,
+    `is_email_verified` boolean NOT NULL DEFAULT 0,
     `password` varchar(1024) NOT NULL, -- hash:salt:hash_func
+    `role` varchar(16) NOT NULL DEFAULT 'user',
+    `is_banned` boolean NOT NULL DEFAULT 0,
+    `is_shadowbanned` boolean NOT NULL DEFAULT 0,
+    `must_reset_password` boolean NOT NULL DEFAULT 0,
     `is_deleted` boolean NOT NULL,
     `created` datetime NOT NULL,
     `updated` datetime NOT NULL,
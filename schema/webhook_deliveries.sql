@@ -0,0 +1,20 @@
+CREATE TABLE `webhook_deliveries` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `public_id` binary(16) NOT NULL,
+    `webhook_id` bigint unsigned NOT NULL,
+    `event` varchar(32) NOT NULL,
+    `subject_id` bigint unsigned NOT NULL, -- the post or comment the event is about
+    `payload` mediumtext NULL, -- built on the first attempt, so retries send the same body
+    `attempts` int unsigned NOT NULL,
+    `status_code` smallint unsigned NULL, -- of the last attempt
+    `error` varchar(1024) NULL, -- of the last attempt
+    `is_delivered` boolean NOT NULL,
+    `next_attempt` datetime NULL, -- null once delivered or given up on
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `webhook_deliveries_idx_public_id` (`public_id`),
+    KEY `webhook_deliveries_idx_webhook_id` (`webhook_id`),
+    KEY `webhook_deliveries_idx_next_attempt` (`next_attempt`)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE `webhooks` (
+    `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+    `public_id` binary(16) NOT NULL,
+    `url` varchar(1024) NOT NULL,
+    `secret` varchar(64) NOT NULL, -- stored as is, every payload is signed with it
+    `events` varchar(128) NOT NULL, -- comma separated, e.g. post.created,comment.created
+    `is_deleted` boolean NOT NULL,
+    `created` datetime NOT NULL,
+    `updated` datetime NOT NULL,
+
+    PRIMARY KEY (`id`),
+    UNIQUE KEY `webhooks_idx_public_id` (`public_id`)
+);
@@ -0,0 +1,11 @@
+use crate::{entities::EntityError, mailer::MailerError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum DigestError {
+    #[error("email address isn't verified")]
+    Unverified,
+    #[error("error loading digest")]
+    Entity(#[from] EntityError),
+    #[error("error sending digest")]
+    Mailer(#[from] MailerError),
+}
@@ -0,0 +1,319 @@
+mod error;
+
+use std::{sync::Arc, time::Duration as StdDuration};
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use log::{error, info};
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::{
+    entities::{
+        email::EmailStore,
+        email_preference::{EmailFrequency, EmailPreference, EmailPreferenceStore},
+        notification::NotificationStore,
+        post::{Post, PostStore},
+        user::{User, UserStore},
+        EntityStores,
+    },
+    mailer::{EmailMessage, Mailer},
+    routes::models::{translate_notification, NotificationModel},
+};
+
+pub use error::DigestError;
+
+const MAX_REPLIES: u8 = 20;
+const MAX_TOP_POSTS: u8 = 5;
+
+/// Emails users about replies in the background, either as they happen or as a daily or weekly
+/// digest that also lists the most discussed posts, depending on their email preferences.
+#[derive(Clone)]
+pub struct DigestJob {
+    stores: EntityStores,
+    mailer: Arc<dyn Mailer>,
+    tera: Tera,
+    base_url: &'static str,
+    // How often to check for replies to send, so how late an immediate email can be
+    interval: StdDuration,
+}
+
+#[derive(Debug, Serialize)]
+struct DigestReply {
+    author: String,
+    post_title: String,
+    is_reply_to_comment: bool,
+    excerpt: String,
+    link: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DigestPost {
+    title: String,
+    link: String,
+}
+
+impl DigestJob {
+    pub fn new(
+        stores: EntityStores,
+        mailer: Arc<dyn Mailer>,
+        tera: Tera,
+        base_url: &'static str,
+        interval: StdDuration,
+    ) -> Self {
+        Self {
+            stores,
+            mailer,
+            tera,
+            base_url,
+            interval,
+        }
+    }
+
+    pub fn spawn(self) {
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                self.run(Utc::now()).await;
+            }
+        });
+    }
+
+    async fn run(&self, now: DateTime<Utc>) {
+        let user_ids = match self
+            .stores
+            .email_preference_store
+            .get_pending_user_ids()
+            .await
+        {
+            Ok(user_ids) => user_ids,
+            Err(e) => {
+                error!("📧 Couldn't look for replies to email: {:?}", e);
+                return;
+            }
+        };
+
+        for user_id in user_ids {
+            match self.send_pending(user_id, now).await {
+                Ok(true) => info!("📧 Emailed user {} about their replies", user_id),
+                Ok(false) => (),
+                Err(e) => error!("📧 Couldn't email user {}: {} ({:?})", user_id, e, e),
+            }
+        }
+    }
+
+    // False when there was nothing to send yet
+    async fn send_pending(&self, user_id: u64, now: DateTime<Utc>) -> Result<bool, DigestError> {
+        let preference = self
+            .stores
+            .email_preference_store
+            .get_by_user_id(user_id)
+            .await?;
+        if !preference.is_due(now) {
+            return Ok(false);
+        }
+
+        let notifications = self
+            .stores
+            .notification_store
+            .get_unread_after(user_id, preference.last_notification_id, MAX_REPLIES)
+            .await?;
+        let newest_id = match notifications.first() {
+            Some(notification) => notification.id,
+            None => return Ok(false),
+        };
+
+        // Claimed before sending, so a failed email is skipped rather than sent twice
+        let claimed = self
+            .stores
+            .email_preference_store
+            .claim(user_id, preference.last_notification_id, newest_id)
+            .await?;
+        if !claimed {
+            return Ok(false);
+        }
+
+        let user = self.stores.user_store.get_by_id(user_id).await?;
+        // Nobody should be emailed about an account they didn't confirm was theirs
+        if user.is_deleted || !user.is_email_verified {
+            return Err(DigestError::Unverified);
+        }
+        let email = self.stores.email_store.get_by_id(user.email_id).await?;
+
+        let mut replies = vec![];
+        for notification in notifications.iter() {
+            let model = translate_notification(&self.stores, notification).await?;
+            replies.push(self.translate_reply(model));
+        }
+
+        let top_posts = match preference.frequency.interval() {
+            Some(interval) if interval > Duration::zero() => {
+                // Rounded so everyone's digest in a run shares a cached query
+                let since = (now - interval)
+                    .duration_trunc(Duration::hours(1))
+                    .unwrap_or(now - interval);
+                self.stores
+                    .post_store
+                    .get_top_since(since, MAX_TOP_POSTS)
+                    .await?
+            }
+            _ => vec![],
+        };
+        let top_posts = top_posts
+            .into_iter()
+            .map(|post| self.translate_post(post))
+            .collect();
+
+        let message = render_email(
+            &self.tera,
+            self.base_url,
+            &user,
+            &email.address,
+            &preference,
+            replies,
+            top_posts,
+        )?;
+        self.mailer.send(&message).await?;
+
+        Ok(true)
+    }
+
+    fn translate_reply(&self, model: NotificationModel) -> DigestReply {
+        DigestReply {
+            author: model.author.name,
+            post_title: plain_text(&model.post_title),
+            is_reply_to_comment: model.is_reply_to_comment,
+            excerpt: model.excerpt,
+            link: format!("{}/post/{}", self.base_url, model.post_id),
+        }
+    }
+
+    fn translate_post(&self, post: Post) -> DigestPost {
+        DigestPost {
+            title: plain_text(&post.title),
+            link: format!("{}/post/{}", self.base_url, post.public_id),
+        }
+    }
+}
+
+fn render_email(
+    tera: &Tera,
+    base_url: &str,
+    user: &User,
+    address: &str,
+    preference: &EmailPreference,
+    replies: Vec<DigestReply>,
+    top_posts: Vec<DigestPost>,
+) -> Result<EmailMessage, DigestError> {
+    let subject = match (preference.frequency, replies.as_slice()) {
+        (EmailFrequency::Immediate, [reply]) => {
+            format!("{} replied to you on effward.dev", reply.author)
+        }
+        (EmailFrequency::Immediate, replies) => {
+            format!("{} new replies on effward.dev", replies.len())
+        }
+        (frequency, _) => format!("your effward.dev {} digest", frequency),
+    };
+    let unsubscribe_link = format!("{}/unsubscribe/{}", base_url, preference.unsubscribe_token);
+
+    let mut context = Context::new();
+    context.insert("name", &user.name);
+    context.insert("frequency", &preference.frequency);
+    context.insert("replies", &replies);
+    context.insert("top_posts", &top_posts);
+    context.insert("inbox_link", &format!("{}/notifications", base_url));
+    context.insert("settings_link", &format!("{}/settings", base_url));
+    context.insert("unsubscribe_link", &unsubscribe_link);
+
+    Ok(
+        EmailMessage::render(tera, "email/digest.txt", &context, address, &subject)?
+            .with_unsubscribe_url(&unsubscribe_link),
+    )
+}
+
+// Titles are stored html escaped, but the email is plain text
+fn plain_text(title: &str) -> String {
+    html_escape::decode_html_entities(title).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::user::Role;
+
+    #[test]
+    fn test_render_email() {
+        let tera = Tera::new("templates/**/*").unwrap();
+        let now = Utc::now();
+        let user = User {
+            id: 1,
+            public_id: "abc".to_owned(),
+            name: "sasquatch".to_owned(),
+            email_id: 1,
+            is_email_verified: true,
+            role: Role::User,
+            is_banned: false,
+            is_shadowbanned: false,
+            must_reset_password: false,
+            is_deleted: false,
+            created: now,
+            updated: now,
+        };
+        let mut preference = EmailPreference {
+            id: 1,
+            user_id: 1,
+            frequency: EmailFrequency::Immediate,
+            unsubscribe_token: "token".to_owned(),
+            last_notification_id: 0,
+            last_sent: None,
+            created: now,
+            updated: now,
+        };
+        let reply = || DigestReply {
+            author: "yeti".to_owned(),
+            post_title: "fish & chips".to_owned(),
+            is_reply_to_comment: true,
+            excerpt: "I agree".to_owned(),
+            link: "https://effward.dev/post/def".to_owned(),
+        };
+
+        let message = render_email(
+            &tera,
+            "https://effward.dev",
+            &user,
+            "sasquatch@example.com",
+            &preference,
+            vec![reply()],
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(message.subject, "yeti replied to you on effward.dev");
+        assert_eq!(
+            message.unsubscribe_url.as_deref(),
+            Some("https://effward.dev/unsubscribe/token")
+        );
+        assert!(message
+            .body
+            .contains("yeti replied to your comment on fish & chips"));
+        assert!(message
+            .body
+            .contains("https://effward.dev/unsubscribe/token"));
+
+        preference.frequency = EmailFrequency::Weekly;
+        let message = render_email(
+            &tera,
+            "https://effward.dev",
+            &user,
+            "sasquatch@example.com",
+            &preference,
+            vec![reply(), reply()],
+            vec![DigestPost {
+                title: "a popular post".to_owned(),
+                link: "https://effward.dev/post/ghi".to_owned(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(message.subject, "your effward.dev weekly digest");
+        assert!(message.body.contains("a popular post"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a personal access token is allowed to do on its user's behalf.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    Read,
+    Post,
+    Comment,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 3] = [ApiScope::Read, ApiScope::Post, ApiScope::Comment];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::Read => "read",
+            ApiScope::Post => "post",
+            ApiScope::Comment => "comment",
+        }
+    }
+}
+
+impl fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ApiScope, Self::Err> {
+        match input {
+            "read" => Ok(ApiScope::Read),
+            "post" => Ok(ApiScope::Post),
+            "comment" => Ok(ApiScope::Comment),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ApiToken {
+    pub id: u64,
+    pub public_id: String,
+    pub user_id: u64,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub last_used: Option<DateTime<Utc>>,
+    pub created: DateTime<Utc>,
+}
+
+impl ApiToken {
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
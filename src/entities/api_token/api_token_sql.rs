@@ -0,0 +1,256 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+use uuid::Uuid;
+
+use crate::entities::{utils, EntityError};
+
+use super::{ApiScope, ApiToken, ApiTokenStore};
+
+pub const MIN_TOKEN_NAME_LENGTH: usize = 1;
+pub const MAX_TOKEN_NAME_LENGTH: usize = 64;
+
+const SCOPE_SEPARATOR: &str = ",";
+
+#[derive(Clone)]
+pub struct SqlApiTokenStore {
+    pool: MySqlPool,
+}
+
+impl SqlApiTokenStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+// The token hash is only ever compared in queries, so it isn't selected
+#[derive(Debug, sqlx::FromRow)]
+pub struct ApiTokenEntity {
+    pub id: u64,
+    pub public_id: Vec<u8>,
+    pub user_id: u64,
+    pub name: String,
+    pub scopes: String,
+    pub last_used: Option<NaiveDateTime>,
+    pub created: NaiveDateTime,
+}
+
+impl From<ApiTokenEntity> for ApiToken {
+    fn from(api_token_entity: ApiTokenEntity) -> Self {
+        Self {
+            id: api_token_entity.id,
+            public_id: utils::get_readable_public_id(api_token_entity.public_id),
+            user_id: api_token_entity.user_id,
+            name: api_token_entity.name,
+            scopes: parse_scopes(&api_token_entity.scopes),
+            last_used: api_token_entity
+                .last_used
+                .map(|last_used| Utc.from_utc_datetime(&last_used)),
+            created: Utc.from_utc_datetime(&api_token_entity.created),
+        }
+    }
+}
+
+#[async_trait]
+impl ApiTokenStore for SqlApiTokenStore {
+    async fn insert(
+        &self,
+        user_id: u64,
+        name: &str,
+        scopes: &[ApiScope],
+    ) -> Result<(ApiToken, String), EntityError> {
+        let token = utils::generate_secret_token();
+        let api_token_id = insert(&self.pool, user_id, name, scopes, &token).await?;
+
+        Ok((
+            ApiToken::from(get_by_id(&self.pool, api_token_id).await?),
+            token,
+        ))
+    }
+
+    async fn get_by_token(&self, token: &str) -> Result<ApiToken, EntityError> {
+        let api_token = ApiToken::from(get_by_token(&self.pool, token).await?);
+        update_last_used(&self.pool, api_token.id).await?;
+
+        Ok(api_token)
+    }
+
+    async fn get_by_user_id(&self, user_id: u64) -> Result<Vec<ApiToken>, EntityError> {
+        Ok(get_by_user_id(&self.pool, user_id)
+            .await?
+            .into_iter()
+            .map(ApiToken::from)
+            .collect())
+    }
+
+    async fn revoke(&self, user_id: u64, public_id: &str) -> Result<(), EntityError> {
+        let public_id = utils::parse_public_id(public_id)?;
+
+        revoke(&self.pool, user_id, public_id).await
+    }
+}
+
+async fn insert(
+    pool: &MySqlPool,
+    user_id: u64,
+    name: &str,
+    scopes: &[ApiScope],
+    token: &str,
+) -> Result<u64, EntityError> {
+    let name = utils::sanitize_text(name, MIN_TOKEN_NAME_LENGTH, MAX_TOKEN_NAME_LENGTH, "name")?;
+    if scopes.is_empty() {
+        return Err(EntityError::InvalidInput("scopes", "no scopes selected"));
+    }
+
+    let public_id = Uuid::new_v4().into_bytes();
+    let token_hash = utils::hash_secret_token(token)?;
+    let now = Utc::now().naive_utc();
+
+    let api_token_id = sqlx::query!(
+        r#"
+INSERT INTO api_tokens (public_id, user_id, name, token_hash, scopes, last_used, is_revoked, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        &public_id[..],
+        user_id,
+        name,
+        token_hash,
+        format_scopes(scopes),
+        Option::<NaiveDateTime>::None,
+        0,
+        now,
+        now
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(api_token_id)
+}
+
+async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<ApiTokenEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        ApiTokenEntity,
+        r#"
+SELECT id, public_id, user_id, name, scopes, last_used, created
+FROM api_tokens
+WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_by_token(pool: &MySqlPool, token: &str) -> Result<ApiTokenEntity, EntityError> {
+    let token_hash = match utils::hash_secret_token(token) {
+        Ok(hash) => hash,
+        // Anything that isn't shaped like a token can't match one
+        Err(_) => return Err(EntityError::NotFound),
+    };
+
+    Ok(sqlx::query_as!(
+        ApiTokenEntity,
+        r#"
+SELECT id, public_id, user_id, name, scopes, last_used, created
+FROM api_tokens
+WHERE token_hash = ? AND is_revoked = 0
+        "#,
+        token_hash
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_by_user_id(
+    pool: &MySqlPool,
+    user_id: u64,
+) -> Result<Vec<ApiTokenEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        ApiTokenEntity,
+        r#"
+SELECT id, public_id, user_id, name, scopes, last_used, created
+FROM api_tokens
+WHERE user_id = ? AND is_revoked = 0
+ORDER BY created DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn update_last_used(pool: &MySqlPool, id: u64) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE api_tokens
+SET last_used = ?
+WHERE id = ?
+        "#,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn revoke(pool: &MySqlPool, user_id: u64, public_id: Uuid) -> Result<(), EntityError> {
+    let public_id_bytes = public_id.into_bytes();
+
+    let result = sqlx::query!(
+        r#"
+UPDATE api_tokens
+SET is_revoked = ?, updated = ?
+WHERE public_id = ? AND user_id = ? AND is_revoked = 0
+        "#,
+        1,
+        Utc::now().naive_utc(),
+        &public_id_bytes[..],
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    match result.rows_affected() {
+        0 => Err(EntityError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+fn format_scopes(scopes: &[ApiScope]) -> String {
+    scopes
+        .iter()
+        .map(|scope| scope.as_str())
+        .collect::<Vec<_>>()
+        .join(SCOPE_SEPARATOR)
+}
+
+// Unknown scopes are dropped rather than failing, so a scope can be retired without breaking old rows
+fn parse_scopes(scopes: &str) -> Vec<ApiScope> {
+    scopes
+        .split(SCOPE_SEPARATOR)
+        .filter_map(|scope| ApiScope::from_str(scope.trim()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_round_trip() {
+        let scopes = [ApiScope::Read, ApiScope::Comment];
+
+        assert_eq!(format_scopes(&scopes), "read,comment");
+        assert_eq!(parse_scopes("read,comment"), scopes);
+        assert_eq!(
+            parse_scopes("read, admin,post"),
+            [ApiScope::Read, ApiScope::Post]
+        );
+        assert!(parse_scopes("").is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::{ApiScope, ApiToken};
+
+#[async_trait]
+pub trait ApiTokenStore: Send + Sync + Clone {
+    /// Creates a token for the user, returning it along with the plaintext token.
+    /// Only a hash of the token is stored, so it can't be shown again.
+    async fn insert(
+        &self,
+        user_id: u64,
+        name: &str,
+        scopes: &[ApiScope],
+    ) -> Result<(ApiToken, String), EntityError>;
+
+    /// Looks up an unrevoked token by its plaintext value and records that it was used.
+    async fn get_by_token(&self, token: &str) -> Result<ApiToken, EntityError>;
+
+    /// The user's unrevoked tokens, newest first.
+    async fn get_by_user_id(&self, user_id: u64) -> Result<Vec<ApiToken>, EntityError>;
+
+    /// Fails with `NotFound` if the token doesn't exist, belongs to someone else or was already revoked.
+    async fn revoke(&self, user_id: u64, public_id: &str) -> Result<(), EntityError>;
+}
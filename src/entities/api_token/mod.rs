@@ -0,0 +1,9 @@
+mod api_token;
+mod api_token_sql;
+mod api_token_store;
+
+pub use api_token::ApiScope;
+pub use api_token::ApiToken;
+pub use api_token_sql::SqlApiTokenStore;
+pub use api_token_sql::MAX_TOKEN_NAME_LENGTH;
+pub use api_token_store::ApiTokenStore;
@@ -0,0 +1,11 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A user hiding everything another user posts from themselves.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Block {
+    pub id: u64,
+    pub user_id: u64,
+    pub blocked_id: u64,
+    pub created: DateTime<Utc>,
+}
@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{Block, BlockStore};
+
+#[derive(Clone)]
+pub struct SqlBlockStore {
+    pool: MySqlPool,
+}
+
+impl SqlBlockStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct BlockEntity {
+    pub id: u64,
+    pub user_id: u64,
+    pub blocked_id: u64,
+    pub created: NaiveDateTime,
+}
+
+impl From<BlockEntity> for Block {
+    fn from(block_entity: BlockEntity) -> Self {
+        Self {
+            id: block_entity.id,
+            user_id: block_entity.user_id,
+            blocked_id: block_entity.blocked_id,
+            created: Utc.from_utc_datetime(&block_entity.created),
+        }
+    }
+}
+
+#[async_trait]
+impl BlockStore for SqlBlockStore {
+    async fn insert(&self, user_id: u64, blocked_id: u64) -> Result<Block, EntityError> {
+        let id = insert(&self.pool, user_id, blocked_id).await?;
+
+        Ok(Block::from(get_by_id(&self.pool, id).await?))
+    }
+
+    async fn delete(&self, user_id: u64, blocked_id: u64) -> Result<(), EntityError> {
+        delete(&self.pool, user_id, blocked_id).await
+    }
+
+    async fn get_by_user_id(&self, user_id: u64) -> Result<Vec<Block>, EntityError> {
+        Ok(get_by_user_id(&self.pool, user_id)
+            .await?
+            .into_iter()
+            .map(Block::from)
+            .collect())
+    }
+
+    async fn is_blocked(&self, user_id: u64, blocked_id: u64) -> Result<bool, EntityError> {
+        is_blocked(&self.pool, user_id, blocked_id).await
+    }
+}
+
+async fn insert(pool: &MySqlPool, user_id: u64, blocked_id: u64) -> Result<u64, EntityError> {
+    if user_id == blocked_id {
+        return Err(EntityError::InvalidInput(
+            "blocked_id",
+            "can't block yourself",
+        ));
+    }
+
+    let block_id = sqlx::query!(
+        r#"
+INSERT INTO blocks (user_id, blocked_id, created)
+VALUES (?, ?, ?)
+        "#,
+        user_id,
+        blocked_id,
+        Utc::now().naive_utc()
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(block_id)
+}
+
+async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<BlockEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        BlockEntity,
+        r#"
+SELECT *
+FROM blocks
+WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn delete(pool: &MySqlPool, user_id: u64, blocked_id: u64) -> Result<(), EntityError> {
+    let result = sqlx::query!(
+        r#"
+DELETE FROM blocks
+WHERE user_id = ? AND blocked_id = ?
+        "#,
+        user_id,
+        blocked_id
+    )
+    .execute(pool)
+    .await?;
+
+    match result.rows_affected() {
+        0 => Err(EntityError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+async fn get_by_user_id(pool: &MySqlPool, user_id: u64) -> Result<Vec<BlockEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        BlockEntity,
+        r#"
+SELECT *
+FROM blocks
+WHERE user_id = ?
+ORDER BY id DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn is_blocked(pool: &MySqlPool, user_id: u64, blocked_id: u64) -> Result<bool, EntityError> {
+    let count = sqlx::query!(
+        r#"
+SELECT COUNT(id) as count
+FROM blocks
+WHERE user_id = ? AND blocked_id = ?
+        "#,
+        user_id,
+        blocked_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.count > 0)
+}
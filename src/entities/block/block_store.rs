@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::Block;
+
+#[async_trait]
+pub trait BlockStore: Send + Sync + Clone {
+    /// Fails with `DuplicateKey` if the user already blocked them.
+    async fn insert(&self, user_id: u64, blocked_id: u64) -> Result<Block, EntityError>;
+
+    /// Fails with `NotFound` if the user hadn't blocked them.
+    async fn delete(&self, user_id: u64, blocked_id: u64) -> Result<(), EntityError>;
+
+    /// Everyone the user blocked, most recent first.
+    async fn get_by_user_id(&self, user_id: u64) -> Result<Vec<Block>, EntityError>;
+
+    async fn is_blocked(&self, user_id: u64, blocked_id: u64) -> Result<bool, EntityError>;
+}
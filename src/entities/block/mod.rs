@@ -0,0 +1,7 @@
+mod block;
+mod block_sql;
+mod block_store;
+
+pub use block::Block;
+pub use block_sql::SqlBlockStore;
+pub use block_store::BlockStore;
@@ -3,13 +3,48 @@ use chrono::{DateTime, Duration, Utc};
 use dashmap::{mapref::one::Ref, DashMap};
 use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
-use std::future::Future;
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::entities::EntityError;
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct Cache {
     map: DashMap<String, Vec<u8>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// A snapshot of how much a cache holds and how often it has been useful since startup.
+/// Expired values count as entries until they're replaced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// The share of lookups answered from the cache, as a percentage.
+    pub fn hit_rate(&self) -> Option<f64> {
+        match self.hits + self.misses {
+            0 => None,
+            lookups => Some(self.hits as f64 * 100.0 / lookups as f64),
+        }
+    }
+}
+
+impl Clone for Cache {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+            hits: AtomicU64::new(self.hits.load(Ordering::Relaxed)),
+            misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
@@ -22,9 +57,28 @@ impl Cache {
     pub fn new() -> Self {
         Self {
             map: DashMap::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.map.len(),
+            bytes: self.map.iter().map(|entry| entry.value().len()).sum(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_lookup(&self, is_hit: bool) {
+        let counter = match is_hit {
+            true => &self.hits,
+            false => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
     fn insert<T>(
         &self,
         key: String,
@@ -47,8 +101,10 @@ impl Cache {
     {
         // TODO: handle error
         let encoded = self.map.get(&key);
+        let value = decode_and_unwrap_ref(encoded)?;
+        self.record_lookup(value.is_some());
 
-        decode_and_unwrap_ref(encoded)
+        Ok(value)
     }
 
     // TODO: use in invalidation
@@ -98,6 +154,48 @@ impl Cache {
         }
     }
 
+    /// Like `get_cached` for many ids at once, fetching every miss from the source in one call.
+    /// Values come back in no particular order, and ids the source doesn't have are left out.
+    pub async fn get_many_cached<T, Fut, F>(
+        &self,
+        ids: &[u64],
+        key_builder: fn(u64) -> String,
+        get_source: F,
+        keys_builder: fn(&T) -> Vec<String>,
+        expiry: Option<Duration>,
+    ) -> Result<Vec<T>, EntityError>
+    where
+        for<'a> T: Deserialize<'a> + Serialize + PartialEq + Clone + std::fmt::Debug,
+        Fut: Future<Output = Result<Vec<T>, EntityError>> + Sized,
+        F: FnOnce(Vec<u64>) -> Fut,
+    {
+        let mut values = vec![];
+        let mut missing = vec![];
+        for id in ids {
+            match self.get(key_builder(*id))? {
+                Some(value) => values.push(value),
+                None => missing.push(*id),
+            }
+        }
+        info!("Got {} from cache, {} missing", values.len(), missing.len());
+
+        if missing.is_empty() {
+            return Ok(values);
+        }
+
+        for source_value in get_source(missing).await? {
+            for key in keys_builder(&source_value) {
+                match self.insert(key, source_value.clone(), expiry) {
+                    Ok(_) => (),
+                    Err(e) => error!("Error adding value to cache. Error: {:?}", e),
+                }
+            }
+            values.push(source_value);
+        }
+
+        Ok(values)
+    }
+
     pub async fn insert_cached<T, Fut, F>(
         &self,
         insert_source: F,
@@ -193,3 +291,31 @@ where
         None => Ok(Some(wrapped.value)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_stats() {
+        let cache = Cache::new();
+        assert_eq!(cache.stats().hit_rate(), None);
+
+        for _ in 0..4 {
+            let value = cache
+                .get_cached(
+                    "id:1".to_owned(),
+                    || async { Ok(1u64) },
+                    |_| vec!["id:1".to_owned()],
+                    None,
+                )
+                .await;
+            assert_eq!(value.unwrap(), 1);
+        }
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 3, 1));
+        assert!(stats.bytes > 0);
+        assert_eq!(stats.hit_rate(), Some(75.0));
+    }
+}
@@ -1,3 +1,3 @@
 mod cache;
 
-pub use cache::Cache;
+pub use cache::{Cache, CacheStats};
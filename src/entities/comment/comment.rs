@@ -9,6 +9,7 @@ pub struct Comment {
     pub post_id: u64,
     pub parent_id: Option<u64>,
     pub content_id: u64,
+    pub is_removed: bool,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
 }
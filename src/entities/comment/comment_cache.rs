@@ -1,7 +1,10 @@
 use async_trait::async_trait;
 use chrono::Duration;
 
-use crate::entities::{cache::Cache, EntityError};
+use crate::entities::{
+    cache::{Cache, CacheStats},
+    EntityError,
+};
 
 use super::{Comment, CommentStore};
 
@@ -21,6 +24,10 @@ where
     pub fn new(cache: Cache, source: T) -> Self {
         Self { cache, source }
     }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
 }
 
 // TODO: set cache expiry, invalidate counts + collections, etc.
@@ -36,12 +43,13 @@ where
         post_id: &u64,
         parent_id: &Option<u64>,
         content: &str,
+        is_held: bool,
     ) -> Result<Comment, EntityError> {
         self.cache
             .insert_cached(
                 || async {
                     self.source
-                        .insert(author_id, post_id, parent_id, content)
+                        .insert(author_id, post_id, parent_id, content, is_held)
                         .await
                 },
                 build_keys,
@@ -74,6 +82,23 @@ where
             .await
     }
 
+    // Cached threads keep showing the comment until they expire a minute later
+    async fn set_removed(&self, id: u64, is_removed: bool) -> Result<Comment, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.set_removed(id, is_removed).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn publish(&self, id: u64) -> Result<Comment, EntityError> {
+        self.cache
+            .insert_cached(|| async { self.source.publish(id).await }, build_keys, None)
+            .await
+    }
+
     async fn get_count_by_post_id(&self, post_id: &u64) -> Result<i64, EntityError> {
         let key = format!("count_by_post_id:{}", post_id);
         self.cache
@@ -86,6 +111,22 @@ where
             .await
     }
 
+    async fn get_recent_by_post_id(
+        &self,
+        post_id: u64,
+        count: u8,
+    ) -> Result<Vec<Comment>, EntityError> {
+        let key = format!("recent_by_post_id:{}:{}", post_id, count);
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_recent_by_post_id(post_id, count).await },
+                |_| vec![key],
+                Some(Duration::seconds(60)),
+            )
+            .await
+    }
+
     async fn get_by_post_id_parent_id(
         &self,
         post_id: u64,
@@ -1,10 +1,20 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use chrono::{NaiveDateTime, TimeZone, Utc};
+use log::error;
 use sqlx::MySqlPool;
 use uuid::Uuid;
 
 use crate::entities::{
-    content::ContentStore, entity_stores::CachedSqlContentStore, utils, EntityError,
+    block::{BlockStore, SqlBlockStore},
+    content::ContentStore,
+    entity_stores::{CachedSqlContentStore, CachedSqlUserStore},
+    notification::{NotificationStore, SqlNotificationStore},
+    user::{User, UserStore},
+    utils,
+    webhook::{SqlWebhookStore, WebhookEvent, WebhookStore},
+    EntityError,
 };
 
 use super::{comment_store::CommentStore, Comment};
@@ -15,18 +25,90 @@ const MAX_COMMENT_LENGTH: usize = 100_000;
 #[derive(Clone)]
 pub struct SqlCommentStore {
     pool: MySqlPool,
+    block_store: Arc<SqlBlockStore>,
     content_store: CachedSqlContentStore,
+    notification_store: Arc<SqlNotificationStore>,
+    user_store: CachedSqlUserStore,
+    webhook_store: Arc<SqlWebhookStore>,
 }
 
 impl SqlCommentStore {
-    pub fn new(pool: MySqlPool, content_store: CachedSqlContentStore) -> Self {
+    pub fn new(
+        pool: MySqlPool,
+        block_store: Arc<SqlBlockStore>,
+        content_store: CachedSqlContentStore,
+        notification_store: Arc<SqlNotificationStore>,
+        user_store: CachedSqlUserStore,
+        webhook_store: Arc<SqlWebhookStore>,
+    ) -> Self {
         Self {
             pool,
+            block_store,
             content_store,
+            notification_store,
+            user_store,
+            webhook_store,
+        }
+    }
+
+    // Tells the author of the post or comment being replied to
+    async fn notify_reply(&self, comment: &Comment) -> Result<(), EntityError> {
+        let recipient_id = match comment.parent_id {
+            Some(parent_id) => get_by_id(&self.pool, parent_id).await?.author_id,
+            None => get_post_author_id(&self.pool, comment.post_id).await?,
+        };
+        let is_blocked = self
+            .block_store
+            .is_blocked(recipient_id, comment.author_id)
+            .await?;
+        if !is_notified(comment.author_id, recipient_id, is_blocked) {
+            return Ok(());
+        }
+
+        self.notification_store
+            .insert(recipient_id, comment.id)
+            .await?;
+
+        Ok(())
+    }
+
+    // The comment is already posted, so failing to notify or queue webhooks is only logged
+    async fn announce(&self, comment: &Comment, is_held: bool) {
+        match self.user_store.get_by_id(comment.author_id).await {
+            Ok(author) if !is_announced(&author, is_held) => return,
+            Ok(_) => {}
+            Err(e) => {
+                error!("Error getting comment {}'s author: {:?}", comment.id, e);
+                return;
+            }
+        }
+        if let Err(e) = self.notify_reply(comment).await {
+            error!("Error notifying of reply {}: {:?}", comment.id, e);
+        }
+        if let Err(e) = self
+            .webhook_store
+            .enqueue(WebhookEvent::CommentCreated, comment.id)
+            .await
+        {
+            error!(
+                "Error queueing webhooks for comment {}: {:?}",
+                comment.id, e
+            );
         }
     }
 }
 
+// Held comments wait until they're published, and no one else is meant to see a shadowbanned
+// author's comments at all
+fn is_announced(author: &User, is_held: bool) -> bool {
+    !is_held && !author.is_shadowbanned
+}
+
+// Nobody is told about replying to themselves, or about replies from someone they blocked
+fn is_notified(author_id: u64, recipient_id: u64, is_blocked: bool) -> bool {
+    author_id != recipient_id && !is_blocked
+}
+
 #[derive(Clone, Debug, sqlx::FromRow)]
 pub struct CommentEntity {
     pub id: u64,
@@ -35,6 +117,7 @@ pub struct CommentEntity {
     pub post_id: u64,
     pub parent_id: Option<u64>,
     pub content_id: u64,
+    pub is_removed: i8,
     pub created: NaiveDateTime,
     pub updated: NaiveDateTime,
 }
@@ -48,6 +131,7 @@ impl From<CommentEntity> for Comment {
             post_id: comment_entity.post_id,
             parent_id: comment_entity.parent_id,
             content_id: comment_entity.content_id,
+            is_removed: comment_entity.is_removed > 0,
             created: Utc.from_utc_datetime(&comment_entity.created),
             updated: Utc.from_utc_datetime(&comment_entity.updated),
         }
@@ -62,6 +146,7 @@ impl CommentStore for SqlCommentStore {
         post_id: &u64,
         parent_id: &Option<u64>,
         content: &str,
+        is_held: bool,
     ) -> Result<Comment, EntityError> {
         let comment_id = insert(
             &self.pool,
@@ -70,10 +155,14 @@ impl CommentStore for SqlCommentStore {
             post_id,
             parent_id,
             content,
+            is_held,
         )
         .await?;
+        let comment = self.get_by_id(comment_id).await?;
+
+        self.announce(&comment, is_held).await;
 
-        Ok(self.get_by_id(comment_id).await?)
+        Ok(comment)
     }
 
     async fn get_by_id(&self, id: u64) -> Result<Comment, EntityError> {
@@ -88,10 +177,36 @@ impl CommentStore for SqlCommentStore {
         ))
     }
 
+    async fn set_removed(&self, id: u64, is_removed: bool) -> Result<Comment, EntityError> {
+        set_removed(&self.pool, id, is_removed).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn publish(&self, id: u64) -> Result<Comment, EntityError> {
+        set_removed(&self.pool, id, false).await?;
+        let comment = self.get_by_id(id).await?;
+        self.announce(&comment, false).await;
+
+        Ok(comment)
+    }
+
     async fn get_count_by_post_id(&self, post_id: &u64) -> Result<i64, EntityError> {
         Ok(get_count_by_post_id(&self.pool, post_id).await?)
     }
 
+    async fn get_recent_by_post_id(
+        &self,
+        post_id: u64,
+        count: u8,
+    ) -> Result<Vec<Comment>, EntityError> {
+        Ok(get_recent_by_post_id(&self.pool, post_id, count)
+            .await?
+            .into_iter()
+            .map(Comment::from)
+            .collect())
+    }
+
     async fn get_by_post_id_parent_id(
         &self,
         post_id: u64,
@@ -119,6 +234,7 @@ async fn insert(
     post_id: &u64,
     parent_id: &Option<u64>,
     content: &str,
+    is_held: bool,
 ) -> Result<u64, EntityError> {
     // TODO: verify if author is a valid user?
 
@@ -143,15 +259,16 @@ async fn insert(
     let comment_id = sqlx::query!(
         r#"
 INSERT INTO comments
-    (public_id, author_id, post_id, parent_id, content_id, created, updated)
+    (public_id, author_id, post_id, parent_id, content_id, is_removed, created, updated)
 VALUES
-    (?, ?, ?, ?, ?, ?, ?)
+    (?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         &public_id[..],
         author_id,
         post_id,
         parent_id,
         content_id.id,
+        is_held,
         created,
         created
     )
@@ -176,6 +293,38 @@ WHERE id = ?
     .await?)
 }
 
+async fn set_removed(pool: &MySqlPool, id: u64, is_removed: bool) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE comments
+SET is_removed = ?, updated = ?
+WHERE id = ?
+        "#,
+        is_removed,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn get_post_author_id(pool: &MySqlPool, post_id: u64) -> Result<u64, EntityError> {
+    let post = sqlx::query!(
+        r#"
+SELECT author_id
+FROM posts
+WHERE id = ?
+        "#,
+        post_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(post.author_id)
+}
+
 async fn get_by_public_id(pool: &MySqlPool, public_id: Uuid) -> Result<CommentEntity, EntityError> {
     let public_id_bytes = public_id.into_bytes();
     let comment_entity = sqlx::query_as!(
@@ -200,6 +349,7 @@ SELECT
     COUNT(id) as count
 FROM comments
 WHERE post_id = ?
+    AND author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1)
         "#,
         post_id
     )
@@ -209,6 +359,28 @@ WHERE post_id = ?
     Ok(count.count)
 }
 
+async fn get_recent_by_post_id(
+    pool: &MySqlPool,
+    post_id: u64,
+    count: u8,
+) -> Result<Vec<CommentEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        CommentEntity,
+        r#"
+SELECT *
+FROM `comments`
+WHERE `post_id` = ?
+    AND `author_id` NOT IN (SELECT `id` FROM `users` WHERE `is_shadowbanned` = 1)
+ORDER BY `id` DESC
+LIMIT ?
+        "#,
+        post_id,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
 async fn get_by_post_id_parent_id(
     pool: &MySqlPool,
     post_id: u64,
@@ -307,3 +479,44 @@ LIMIT ?
 
     Ok(comment_entities)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::entities::user::Role;
+
+    fn author(is_shadowbanned: bool) -> User {
+        let now = Utc::now();
+        User {
+            id: 1,
+            public_id: "abc".to_owned(),
+            name: "sasquatch".to_owned(),
+            email_id: 1,
+            is_email_verified: true,
+            role: Role::User,
+            is_banned: false,
+            is_shadowbanned,
+            must_reset_password: false,
+            is_deleted: false,
+            created: now,
+            updated: now,
+        }
+    }
+
+    #[test]
+    fn test_is_announced() {
+        assert!(is_announced(&author(false), false));
+        assert!(!is_announced(&author(false), true));
+        assert!(!is_announced(&author(true), false));
+        assert!(!is_announced(&author(true), true));
+    }
+
+    #[test]
+    fn test_is_notified() {
+        assert!(is_notified(1, 2, false));
+        assert!(!is_notified(1, 1, false));
+        assert!(!is_notified(1, 2, true));
+    }
+}
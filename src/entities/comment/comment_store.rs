@@ -6,20 +6,40 @@ use super::Comment;
 
 #[async_trait]
 pub trait CommentStore: Send + Sync + Clone {
+    /// Held comments are saved removed, and don't notify anyone or queue webhooks until they're
+    /// published.
     async fn insert(
         &self,
         author_id: &u64,
         post_id: &u64,
         parent_id: &Option<u64>,
         content: &str,
+        is_held: bool,
     ) -> Result<Comment, EntityError>;
 
     async fn get_by_id(&self, id: u64) -> Result<Comment, EntityError>;
 
     async fn get_by_public_id(&self, public_id: &str) -> Result<Comment, EntityError>;
 
+    /// Removed comments keep their place in the thread so replies still make sense, but their
+    /// content is no longer shown.
+    async fn set_removed(&self, id: u64, is_removed: bool) -> Result<Comment, EntityError>;
+
+    /// Publishes a held comment, sending the notification and webhooks it skipped when it was
+    /// posted.
+    async fn publish(&self, id: u64) -> Result<Comment, EntityError>;
+
+    /// Leaves out shadowbanned authors' comments.
     async fn get_count_by_post_id(&self, post_id: &u64) -> Result<i64, EntityError>;
 
+    /// A post's comments at any depth, newest first, leaving out shadowbanned authors' comments.
+    async fn get_recent_by_post_id(
+        &self,
+        post_id: u64,
+        count: u8,
+    ) -> Result<Vec<Comment>, EntityError>;
+
+    /// Includes shadowbanned authors' comments, since who gets to see them depends on the reader.
     async fn get_by_post_id_parent_id(
         &self,
         post_id: u64,
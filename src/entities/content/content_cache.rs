@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 
-use crate::entities::{cache::Cache, utils, EntityError};
+use crate::entities::{
+    cache::{Cache, CacheStats},
+    utils, EntityError,
+};
 
 use super::{content_sql::hash_body, Content, ContentStore};
 
@@ -20,6 +23,10 @@ where
     pub fn new(cache: Cache, source: T) -> Self {
         Self { cache, source }
     }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
 }
 
 #[async_trait]
@@ -68,6 +75,18 @@ where
             .await
     }
 
+    async fn get_by_ids(&self, ids: &[u64]) -> Result<Vec<Content>, EntityError> {
+        self.cache
+            .get_many_cached(
+                ids,
+                build_id_key,
+                |missing| async move { self.source.get_by_ids(&missing).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
     async fn get_by_body(&self, body: &str) -> Result<Content, EntityError> {
         let body_hash = hash_body(body)?;
         let key = build_body_hash_key(body_hash);
@@ -2,10 +2,13 @@ use async_trait::async_trait;
 use chrono::{NaiveDateTime, TimeZone, Utc};
 use log::info;
 use maplit::{hashmap, hashset};
-use pulldown_cmark::{html, Options, Parser};
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag};
 use sqlx::MySqlPool;
 
-use crate::entities::{utils, EntityError};
+use crate::{
+    entities::{utils, EntityError},
+    highlight,
+};
 
 use super::{Content, ContentStore};
 
@@ -27,16 +30,22 @@ impl SqlContentStore {
 pub struct ContentEntity {
     pub id: u64,
     pub body: String,
+    // None for contents written before the HTML was stored with them
+    pub body_html: Option<String>,
     pub body_hash: Vec<u8>,
     pub created: NaiveDateTime,
 }
 
 impl From<ContentEntity> for Content {
     fn from(content_entity: ContentEntity) -> Self {
+        let body_html = content_entity
+            .body_html
+            .unwrap_or_else(|| render_safe_html(&content_entity.body));
+
         Self {
             id: content_entity.id,
-            body: content_entity.body.clone(),
-            body_html: render_safe_html(&content_entity.body),
+            body: content_entity.body,
+            body_html,
             body_hash: content_entity.body_hash,
             created: Utc.from_utc_datetime(&content_entity.created),
         }
@@ -57,6 +66,14 @@ impl ContentStore for SqlContentStore {
         Ok(Content::from(get_by_id(&self.pool, id).await?))
     }
 
+    async fn get_by_ids(&self, ids: &[u64]) -> Result<Vec<Content>, EntityError> {
+        Ok(get_by_ids(&self.pool, ids)
+            .await?
+            .into_iter()
+            .map(Content::from)
+            .collect())
+    }
+
     async fn get_by_body(&self, body: &str) -> Result<Content, EntityError> {
         Ok(Content::from(get_by_body(&self.pool, body).await?))
     }
@@ -92,6 +109,23 @@ WHERE id = ?
     .await?)
 }
 
+async fn get_by_ids(pool: &MySqlPool, ids: &[u64]) -> Result<Vec<ContentEntity>, EntityError> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT * FROM contents WHERE id IN ({})",
+        utils::placeholders(ids.len())
+    );
+    let mut query = sqlx::query_as::<_, ContentEntity>(&sql);
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    Ok(query.fetch_all(pool).await?)
+}
+
 async fn get_by_body(pool: &MySqlPool, body: &str) -> Result<ContentEntity, EntityError> {
     let body_hash = hash_body(body)?;
     try_get_by_body_hash(pool, &body_hash).await
@@ -102,13 +136,16 @@ async fn insert_by_body_hash(
     body: &str,
     body_hash: &Vec<u8>,
 ) -> Result<ContentEntity, EntityError> {
+    // Rendered once here rather than every time it's read, highlighting code isn't cheap
+    let body_html = render_safe_html(body);
     let created = Utc::now().naive_utc();
     let content_id = sqlx::query!(
         r#"
-INSERT INTO contents (body, body_hash, created)
-VALUES (?, ?, ?)
+INSERT INTO contents (body, body_html, body_hash, created)
+VALUES (?, ?, ?, ?)
         "#,
         body,
+        body_html,
         body_hash,
         created
     )
@@ -119,6 +156,7 @@ VALUES (?, ?, ?)
     Ok(ContentEntity {
         id: content_id,
         body: body.to_owned(),
+        body_html: Some(body_html),
         body_hash: body_hash.clone(),
         created,
     })
@@ -155,8 +193,29 @@ fn render_safe_html(body: &str) -> String {
     options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
     let parser = Parser::new_ext(body, options);
 
+    // Code blocks are highlighted here, so they're readable without JavaScript and in feeds
+    let mut code_block: Option<(String, String)> = None;
+    let events = parser.filter_map(|event| match (event, &mut code_block) {
+        (Event::Start(Tag::CodeBlock(kind)), _) => {
+            let info = match kind {
+                CodeBlockKind::Fenced(info) => info.to_string(),
+                CodeBlockKind::Indented => String::new(),
+            };
+            code_block = Some((info, String::new()));
+            None
+        }
+        (Event::Text(text), Some((_, code))) => {
+            code.push_str(&text);
+            None
+        }
+        (Event::End(Tag::CodeBlock(_)), _) => code_block
+            .take()
+            .map(|(info, code)| Event::Html(highlight::code_block(&info, &code).into())),
+        (event, _) => Some(event),
+    });
+
     let mut unsafe_html = String::new();
-    html::push_html(&mut unsafe_html, parser);
+    html::push_html(&mut unsafe_html, events);
 
     let mut builder = ammonia::Builder::new();
     let tag_blocklist = hashset!["script", "style"];
@@ -181,6 +240,7 @@ fn render_safe_html(body: &str) -> String {
         "ol",
         "p",
         "pre",
+        "span",
         "strong",
         "sup",
         "table",
@@ -206,12 +266,34 @@ fn render_safe_html(body: &str) -> String {
         "h5" => hashset!["id", "class"],
         "h6" => hashset!["id", "class"],
     ];
+    let allowed_classes = hashmap![
+        "span" => highlight::TOKEN_CLASSES.into_iter().collect(),
+    ];
     let cleaner = builder
         .tags(tags)
         .tag_attributes(tag_attributes)
+        .allowed_classes(allowed_classes)
         .clean_content_tags(tag_blocklist)
         .link_rel(Some("noopener noreferrer nofollow"));
     let safe_html = cleaner.clean(&unsafe_html).to_string();
     info!("html: {}", safe_html);
     safe_html
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_safe_html_highlights_code() {
+        let html = render_safe_html(
+            "```rust\nfn main() {}\n```\n\n<span class=\"hl-hidden\" onclick=\"x()\">hi</span>",
+        );
+
+        assert!(html.starts_with("<pre><code class=\"language-rust\">"));
+        assert!(html.contains("<span class=\"hl-storage hl-function\">fn</span>"));
+        assert!(html.contains("<span class=\"hl-entity hl-name hl-function\">main</span>"));
+        assert!(!html.contains("hl-rust"));
+        assert!(html.contains("<span class=\"\">hi</span>"));
+    }
+}
@@ -12,5 +12,8 @@ pub trait ContentStore: Send + Sync + Clone {
 
     async fn get_by_id(&self, id: u64) -> Result<Content, EntityError>;
 
+    /// Contents in no particular order, ids that don't exist are left out.
+    async fn get_by_ids(&self, ids: &[u64]) -> Result<Vec<Content>, EntityError>;
+
     async fn get_by_body(&self, body: &str) -> Result<Content, EntityError>;
 }
@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 
-use crate::entities::{cache::Cache, EntityError};
+use crate::entities::{
+    cache::{Cache, CacheStats},
+    EntityError,
+};
 
 use super::{Email, EmailStore};
 
@@ -20,6 +23,10 @@ where
     pub fn new(cache: Cache, source: T) -> Self {
         Self { cache, source }
     }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,120 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How often a user is emailed about replies.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailFrequency {
+    Immediate,
+    #[default]
+    Daily,
+    Weekly,
+    Never,
+}
+
+impl EmailFrequency {
+    pub const ALL: [EmailFrequency; 4] = [
+        EmailFrequency::Immediate,
+        EmailFrequency::Daily,
+        EmailFrequency::Weekly,
+        EmailFrequency::Never,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailFrequency::Immediate => "immediate",
+            EmailFrequency::Daily => "daily",
+            EmailFrequency::Weekly => "weekly",
+            EmailFrequency::Never => "never",
+        }
+    }
+
+    /// The least time between two emails, `None` if they're never sent.
+    pub fn interval(&self) -> Option<Duration> {
+        match self {
+            EmailFrequency::Immediate => Some(Duration::zero()),
+            EmailFrequency::Daily => Some(Duration::days(1)),
+            EmailFrequency::Weekly => Some(Duration::weeks(1)),
+            EmailFrequency::Never => None,
+        }
+    }
+}
+
+impl fmt::Display for EmailFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for EmailFrequency {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<EmailFrequency, Self::Err> {
+        match input {
+            "immediate" => Ok(EmailFrequency::Immediate),
+            "daily" => Ok(EmailFrequency::Daily),
+            "weekly" => Ok(EmailFrequency::Weekly),
+            "never" => Ok(EmailFrequency::Never),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EmailPreference {
+    pub id: u64,
+    pub user_id: u64,
+    pub frequency: EmailFrequency,
+    pub unsubscribe_token: String,
+    pub last_notification_id: u64,
+    pub last_sent: Option<DateTime<Utc>>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+impl EmailPreference {
+    /// Whether enough time has passed since the last email for another one.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match (self.frequency.interval(), self.last_sent) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(interval), Some(last_sent)) => now - last_sent >= interval,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_due() {
+        let now = Utc::now();
+        let mut preference = EmailPreference {
+            id: 1,
+            user_id: 1,
+            frequency: EmailFrequency::Daily,
+            unsubscribe_token: "token".to_owned(),
+            last_notification_id: 0,
+            last_sent: None,
+            created: now,
+            updated: now,
+        };
+        assert!(preference.is_due(now));
+
+        preference.last_sent = Some(now - Duration::hours(23));
+        assert!(!preference.is_due(now));
+        preference.last_sent = Some(now - Duration::hours(24));
+        assert!(preference.is_due(now));
+
+        preference.frequency = EmailFrequency::Immediate;
+        preference.last_sent = Some(now);
+        assert!(preference.is_due(now));
+
+        preference.frequency = EmailFrequency::Never;
+        preference.last_sent = None;
+        assert!(!preference.is_due(now));
+    }
+}
@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use log::warn;
+use sqlx::MySqlPool;
+
+use crate::entities::{utils, EntityError};
+
+use super::{EmailFrequency, EmailPreference, EmailPreferenceStore};
+
+#[derive(Clone)]
+pub struct SqlEmailPreferenceStore {
+    pool: MySqlPool,
+}
+
+impl SqlEmailPreferenceStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct EmailPreferenceEntity {
+    pub id: u64,
+    pub user_id: u64,
+    pub frequency: String,
+    pub unsubscribe_token: String,
+    pub last_notification_id: u64,
+    pub last_sent: Option<NaiveDateTime>,
+    pub created: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+impl From<EmailPreferenceEntity> for EmailPreference {
+    fn from(email_preference_entity: EmailPreferenceEntity) -> Self {
+        Self {
+            id: email_preference_entity.id,
+            user_id: email_preference_entity.user_id,
+            frequency: parse_frequency(&email_preference_entity.frequency),
+            unsubscribe_token: email_preference_entity.unsubscribe_token,
+            last_notification_id: email_preference_entity.last_notification_id,
+            last_sent: email_preference_entity
+                .last_sent
+                .map(|last_sent| Utc.from_utc_datetime(&last_sent)),
+            created: Utc.from_utc_datetime(&email_preference_entity.created),
+            updated: Utc.from_utc_datetime(&email_preference_entity.updated),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailPreferenceStore for SqlEmailPreferenceStore {
+    async fn get_by_user_id(&self, user_id: u64) -> Result<EmailPreference, EntityError> {
+        match get_by_user_id(&self.pool, user_id).await {
+            Ok(email_preference) => return Ok(EmailPreference::from(email_preference)),
+            Err(EntityError::NotFound) => (),
+            Err(e) => return Err(e),
+        }
+
+        match insert(&self.pool, user_id).await {
+            // Someone else created them first, which is just as good
+            Ok(_) | Err(EntityError::DuplicateKey) => (),
+            Err(e) => return Err(e),
+        }
+
+        Ok(EmailPreference::from(
+            get_by_user_id(&self.pool, user_id).await?,
+        ))
+    }
+
+    async fn get_by_unsubscribe_token(&self, token: &str) -> Result<EmailPreference, EntityError> {
+        Ok(EmailPreference::from(
+            get_by_unsubscribe_token(&self.pool, token).await?,
+        ))
+    }
+
+    async fn set_frequency(
+        &self,
+        user_id: u64,
+        frequency: EmailFrequency,
+    ) -> Result<EmailPreference, EntityError> {
+        // Makes sure there's a row to update
+        self.get_by_user_id(user_id).await?;
+        set_frequency(&self.pool, user_id, frequency).await?;
+
+        Ok(EmailPreference::from(
+            get_by_user_id(&self.pool, user_id).await?,
+        ))
+    }
+
+    async fn get_pending_user_ids(&self) -> Result<Vec<u64>, EntityError> {
+        get_pending_user_ids(&self.pool).await
+    }
+
+    async fn claim(
+        &self,
+        user_id: u64,
+        from_notification_id: u64,
+        to_notification_id: u64,
+    ) -> Result<bool, EntityError> {
+        claim(
+            &self.pool,
+            user_id,
+            from_notification_id,
+            to_notification_id,
+        )
+        .await
+    }
+}
+
+async fn insert(pool: &MySqlPool, user_id: u64) -> Result<u64, EntityError> {
+    let unsubscribe_token = utils::generate_secret_token();
+    let created = Utc::now().naive_utc();
+
+    let email_preference_id = sqlx::query!(
+        r#"
+INSERT INTO email_preferences (user_id, frequency, unsubscribe_token, last_notification_id, last_sent, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+        user_id,
+        EmailFrequency::default().as_str(),
+        unsubscribe_token,
+        0,
+        Option::<NaiveDateTime>::None,
+        created,
+        created
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(email_preference_id)
+}
+
+async fn get_by_user_id(
+    pool: &MySqlPool,
+    user_id: u64,
+) -> Result<EmailPreferenceEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        EmailPreferenceEntity,
+        r#"
+SELECT *
+FROM email_preferences
+WHERE user_id = ?
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_by_unsubscribe_token(
+    pool: &MySqlPool,
+    token: &str,
+) -> Result<EmailPreferenceEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        EmailPreferenceEntity,
+        r#"
+SELECT *
+FROM email_preferences
+WHERE unsubscribe_token = ?
+        "#,
+        token
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn set_frequency(
+    pool: &MySqlPool,
+    user_id: u64,
+    frequency: EmailFrequency,
+) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE email_preferences
+SET frequency = ?, updated = ?
+WHERE user_id = ?
+        "#,
+        frequency.as_str(),
+        Utc::now().naive_utc(),
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Users without preferences yet get the default frequency
+async fn get_pending_user_ids(pool: &MySqlPool) -> Result<Vec<u64>, EntityError> {
+    let rows = sqlx::query!(
+        r#"
+SELECT DISTINCT n.user_id AS id
+FROM notifications n
+LEFT JOIN email_preferences p ON p.user_id = n.user_id
+WHERE n.is_read = 0
+    AND n.id > COALESCE(p.last_notification_id, 0)
+    AND COALESCE(p.frequency, ?) <> ?
+        "#,
+        EmailFrequency::default().as_str(),
+        EmailFrequency::Never.as_str()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.id).collect())
+}
+
+async fn claim(
+    pool: &MySqlPool,
+    user_id: u64,
+    from_notification_id: u64,
+    to_notification_id: u64,
+) -> Result<bool, EntityError> {
+    let now = Utc::now().naive_utc();
+
+    let result = sqlx::query!(
+        r#"
+UPDATE email_preferences
+SET last_notification_id = ?, last_sent = ?, updated = ?
+WHERE user_id = ? AND last_notification_id = ?
+        "#,
+        to_notification_id,
+        now,
+        now,
+        user_id,
+        from_notification_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+fn parse_frequency(frequency: &str) -> EmailFrequency {
+    match frequency.parse() {
+        Ok(frequency) => frequency,
+        Err(_) => {
+            warn!("Unknown email frequency {}, using the default", frequency);
+            EmailFrequency::default()
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::{EmailFrequency, EmailPreference};
+
+#[async_trait]
+pub trait EmailPreferenceStore: Send + Sync + Clone {
+    /// A user's preferences, created with the default frequency the first time they're needed.
+    async fn get_by_user_id(&self, user_id: u64) -> Result<EmailPreference, EntityError>;
+
+    /// Fails with `NotFound` unless the token is from one of our emails.
+    async fn get_by_unsubscribe_token(&self, token: &str) -> Result<EmailPreference, EntityError>;
+
+    async fn set_frequency(
+        &self,
+        user_id: u64,
+        frequency: EmailFrequency,
+    ) -> Result<EmailPreference, EntityError>;
+
+    /// Users with unread notifications that haven't been emailed yet and who haven't turned
+    /// emails off. They may not be due another email.
+    async fn get_pending_user_ids(&self) -> Result<Vec<u64>, EntityError>;
+
+    /// Records that notifications up to `to_notification_id` were emailed. Returns false if the
+    /// user's last emailed notification is no longer `from_notification_id`, so when several
+    /// instances run the digest only one of them sends it.
+    async fn claim(
+        &self,
+        user_id: u64,
+        from_notification_id: u64,
+        to_notification_id: u64,
+    ) -> Result<bool, EntityError>;
+}
@@ -0,0 +1,7 @@
+mod email_preference;
+mod email_preference_sql;
+mod email_preference_store;
+
+pub use email_preference::{EmailFrequency, EmailPreference};
+pub use email_preference_sql::SqlEmailPreferenceStore;
+pub use email_preference_store::EmailPreferenceStore;
@@ -3,27 +3,54 @@ use std::sync::Arc;
 use sqlx::MySqlPool;
 
 use super::{
-    cache::Cache,
+    api_token::SqlApiTokenStore,
+    block::SqlBlockStore,
+    cache::{Cache, CacheStats},
     comment::{CachedCommentStore, SqlCommentStore},
     content::{CachedContentStore, SqlContentStore},
     email::{CachedEmailStore, SqlEmailStore},
+    email_preference::SqlEmailPreferenceStore,
+    identity::SqlIdentityStore,
+    link_preview::{CachedLinkPreviewStore, SqlLinkPreviewStore},
+    login_attempt::SqlLoginAttemptStore,
+    moderation_log::SqlModerationLogStore,
+    notification::SqlNotificationStore,
     post::{CachedPostStore, SqlPostStore},
+    report::SqlReportStore,
+    spam::SqlSpamStore,
+    stats::SqlStatsStore,
+    two_factor::SqlTwoFactorStore,
     user::{CachedUserStore, SqlUserStore},
+    webhook::SqlWebhookStore,
 };
 
 pub type CachedSqlCommentStore = Arc<CachedCommentStore<SqlCommentStore>>;
 pub type CachedSqlContentStore = Arc<CachedContentStore<SqlContentStore>>;
 pub type CachedSqlEmailStore = Arc<CachedEmailStore<SqlEmailStore>>;
+pub type CachedSqlLinkPreviewStore = Arc<CachedLinkPreviewStore<SqlLinkPreviewStore>>;
 pub type CachedSqlPostStore = Arc<CachedPostStore<SqlPostStore>>;
 pub type CachedSqlUserStore = Arc<CachedUserStore<SqlUserStore>>;
 
 #[derive(Clone)]
 pub struct EntityStores {
+    pub api_token_store: Arc<SqlApiTokenStore>,
+    pub block_store: Arc<SqlBlockStore>,
     pub comment_store: CachedSqlCommentStore,
     pub content_store: CachedSqlContentStore,
     pub email_store: CachedSqlEmailStore,
+    pub email_preference_store: Arc<SqlEmailPreferenceStore>,
+    pub identity_store: Arc<SqlIdentityStore>,
+    pub link_preview_store: CachedSqlLinkPreviewStore,
+    pub login_attempt_store: Arc<SqlLoginAttemptStore>,
+    pub moderation_log_store: Arc<SqlModerationLogStore>,
+    pub notification_store: Arc<SqlNotificationStore>,
     pub post_store: CachedSqlPostStore,
+    pub report_store: Arc<SqlReportStore>,
+    pub spam_store: Arc<SqlSpamStore>,
+    pub stats_store: Arc<SqlStatsStore>,
+    pub two_factor_store: Arc<SqlTwoFactorStore>,
     pub user_store: CachedSqlUserStore,
+    pub webhook_store: Arc<SqlWebhookStore>,
 }
 
 impl EntityStores {
@@ -31,24 +58,102 @@ impl EntityStores {
         let email_source = SqlEmailStore::new(pool.clone());
         let email_store = Arc::new(CachedEmailStore::new(Cache::new(), email_source));
 
+        // Never cached, so the digest job sees where the last email left off
+        let email_preference_store = Arc::new(SqlEmailPreferenceStore::new(pool.clone()));
+
+        // Never cached, so a revoked token stops working immediately
+        let api_token_store = Arc::new(SqlApiTokenStore::new(pool.clone()));
+
+        // Never cached, so blocking someone hides them on the very next page
+        let block_store = Arc::new(SqlBlockStore::new(pool.clone()));
+
+        let identity_store = Arc::new(SqlIdentityStore::new(pool.clone()));
+
+        let link_preview_source = SqlLinkPreviewStore::new(pool.clone());
+        let link_preview_store = Arc::new(CachedLinkPreviewStore::new(
+            Cache::new(),
+            link_preview_source,
+        ));
+
+        // Never cached, lockout checks must always see the latest attempts
+        let login_attempt_store = Arc::new(SqlLoginAttemptStore::new(pool.clone()));
+
+        // Never cached, so the log shows an action as soon as it's taken
+        let moderation_log_store = Arc::new(SqlModerationLogStore::new(pool.clone()));
+
+        // Never cached, so the moderation queue empties as soon as reports are resolved
+        let report_store = Arc::new(SqlReportStore::new(pool.clone()));
+
+        // Never cached, so training takes effect on the very next submission
+        let spam_store = Arc::new(SqlSpamStore::new(pool.clone()));
+
+        // Never cached, so the admin dashboard's counts are current
+        let stats_store = Arc::new(SqlStatsStore::new(pool.clone()));
+
+        // Never cached, so a disabled or reset 2FA secret stops working immediately
+        let two_factor_store = Arc::new(SqlTwoFactorStore::new(pool.clone()));
+
         let user_source = SqlUserStore::new(pool.clone(), email_store.clone());
         let user_store = Arc::new(CachedUserStore::new(Cache::new(), user_source));
 
+        // Never cached, so a deleted webhook stops getting deliveries immediately
+        let webhook_store = Arc::new(SqlWebhookStore::new(pool.clone()));
+
         let content_source = SqlContentStore::new(pool.clone());
         let content_store = Arc::new(CachedContentStore::new(Cache::new(), content_source));
 
-        let post_source = SqlPostStore::new(pool.clone(), content_store.clone());
+        let post_source = SqlPostStore::new(
+            pool.clone(),
+            content_store.clone(),
+            user_store.clone(),
+            webhook_store.clone(),
+        );
         let post_store = Arc::new(CachedPostStore::new(Cache::new(), post_source));
 
-        let comment_source = SqlCommentStore::new(pool, content_store.clone());
+        // Never cached, so the unread count is current as soon as a reply is posted
+        let notification_store = Arc::new(SqlNotificationStore::new(pool.clone()));
+
+        let comment_source = SqlCommentStore::new(
+            pool,
+            block_store.clone(),
+            content_store.clone(),
+            notification_store.clone(),
+            user_store.clone(),
+            webhook_store.clone(),
+        );
         let comment_store = Arc::new(CachedCommentStore::new(Cache::new(), comment_source));
 
         Self {
+            api_token_store,
+            block_store,
             comment_store,
             content_store,
             email_store,
+            email_preference_store,
+            identity_store,
+            link_preview_store,
+            login_attempt_store,
+            moderation_log_store,
+            notification_store,
             post_store,
+            report_store,
+            spam_store,
+            stats_store,
+            two_factor_store,
             user_store,
+            webhook_store,
         }
     }
+
+    /// Each cached store's cache, by the name of what it holds.
+    pub fn cache_stats(&self) -> Vec<(&'static str, CacheStats)> {
+        vec![
+            ("comments", self.comment_store.cache_stats()),
+            ("contents", self.content_store.cache_stats()),
+            ("emails", self.email_store.cache_stats()),
+            ("link previews", self.link_preview_store.cache_stats()),
+            ("posts", self.post_store.cache_stats()),
+            ("users", self.user_store.cache_stats()),
+        ]
+    }
 }
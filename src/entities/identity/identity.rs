@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Links a user to their account with an external identity provider.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Identity {
+    pub id: u64,
+    pub user_id: u64,
+    pub provider: String,
+    pub subject: String,
+    pub created: DateTime<Utc>,
+}
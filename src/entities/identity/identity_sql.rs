@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{Identity, IdentityStore};
+
+#[derive(Clone)]
+pub struct SqlIdentityStore {
+    pool: MySqlPool,
+}
+
+impl SqlIdentityStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct IdentityEntity {
+    pub id: u64,
+    pub user_id: u64,
+    pub provider: String,
+    pub subject: String,
+    pub created: NaiveDateTime,
+}
+
+impl From<IdentityEntity> for Identity {
+    fn from(identity_entity: IdentityEntity) -> Self {
+        Self {
+            id: identity_entity.id,
+            user_id: identity_entity.user_id,
+            provider: identity_entity.provider,
+            subject: identity_entity.subject,
+            created: Utc.from_utc_datetime(&identity_entity.created),
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityStore for SqlIdentityStore {
+    async fn insert(
+        &self,
+        user_id: u64,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Identity, EntityError> {
+        insert(&self.pool, user_id, provider, subject).await?;
+
+        self.get_by_provider_subject(provider, subject).await
+    }
+
+    async fn get_by_provider_subject(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Identity, EntityError> {
+        Ok(Identity::from(
+            get_by_provider_subject(&self.pool, provider, subject).await?,
+        ))
+    }
+
+    async fn get_by_user_id(&self, user_id: u64) -> Result<Vec<Identity>, EntityError> {
+        Ok(get_by_user_id(&self.pool, user_id)
+            .await?
+            .into_iter()
+            .map(Identity::from)
+            .collect())
+    }
+}
+
+async fn insert(
+    pool: &MySqlPool,
+    user_id: u64,
+    provider: &str,
+    subject: &str,
+) -> Result<u64, EntityError> {
+    let identity_id = sqlx::query!(
+        r#"
+INSERT INTO identities (user_id, provider, subject, created)
+VALUES (?, ?, ?, ?)
+        "#,
+        user_id,
+        provider,
+        subject,
+        Utc::now().naive_utc()
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(identity_id)
+}
+
+async fn get_by_provider_subject(
+    pool: &MySqlPool,
+    provider: &str,
+    subject: &str,
+) -> Result<IdentityEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        IdentityEntity,
+        r#"
+SELECT *
+FROM identities
+WHERE provider = ? AND subject = ?
+        "#,
+        provider,
+        subject
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_by_user_id(
+    pool: &MySqlPool,
+    user_id: u64,
+) -> Result<Vec<IdentityEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        IdentityEntity,
+        r#"
+SELECT *
+FROM identities
+WHERE user_id = ?
+ORDER BY created
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?)
+}
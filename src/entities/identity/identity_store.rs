@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::Identity;
+
+#[async_trait]
+pub trait IdentityStore: Send + Sync + Clone {
+    /// Fails with `DuplicateKey` if the external account is already linked to a user.
+    async fn insert(
+        &self,
+        user_id: u64,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Identity, EntityError>;
+
+    async fn get_by_provider_subject(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Identity, EntityError>;
+
+    async fn get_by_user_id(&self, user_id: u64) -> Result<Vec<Identity>, EntityError>;
+}
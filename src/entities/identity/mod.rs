@@ -0,0 +1,7 @@
+mod identity;
+mod identity_sql;
+mod identity_store;
+
+pub use identity::Identity;
+pub use identity_sql::SqlIdentityStore;
+pub use identity_store::IdentityStore;
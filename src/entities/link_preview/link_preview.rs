@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a link post's page says about itself, shown as a card instead of the bare link.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LinkPreview {
+    pub id: u64,
+    pub post_id: u64,
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub created: DateTime<Utc>,
+}
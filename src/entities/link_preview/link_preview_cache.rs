@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+
+use crate::entities::{
+    cache::{Cache, CacheStats},
+    EntityError,
+};
+
+use super::{LinkPreview, LinkPreviewStore};
+
+#[derive(Clone)]
+pub struct CachedLinkPreviewStore<T>
+where
+    T: LinkPreviewStore,
+{
+    cache: Cache,
+    source: T,
+}
+
+impl<T> CachedLinkPreviewStore<T>
+where
+    T: LinkPreviewStore,
+{
+    pub fn new(cache: Cache, source: T) -> Self {
+        Self { cache, source }
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+}
+
+#[async_trait]
+impl<T> LinkPreviewStore for CachedLinkPreviewStore<T>
+where
+    T: LinkPreviewStore + Send + Sync,
+{
+    async fn insert(
+        &self,
+        post_id: u64,
+        url: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+        image_url: &Option<String>,
+        site_name: &Option<String>,
+    ) -> Result<LinkPreview, EntityError> {
+        self.cache
+            .insert_cached(
+                || async {
+                    self.source
+                        .insert(post_id, url, title, description, image_url, site_name)
+                        .await
+                },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    // Misses aren't cached, so a post's preview shows up as soon as it's fetched
+    async fn get_by_post_id(&self, post_id: u64) -> Result<LinkPreview, EntityError> {
+        let key = build_post_id_key(post_id);
+        self.cache
+            .get_cached(
+                key,
+                || async { self.source.get_by_post_id(post_id).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+}
+
+fn build_keys(link_preview: &LinkPreview) -> Vec<String> {
+    vec![build_post_id_key(link_preview.post_id)]
+}
+
+fn build_post_id_key(post_id: u64) -> String {
+    format!("post_id:{}", post_id)
+}
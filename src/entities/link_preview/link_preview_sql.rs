@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{LinkPreview, LinkPreviewStore};
+
+#[derive(Clone)]
+pub struct SqlLinkPreviewStore {
+    pool: MySqlPool,
+}
+
+impl SqlLinkPreviewStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct LinkPreviewEntity {
+    pub id: u64,
+    pub post_id: u64,
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub created: NaiveDateTime,
+}
+
+impl From<LinkPreviewEntity> for LinkPreview {
+    fn from(link_preview_entity: LinkPreviewEntity) -> Self {
+        Self {
+            id: link_preview_entity.id,
+            post_id: link_preview_entity.post_id,
+            url: link_preview_entity.url,
+            title: link_preview_entity.title,
+            description: link_preview_entity.description,
+            image_url: link_preview_entity.image_url,
+            site_name: link_preview_entity.site_name,
+            created: Utc.from_utc_datetime(&link_preview_entity.created),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkPreviewStore for SqlLinkPreviewStore {
+    async fn insert(
+        &self,
+        post_id: u64,
+        url: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+        image_url: &Option<String>,
+        site_name: &Option<String>,
+    ) -> Result<LinkPreview, EntityError> {
+        insert(
+            &self.pool,
+            post_id,
+            url,
+            title,
+            description,
+            image_url,
+            site_name,
+        )
+        .await?;
+
+        self.get_by_post_id(post_id).await
+    }
+
+    async fn get_by_post_id(&self, post_id: u64) -> Result<LinkPreview, EntityError> {
+        Ok(LinkPreview::from(
+            get_by_post_id(&self.pool, post_id).await?,
+        ))
+    }
+}
+
+async fn insert(
+    pool: &MySqlPool,
+    post_id: u64,
+    url: &str,
+    title: &Option<String>,
+    description: &Option<String>,
+    image_url: &Option<String>,
+    site_name: &Option<String>,
+) -> Result<u64, EntityError> {
+    let link_preview_id = sqlx::query!(
+        r#"
+INSERT INTO link_previews (post_id, url, title, description, image_url, site_name, created)
+VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+        post_id,
+        url,
+        title,
+        description,
+        image_url,
+        site_name,
+        Utc::now().naive_utc()
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(link_preview_id)
+}
+
+async fn get_by_post_id(pool: &MySqlPool, post_id: u64) -> Result<LinkPreviewEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        LinkPreviewEntity,
+        r#"
+SELECT *
+FROM link_previews
+WHERE post_id = ?
+        "#,
+        post_id
+    )
+    .fetch_one(pool)
+    .await?)
+}
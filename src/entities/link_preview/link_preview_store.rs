@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::LinkPreview;
+
+#[async_trait]
+pub trait LinkPreviewStore: Send + Sync + Clone {
+    /// Fails with `DuplicateKey` if the post already has a preview.
+    async fn insert(
+        &self,
+        post_id: u64,
+        url: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+        image_url: &Option<String>,
+        site_name: &Option<String>,
+    ) -> Result<LinkPreview, EntityError>;
+
+    async fn get_by_post_id(&self, post_id: u64) -> Result<LinkPreview, EntityError>;
+}
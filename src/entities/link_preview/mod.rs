@@ -0,0 +1,9 @@
+mod link_preview;
+mod link_preview_cache;
+mod link_preview_sql;
+mod link_preview_store;
+
+pub use link_preview::LinkPreview;
+pub use link_preview_cache::CachedLinkPreviewStore;
+pub use link_preview_sql::SqlLinkPreviewStore;
+pub use link_preview_store::LinkPreviewStore;
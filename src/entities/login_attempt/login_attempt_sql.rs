@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{LoginAttemptStore, LoginFailures};
+
+#[derive(Clone)]
+pub struct SqlLoginAttemptStore {
+    pool: MySqlPool,
+}
+
+impl SqlLoginAttemptStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for SqlLoginAttemptStore {
+    async fn insert(
+        &self,
+        user_id: Option<u64>,
+        ip: Option<&str>,
+        is_success: bool,
+    ) -> Result<(), EntityError> {
+        insert(&self.pool, user_id, ip, is_success).await
+    }
+
+    async fn get_failures_by_user_id(
+        &self,
+        user_id: u64,
+        since: DateTime<Utc>,
+    ) -> Result<LoginFailures, EntityError> {
+        get_failures_by_user_id(&self.pool, user_id, since).await
+    }
+
+    async fn get_failures_by_ip(
+        &self,
+        ip: &str,
+        since: DateTime<Utc>,
+    ) -> Result<LoginFailures, EntityError> {
+        get_failures_by_ip(&self.pool, ip, since).await
+    }
+}
+
+async fn insert(
+    pool: &MySqlPool,
+    user_id: Option<u64>,
+    ip: Option<&str>,
+    is_success: bool,
+) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+INSERT INTO login_attempts (user_id, ip, is_success, created)
+VALUES (?, ?, ?, ?)
+        "#,
+        user_id,
+        ip,
+        is_success,
+        Utc::now().naive_utc()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn get_failures_by_user_id(
+    pool: &MySqlPool,
+    user_id: u64,
+    since: DateTime<Utc>,
+) -> Result<LoginFailures, EntityError> {
+    let failures = sqlx::query!(
+        r#"
+SELECT
+    COUNT(id) AS count,
+    MAX(created) AS last_failure
+FROM login_attempts
+WHERE
+    user_id = ?
+    AND is_success = 0
+    AND created > ?
+    AND id > (
+        SELECT COALESCE(MAX(id), 0)
+        FROM login_attempts
+        WHERE user_id = ? AND is_success = 1
+    )
+        "#,
+        user_id,
+        since.naive_utc(),
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(LoginFailures {
+        count: failures.count,
+        last_failure: failures
+            .last_failure
+            .map(|last_failure| Utc.from_utc_datetime(&last_failure)),
+    })
+}
+
+async fn get_failures_by_ip(
+    pool: &MySqlPool,
+    ip: &str,
+    since: DateTime<Utc>,
+) -> Result<LoginFailures, EntityError> {
+    let failures = sqlx::query!(
+        r#"
+SELECT
+    COUNT(id) AS count,
+    MAX(created) AS last_failure
+FROM login_attempts
+WHERE
+    ip = ?
+    AND is_success = 0
+    AND created > ?
+        "#,
+        ip,
+        since.naive_utc()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(LoginFailures {
+        count: failures.count,
+        last_failure: failures
+            .last_failure
+            .map(|last_failure| Utc.from_utc_datetime(&last_failure)),
+    })
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::entities::EntityError;
+
+use super::LoginFailures;
+
+#[async_trait]
+pub trait LoginAttemptStore: Send + Sync + Clone {
+    async fn insert(
+        &self,
+        user_id: Option<u64>,
+        ip: Option<&str>,
+        is_success: bool,
+    ) -> Result<(), EntityError>;
+
+    /// Failed attempts for the user since `since`, ignoring any before their last successful login.
+    async fn get_failures_by_user_id(
+        &self,
+        user_id: u64,
+        since: DateTime<Utc>,
+    ) -> Result<LoginFailures, EntityError>;
+
+    /// Failed attempts from the IP since `since`, across all accounts.
+    async fn get_failures_by_ip(
+        &self,
+        ip: &str,
+        since: DateTime<Utc>,
+    ) -> Result<LoginFailures, EntityError>;
+}
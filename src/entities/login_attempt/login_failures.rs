@@ -0,0 +1,8 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LoginFailures {
+    pub count: i64,
+    pub last_failure: Option<DateTime<Utc>>,
+}
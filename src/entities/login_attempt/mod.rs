@@ -0,0 +1,7 @@
+mod login_attempt_sql;
+mod login_attempt_store;
+mod login_failures;
+
+pub use login_attempt_sql::SqlLoginAttemptStore;
+pub use login_attempt_store::LoginAttemptStore;
+pub use login_failures::LoginFailures;
@@ -2,12 +2,25 @@ mod entity_stores;
 mod error;
 mod utils;
 
+pub mod api_token;
+pub mod block;
 pub mod cache;
 pub mod comment;
 pub mod content;
 pub mod email;
+pub mod email_preference;
+pub mod identity;
+pub mod link_preview;
+pub mod login_attempt;
+pub mod moderation_log;
+pub mod notification;
 pub mod post;
+pub mod report;
+pub mod spam;
+pub mod stats;
+pub mod two_factor;
 pub mod user;
+pub mod webhook;
 
 pub use entity_stores::EntityStores;
 pub use error::EntityError;
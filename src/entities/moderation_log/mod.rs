@@ -0,0 +1,9 @@
+mod moderation_log;
+mod moderation_log_sql;
+mod moderation_log_store;
+
+pub use moderation_log::{
+    ModerationAction, ModerationEntry, ModerationLogFilter, ModerationTarget,
+};
+pub use moderation_log_sql::{SqlModerationLogStore, MAX_REASON_LENGTH};
+pub use moderation_log_store::ModerationLogStore;
@@ -0,0 +1,159 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{comment::Comment, post::Post};
+
+/// Something a moderator or admin did.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    RemovePost,
+    RestorePost,
+    LockThread,
+    UnlockThread,
+    RemoveComment,
+    RestoreComment,
+    BanUser,
+    UnbanUser,
+    ShadowbanUser,
+    UnshadowbanUser,
+    ChangeRole,
+    ResetTwoFactor,
+    ForcePasswordReset,
+    DismissReports,
+    /// Published a post or comment the spam filter held.
+    ApproveHeld,
+}
+
+impl ModerationAction {
+    pub const ALL: [ModerationAction; 15] = [
+        ModerationAction::RemovePost,
+        ModerationAction::RestorePost,
+        ModerationAction::LockThread,
+        ModerationAction::UnlockThread,
+        ModerationAction::RemoveComment,
+        ModerationAction::RestoreComment,
+        ModerationAction::BanUser,
+        ModerationAction::UnbanUser,
+        ModerationAction::ShadowbanUser,
+        ModerationAction::UnshadowbanUser,
+        ModerationAction::ChangeRole,
+        ModerationAction::ResetTwoFactor,
+        ModerationAction::ForcePasswordReset,
+        ModerationAction::DismissReports,
+        ModerationAction::ApproveHeld,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationAction::RemovePost => "remove_post",
+            ModerationAction::RestorePost => "restore_post",
+            ModerationAction::LockThread => "lock_thread",
+            ModerationAction::UnlockThread => "unlock_thread",
+            ModerationAction::RemoveComment => "remove_comment",
+            ModerationAction::RestoreComment => "restore_comment",
+            ModerationAction::BanUser => "ban_user",
+            ModerationAction::UnbanUser => "unban_user",
+            ModerationAction::ShadowbanUser => "shadowban_user",
+            ModerationAction::UnshadowbanUser => "unshadowban_user",
+            ModerationAction::ChangeRole => "change_role",
+            ModerationAction::ResetTwoFactor => "reset_two_factor",
+            ModerationAction::ForcePasswordReset => "force_password_reset",
+            ModerationAction::DismissReports => "dismiss_reports",
+            ModerationAction::ApproveHeld => "approve_held",
+        }
+    }
+}
+
+impl fmt::Display for ModerationAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ModerationAction {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ModerationAction, Self::Err> {
+        ModerationAction::ALL
+            .into_iter()
+            .find(|action| action.as_str() == input)
+            .ok_or(())
+    }
+}
+
+/// Who and what an action was taken on. Actions on a post or comment also record its author,
+/// so they show up in the author's history.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModerationTarget {
+    pub user_id: Option<u64>,
+    pub post_id: Option<u64>,
+    pub comment_id: Option<u64>,
+}
+
+impl ModerationTarget {
+    pub fn user(user_id: u64) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ..Default::default()
+        }
+    }
+
+    pub fn post(post: &Post) -> Self {
+        Self {
+            user_id: Some(post.author_id),
+            post_id: Some(post.id),
+            comment_id: None,
+        }
+    }
+
+    pub fn comment(comment: &Comment) -> Self {
+        Self {
+            user_id: Some(comment.author_id),
+            post_id: Some(comment.post_id),
+            comment_id: Some(comment.id),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ModerationEntry {
+    pub id: u64,
+    pub actor_id: u64,
+    pub action: ModerationAction,
+    pub target_user_id: Option<u64>,
+    pub post_id: Option<u64>,
+    pub comment_id: Option<u64>,
+    /// Why the moderator did it, if they said.
+    pub reason: Option<String>,
+    /// What changed, when the action alone doesn't say, like the user's new role.
+    pub details: Option<String>,
+    pub created: DateTime<Utc>,
+}
+
+/// Narrows down the log, each filter that's set has to match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModerationLogFilter {
+    pub actor_id: Option<u64>,
+    pub target_user_id: Option<u64>,
+    pub action: Option<ModerationAction>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_round_trip() {
+        for action in ModerationAction::ALL {
+            assert_eq!(action.as_str().parse(), Ok(action));
+            assert_eq!(
+                serde_json::to_string(&action).unwrap(),
+                format!("\"{}\"", action)
+            );
+        }
+        assert_eq!("delete_everything".parse::<ModerationAction>(), Err(()));
+    }
+}
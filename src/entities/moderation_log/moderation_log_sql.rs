@@ -0,0 +1,182 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{
+    ModerationAction, ModerationEntry, ModerationLogFilter, ModerationLogStore, ModerationTarget,
+};
+
+pub const MAX_REASON_LENGTH: usize = 1024;
+
+#[derive(Clone)]
+pub struct SqlModerationLogStore {
+    pool: MySqlPool,
+}
+
+impl SqlModerationLogStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ModerationEntryEntity {
+    pub id: u64,
+    pub actor_id: u64,
+    pub action: String,
+    pub target_user_id: Option<u64>,
+    pub post_id: Option<u64>,
+    pub comment_id: Option<u64>,
+    pub reason: Option<String>,
+    pub details: Option<String>,
+    pub created: NaiveDateTime,
+}
+
+impl TryFrom<ModerationEntryEntity> for ModerationEntry {
+    type Error = EntityError;
+
+    fn try_from(entry_entity: ModerationEntryEntity) -> Result<Self, Self::Error> {
+        let action = match ModerationAction::from_str(&entry_entity.action) {
+            Ok(action) => action,
+            Err(_) => {
+                return Err(EntityError::Internal(format!(
+                    "unknown moderation action {}",
+                    entry_entity.action
+                )))
+            }
+        };
+
+        Ok(Self {
+            id: entry_entity.id,
+            actor_id: entry_entity.actor_id,
+            action,
+            target_user_id: entry_entity.target_user_id,
+            post_id: entry_entity.post_id,
+            comment_id: entry_entity.comment_id,
+            reason: entry_entity.reason,
+            details: entry_entity.details,
+            created: Utc.from_utc_datetime(&entry_entity.created),
+        })
+    }
+}
+
+#[async_trait]
+impl ModerationLogStore for SqlModerationLogStore {
+    async fn insert(
+        &self,
+        actor_id: u64,
+        action: ModerationAction,
+        target: &ModerationTarget,
+        reason: &Option<String>,
+        details: &Option<String>,
+    ) -> Result<ModerationEntry, EntityError> {
+        let id = insert(&self.pool, actor_id, action, target, reason, details).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn get_by_id(&self, id: u64) -> Result<ModerationEntry, EntityError> {
+        ModerationEntry::try_from(get_by_id(&self.pool, id).await?)
+    }
+
+    async fn get_recent(
+        &self,
+        filter: &ModerationLogFilter,
+        count: u32,
+    ) -> Result<Vec<ModerationEntry>, EntityError> {
+        get_recent(&self.pool, filter, count)
+            .await?
+            .into_iter()
+            .map(ModerationEntry::try_from)
+            .collect()
+    }
+}
+
+async fn insert(
+    pool: &MySqlPool,
+    actor_id: u64,
+    action: ModerationAction,
+    target: &ModerationTarget,
+    reason: &Option<String>,
+    details: &Option<String>,
+) -> Result<u64, EntityError> {
+    let reason = sanitize_reason(reason)?;
+
+    let entry_id = sqlx::query!(
+        r#"
+INSERT INTO moderation_log (actor_id, action, target_user_id, post_id, comment_id, reason, details, created)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        actor_id,
+        action.as_str(),
+        target.user_id,
+        target.post_id,
+        target.comment_id,
+        reason,
+        details,
+        Utc::now().naive_utc()
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(entry_id)
+}
+
+async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<ModerationEntryEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        ModerationEntryEntity,
+        r#"
+SELECT *
+FROM moderation_log
+WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_recent(
+    pool: &MySqlPool,
+    filter: &ModerationLogFilter,
+    count: u32,
+) -> Result<Vec<ModerationEntryEntity>, EntityError> {
+    let action = filter.action.map(|a| a.as_str());
+
+    Ok(sqlx::query_as!(
+        ModerationEntryEntity,
+        r#"
+SELECT *
+FROM moderation_log
+WHERE (? IS NULL OR actor_id = ?)
+    AND (? IS NULL OR target_user_id = ?)
+    AND (? IS NULL OR action = ?)
+ORDER BY id DESC
+LIMIT ?
+        "#,
+        filter.actor_id,
+        filter.actor_id,
+        filter.target_user_id,
+        filter.target_user_id,
+        action,
+        action,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+fn sanitize_reason(reason: &Option<String>) -> Result<Option<String>, EntityError> {
+    match reason.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(reason) if reason.len() > MAX_REASON_LENGTH => {
+            Err(EntityError::InvalidInput("reason", "too long"))
+        }
+        Some(reason) => Ok(Some(reason.to_owned())),
+    }
+}
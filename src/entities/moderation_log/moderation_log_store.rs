@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::{ModerationAction, ModerationEntry, ModerationLogFilter, ModerationTarget};
+
+/// Append-only, entries are never updated or deleted so the log can be trusted.
+#[async_trait]
+pub trait ModerationLogStore: Send + Sync + Clone {
+    async fn insert(
+        &self,
+        actor_id: u64,
+        action: ModerationAction,
+        target: &ModerationTarget,
+        reason: &Option<String>,
+        details: &Option<String>,
+    ) -> Result<ModerationEntry, EntityError>;
+
+    async fn get_by_id(&self, id: u64) -> Result<ModerationEntry, EntityError>;
+
+    /// Entries matching the filter, newest first.
+    async fn get_recent(
+        &self,
+        filter: &ModerationLogFilter,
+        count: u32,
+    ) -> Result<Vec<ModerationEntry>, EntityError>;
+}
@@ -0,0 +1,7 @@
+mod notification;
+mod notification_sql;
+mod notification_store;
+
+pub use notification::Notification;
+pub use notification_sql::SqlNotificationStore;
+pub use notification_store::NotificationStore;
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tells a user that someone replied to one of their posts or comments.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Notification {
+    pub id: u64,
+    pub public_id: String,
+    pub user_id: u64,
+    pub comment_id: u64,
+    pub is_read: bool,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
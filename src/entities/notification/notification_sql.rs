@@ -0,0 +1,252 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+use uuid::Uuid;
+
+use crate::entities::{utils, EntityError};
+
+use super::{Notification, NotificationStore};
+
+#[derive(Clone)]
+pub struct SqlNotificationStore {
+    pool: MySqlPool,
+}
+
+impl SqlNotificationStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct NotificationEntity {
+    pub id: u64,
+    pub public_id: Vec<u8>,
+    pub user_id: u64,
+    pub comment_id: u64,
+    pub is_read: i8,
+    pub created: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+impl From<NotificationEntity> for Notification {
+    fn from(notification_entity: NotificationEntity) -> Self {
+        Self {
+            id: notification_entity.id,
+            public_id: utils::get_readable_public_id(notification_entity.public_id),
+            user_id: notification_entity.user_id,
+            comment_id: notification_entity.comment_id,
+            is_read: notification_entity.is_read > 0,
+            created: Utc.from_utc_datetime(&notification_entity.created),
+            updated: Utc.from_utc_datetime(&notification_entity.updated),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationStore for SqlNotificationStore {
+    async fn insert(&self, user_id: u64, comment_id: u64) -> Result<Notification, EntityError> {
+        let id = insert(&self.pool, user_id, comment_id).await?;
+
+        Ok(Notification::from(get_by_id(&self.pool, id).await?))
+    }
+
+    async fn get_by_user_id(
+        &self,
+        user_id: u64,
+        count: u8,
+    ) -> Result<Vec<Notification>, EntityError> {
+        Ok(get_by_user_id(&self.pool, user_id, count)
+            .await?
+            .into_iter()
+            .map(Notification::from)
+            .collect())
+    }
+
+    async fn get_unread_after(
+        &self,
+        user_id: u64,
+        after_id: u64,
+        count: u8,
+    ) -> Result<Vec<Notification>, EntityError> {
+        Ok(get_unread_after(&self.pool, user_id, after_id, count)
+            .await?
+            .into_iter()
+            .map(Notification::from)
+            .collect())
+    }
+
+    async fn get_unread_count(&self, user_id: u64) -> Result<i64, EntityError> {
+        get_unread_count(&self.pool, user_id).await
+    }
+
+    async fn mark_read(&self, user_id: u64, public_id: &str) -> Result<Notification, EntityError> {
+        let public_id = utils::parse_public_id(public_id)?;
+        let notification = get_by_public_id(&self.pool, public_id).await?;
+        if notification.user_id != user_id {
+            return Err(EntityError::NotFound);
+        }
+
+        if notification.is_read == 0 {
+            mark_read(&self.pool, notification.id).await?;
+        }
+
+        Ok(Notification::from(
+            get_by_id(&self.pool, notification.id).await?,
+        ))
+    }
+
+    async fn mark_all_read(&self, user_id: u64) -> Result<(), EntityError> {
+        mark_all_read(&self.pool, user_id).await
+    }
+}
+
+async fn insert(pool: &MySqlPool, user_id: u64, comment_id: u64) -> Result<u64, EntityError> {
+    let public_id = Uuid::new_v4().into_bytes();
+    let created = Utc::now().naive_utc();
+
+    let notification_id = sqlx::query!(
+        r#"
+INSERT INTO notifications (public_id, user_id, comment_id, is_read, created, updated)
+VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        &public_id[..],
+        user_id,
+        comment_id,
+        0,
+        created,
+        created
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(notification_id)
+}
+
+async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<NotificationEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        NotificationEntity,
+        r#"
+SELECT *
+FROM notifications
+WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_by_public_id(
+    pool: &MySqlPool,
+    public_id: Uuid,
+) -> Result<NotificationEntity, EntityError> {
+    let public_id_bytes = public_id.into_bytes();
+
+    Ok(sqlx::query_as!(
+        NotificationEntity,
+        r#"
+SELECT *
+FROM notifications
+WHERE public_id = ?
+        "#,
+        &public_id_bytes[..]
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn get_by_user_id(
+    pool: &MySqlPool,
+    user_id: u64,
+    count: u8,
+) -> Result<Vec<NotificationEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        NotificationEntity,
+        r#"
+SELECT *
+FROM notifications
+WHERE user_id = ?
+ORDER BY id DESC
+LIMIT ?
+        "#,
+        user_id,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn get_unread_after(
+    pool: &MySqlPool,
+    user_id: u64,
+    after_id: u64,
+    count: u8,
+) -> Result<Vec<NotificationEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        NotificationEntity,
+        r#"
+SELECT *
+FROM notifications
+WHERE user_id = ? AND is_read = 0 AND id > ?
+ORDER BY id DESC
+LIMIT ?
+        "#,
+        user_id,
+        after_id,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn get_unread_count(pool: &MySqlPool, user_id: u64) -> Result<i64, EntityError> {
+    let count = sqlx::query!(
+        r#"
+SELECT
+    COUNT(id) as count
+FROM notifications
+WHERE user_id = ? AND is_read = 0
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.count)
+}
+
+async fn mark_read(pool: &MySqlPool, id: u64) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE notifications
+SET is_read = ?, updated = ?
+WHERE id = ?
+        "#,
+        1,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn mark_all_read(pool: &MySqlPool, user_id: u64) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE notifications
+SET is_read = ?, updated = ?
+WHERE user_id = ? AND is_read = 0
+        "#,
+        1,
+        Utc::now().naive_utc(),
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
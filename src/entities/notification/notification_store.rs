@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::Notification;
+
+#[async_trait]
+pub trait NotificationStore: Send + Sync + Clone {
+    /// Fails with `DuplicateKey` if the user was already notified of the comment.
+    async fn insert(&self, user_id: u64, comment_id: u64) -> Result<Notification, EntityError>;
+
+    /// A user's notifications, newest first.
+    async fn get_by_user_id(
+        &self,
+        user_id: u64,
+        count: u8,
+    ) -> Result<Vec<Notification>, EntityError>;
+
+    /// Unread notifications newer than `after_id`, newest first.
+    async fn get_unread_after(
+        &self,
+        user_id: u64,
+        after_id: u64,
+        count: u8,
+    ) -> Result<Vec<Notification>, EntityError>;
+
+    async fn get_unread_count(&self, user_id: u64) -> Result<i64, EntityError>;
+
+    /// Fails with `NotFound` unless the notification is the user's.
+    async fn mark_read(&self, user_id: u64, public_id: &str) -> Result<Notification, EntityError>;
+
+    async fn mark_all_read(&self, user_id: u64) -> Result<(), EntityError>;
+}
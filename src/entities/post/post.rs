@@ -9,6 +9,8 @@ pub struct Post {
     pub title: String,
     pub link: Option<String>,
     pub content_id: Option<u64>,
+    pub is_removed: bool,
+    pub is_locked: bool,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
 }
@@ -1,7 +1,10 @@
 use async_trait::async_trait;
-use chrono::Duration;
+use chrono::{DateTime, Duration, Utc};
 
-use crate::entities::{cache::Cache, EntityError};
+use crate::entities::{
+    cache::{Cache, CacheStats},
+    EntityError,
+};
 
 use super::{Post, PostStore};
 
@@ -21,6 +24,10 @@ where
     pub fn new(cache: Cache, source: T) -> Self {
         Self { cache, source }
     }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
 }
 
 // TODO: set cache expiry, invalidate collections, etc.
@@ -36,10 +43,15 @@ where
         title: &str,
         link: &Option<String>,
         content: &Option<String>,
+        is_held: bool,
     ) -> Result<Post, EntityError> {
         self.cache
             .insert_cached(
-                || async { self.source.insert(author_id, title, link, content).await },
+                || async {
+                    self.source
+                        .insert(author_id, title, link, content, is_held)
+                        .await
+                },
                 build_keys,
                 None,
             )
@@ -70,12 +82,44 @@ where
             .await
     }
 
+    // Cached listings keep a removed post until they expire, the post itself is updated at once
+    async fn set_removed(&self, id: u64, is_removed: bool) -> Result<Post, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.set_removed(id, is_removed).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn publish(&self, id: u64) -> Result<Post, EntityError> {
+        self.cache
+            .insert_cached(|| async { self.source.publish(id).await }, build_keys, None)
+            .await
+    }
+
+    async fn set_locked(&self, id: u64, is_locked: bool) -> Result<Post, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.set_locked(id, is_locked).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
     async fn get_recent(
         &self,
         start_index: Option<u64>,
         count: u8,
+        viewer_id: Option<u64>,
+        include_shadowbanned: bool,
     ) -> Result<Vec<Post>, EntityError> {
-        let key = format!("recent:{:?}:{}", start_index, count);
+        let key = format!(
+            "recent:{:?}:{}:{:?}:{}",
+            start_index, count, viewer_id, include_shadowbanned
+        );
         let expiry = match start_index {
             Some(_) => Duration::minutes(60),
             None => Duration::seconds(60),
@@ -83,12 +127,72 @@ where
         self.cache
             .get_cached(
                 key.clone(),
-                || async { self.source.get_recent(start_index, count).await },
+                || async {
+                    self.source
+                        .get_recent(start_index, count, viewer_id, include_shadowbanned)
+                        .await
+                },
                 |_| vec![key],
                 Some(expiry),
             )
             .await
     }
+
+    async fn get_recent_by_author_id(
+        &self,
+        author_id: u64,
+        count: u8,
+    ) -> Result<Vec<Post>, EntityError> {
+        let key = format!("recent_by_author_id:{}:{}", author_id, count);
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_recent_by_author_id(author_id, count).await },
+                |_| vec![key],
+                Some(Duration::seconds(60)),
+            )
+            .await
+    }
+
+    async fn get_top_since(
+        &self,
+        since: DateTime<Utc>,
+        count: u8,
+    ) -> Result<Vec<Post>, EntityError> {
+        let key = format!("top_since:{}:{}", since.timestamp(), count);
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_top_since(since, count).await },
+                |_| vec![key],
+                Some(Duration::minutes(10)),
+            )
+            .await
+    }
+
+    async fn get_count(&self) -> Result<i64, EntityError> {
+        let key = "count".to_owned();
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_count().await },
+                |_| vec![key],
+                Some(Duration::minutes(10)),
+            )
+            .await
+    }
+
+    async fn get_page(&self, page: u64, page_size: u32) -> Result<Vec<Post>, EntityError> {
+        let key = format!("page:{}:{}", page, page_size);
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_page(page, page_size).await },
+                |_| vec![key],
+                Some(Duration::minutes(10)),
+            )
+            .await
+    }
 }
 
 fn build_keys(post: &Post) -> Vec<String> {
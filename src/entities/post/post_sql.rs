@@ -1,12 +1,19 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use log::error;
 use sqlx::MySqlPool;
 use url::Url;
 use uuid::Uuid;
 
 use crate::entities::{
-    content::ContentStore, entity_stores::CachedSqlContentStore, utils, EntityError,
+    content::ContentStore,
+    entity_stores::{CachedSqlContentStore, CachedSqlUserStore},
+    user::UserStore,
+    utils,
+    webhook::{SqlWebhookStore, WebhookEvent, WebhookStore},
+    EntityError,
 };
 
 use super::{Post, PostStore};
@@ -18,13 +25,42 @@ pub const MAX_TITLE_LENGTH: usize = 400;
 pub struct SqlPostStore {
     pool: MySqlPool,
     content_store: CachedSqlContentStore,
+    user_store: CachedSqlUserStore,
+    webhook_store: Arc<SqlWebhookStore>,
 }
 
 impl SqlPostStore {
-    pub fn new(pool: MySqlPool, content_store: CachedSqlContentStore) -> Self {
+    pub fn new(
+        pool: MySqlPool,
+        content_store: CachedSqlContentStore,
+        user_store: CachedSqlUserStore,
+        webhook_store: Arc<SqlWebhookStore>,
+    ) -> Self {
         Self {
             pool,
             content_store,
+            user_store,
+            webhook_store,
+        }
+    }
+
+    // The post is already submitted, so failing to queue webhooks is only logged. Shadowbanned
+    // authors' posts aren't sent anywhere, no one else is meant to see them.
+    async fn enqueue_webhooks(&self, post: &Post) {
+        match self.user_store.get_by_id(post.author_id).await {
+            Ok(author) if author.is_shadowbanned => return,
+            Ok(_) => {}
+            Err(e) => {
+                error!("Error getting post {}'s author: {:?}", post.id, e);
+                return;
+            }
+        }
+        if let Err(e) = self
+            .webhook_store
+            .enqueue(WebhookEvent::PostCreated, post.id)
+            .await
+        {
+            error!("Error queueing webhooks for post {}: {:?}", post.id, e);
         }
     }
 }
@@ -37,6 +73,8 @@ pub struct PostEntity {
     pub title: String,
     pub link: Option<String>,
     pub content_id: Option<u64>,
+    pub is_removed: i8,
+    pub is_locked: i8,
     pub created: NaiveDateTime,
     pub updated: NaiveDateTime,
 }
@@ -50,6 +88,8 @@ impl From<PostEntity> for Post {
             title: post_entity.title,
             link: post_entity.link,
             content_id: post_entity.content_id,
+            is_removed: post_entity.is_removed > 0,
+            is_locked: post_entity.is_locked > 0,
             created: Utc.from_utc_datetime(&post_entity.created),
             updated: Utc.from_utc_datetime(&post_entity.updated),
         }
@@ -64,6 +104,7 @@ impl PostStore for SqlPostStore {
         title: &str,
         link: &Option<String>,
         content: &Option<String>,
+        is_held: bool,
     ) -> Result<Post, EntityError> {
         let post_id = insert(
             &self.pool,
@@ -72,10 +113,16 @@ impl PostStore for SqlPostStore {
             title,
             link,
             content,
+            is_held,
         )
         .await?;
+        let post = self.get_by_id(post_id).await?;
 
-        Ok(self.get_by_id(post_id).await?)
+        if !is_held {
+            self.enqueue_webhooks(&post).await;
+        }
+
+        Ok(post)
     }
 
     async fn get_by_id(&self, id: u64) -> Result<Post, EntityError> {
@@ -88,12 +135,41 @@ impl PostStore for SqlPostStore {
         Ok(Post::from(get_by_public_id(&self.pool, public_id).await?))
     }
 
+    async fn set_removed(&self, id: u64, is_removed: bool) -> Result<Post, EntityError> {
+        set_removed(&self.pool, id, is_removed).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn publish(&self, id: u64) -> Result<Post, EntityError> {
+        set_removed(&self.pool, id, false).await?;
+        let post = self.get_by_id(id).await?;
+        self.enqueue_webhooks(&post).await;
+
+        Ok(post)
+    }
+
+    async fn set_locked(&self, id: u64, is_locked: bool) -> Result<Post, EntityError> {
+        set_locked(&self.pool, id, is_locked).await?;
+
+        self.get_by_id(id).await
+    }
+
     async fn get_recent(
         &self,
         start_index: Option<u64>,
         count: u8,
+        viewer_id: Option<u64>,
+        include_shadowbanned: bool,
     ) -> Result<Vec<Post>, EntityError> {
-        let recent_posts = get_recent(&self.pool, start_index, count).await?;
+        let recent_posts = get_recent(
+            &self.pool,
+            start_index,
+            count,
+            viewer_id,
+            include_shadowbanned,
+        )
+        .await?;
         let mut posts: Vec<Post> = vec![];
 
         for post in recent_posts {
@@ -102,6 +178,42 @@ impl PostStore for SqlPostStore {
 
         Ok(posts)
     }
+
+    async fn get_recent_by_author_id(
+        &self,
+        author_id: u64,
+        count: u8,
+    ) -> Result<Vec<Post>, EntityError> {
+        Ok(get_recent_by_author_id(&self.pool, author_id, count)
+            .await?
+            .into_iter()
+            .map(Post::from)
+            .collect())
+    }
+
+    async fn get_top_since(
+        &self,
+        since: DateTime<Utc>,
+        count: u8,
+    ) -> Result<Vec<Post>, EntityError> {
+        Ok(get_top_since(&self.pool, since, count)
+            .await?
+            .into_iter()
+            .map(Post::from)
+            .collect())
+    }
+
+    async fn get_count(&self) -> Result<i64, EntityError> {
+        get_count(&self.pool).await
+    }
+
+    async fn get_page(&self, page: u64, page_size: u32) -> Result<Vec<Post>, EntityError> {
+        Ok(get_page(&self.pool, page, page_size)
+            .await?
+            .into_iter()
+            .map(Post::from)
+            .collect())
+    }
 }
 
 async fn insert(
@@ -111,6 +223,7 @@ async fn insert(
     title: &str,
     link: &Option<String>,
     content: &Option<String>,
+    is_held: bool,
 ) -> Result<u64, EntityError> {
     // TODO: verify if author is a valid user?
     let sanitized_title = sanitize_title(title)?;
@@ -136,14 +249,16 @@ async fn insert(
 
     let post_id = sqlx::query!(
         r#"
-INSERT INTO posts (public_id, author_id, title, link, content_id, created, updated)
-VALUES (?, ?, ?, ?, ?, ?, ?)
+INSERT INTO posts (public_id, author_id, title, link, content_id, is_removed, is_locked, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         &public_id[..],
         author_id,
         sanitized_title,
         link,
         content_id,
+        is_held,
+        0,
         created,
         created
     )
@@ -187,10 +302,46 @@ WHERE public_id = ?
     Ok(post_entity)
 }
 
+async fn set_removed(pool: &MySqlPool, id: u64, is_removed: bool) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE posts
+SET is_removed = ?, updated = ?
+WHERE id = ?
+        "#,
+        is_removed,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn set_locked(pool: &MySqlPool, id: u64, is_locked: bool) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE posts
+SET is_locked = ?, updated = ?
+WHERE id = ?
+        "#,
+        is_locked,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 async fn get_recent(
     pool: &MySqlPool,
     start_index: Option<u64>,
     count: u8,
+    viewer_id: Option<u64>,
+    include_shadowbanned: bool,
 ) -> Result<Vec<PostEntity>, EntityError> {
     let post_entities = match start_index {
         Some(start_index) => {
@@ -199,11 +350,14 @@ async fn get_recent(
                 r#"
 SELECT *
 FROM posts
-WHERE id < ?
+WHERE id < ? AND is_removed = 0
+    AND (? OR author_id = ? OR author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1))
 ORDER BY id DESC
 LIMIT ?
                 "#,
                 start_index,
+                include_shadowbanned,
+                viewer_id,
                 count
             )
             .fetch_all(pool)
@@ -215,9 +369,13 @@ LIMIT ?
                 r#"
 SELECT *
 FROM posts
+WHERE is_removed = 0
+    AND (? OR author_id = ? OR author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1))
 ORDER BY id DESC
 LIMIT ?
                 "#,
+                include_shadowbanned,
+                viewer_id,
                 count
             )
             .fetch_all(pool)
@@ -228,6 +386,52 @@ LIMIT ?
     Ok(post_entities)
 }
 
+async fn get_recent_by_author_id(
+    pool: &MySqlPool,
+    author_id: u64,
+    count: u8,
+) -> Result<Vec<PostEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        PostEntity,
+        r#"
+SELECT *
+FROM posts
+WHERE author_id = ? AND is_removed = 0
+    AND author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1)
+ORDER BY id DESC
+LIMIT ?
+        "#,
+        author_id,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn get_top_since(
+    pool: &MySqlPool,
+    since: DateTime<Utc>,
+    count: u8,
+) -> Result<Vec<PostEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        PostEntity,
+        r#"
+SELECT p.*
+FROM posts p
+JOIN comments c ON c.post_id = p.id
+WHERE p.created >= ? AND p.is_removed = 0
+    AND p.author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1)
+GROUP BY p.id
+ORDER BY COUNT(c.id) DESC, p.id DESC
+LIMIT ?
+        "#,
+        since.naive_utc(),
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
 fn verify_link(link: &Option<String>) -> Result<Option<String>, EntityError> {
     match link {
         Some(l) => match Url::parse(l) {
@@ -248,3 +452,42 @@ fn verify_link(link: &Option<String>) -> Result<Option<String>, EntityError> {
 fn sanitize_title(title: &str) -> Result<String, EntityError> {
     utils::sanitize_text(title, MIN_TITLE_LENGTH, MAX_TITLE_LENGTH, "title")
 }
+
+async fn get_count(pool: &MySqlPool) -> Result<i64, EntityError> {
+    let count = sqlx::query!(
+        r#"
+SELECT
+    COUNT(id) as count
+FROM posts
+WHERE is_removed = 0
+    AND author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1)
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.count)
+}
+
+async fn get_page(
+    pool: &MySqlPool,
+    page: u64,
+    page_size: u32,
+) -> Result<Vec<PostEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        PostEntity,
+        r#"
+SELECT *
+FROM posts
+WHERE is_removed = 0
+    AND author_id NOT IN (SELECT id FROM users WHERE is_shadowbanned = 1)
+ORDER BY id ASC
+LIMIT ?
+OFFSET ?
+        "#,
+        page_size,
+        page * page_size as u64
+    )
+    .fetch_all(pool)
+    .await?)
+}
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 use crate::entities::EntityError;
 
@@ -6,21 +7,56 @@ use super::Post;
 
 #[async_trait]
 pub trait PostStore: Send + Sync + Clone {
+    /// Held posts are saved removed, and don't queue webhooks until they're published.
     async fn insert(
         &self,
         author_id: &u64,
         title: &str,
         link: &Option<String>,
         content: &Option<String>,
+        is_held: bool,
     ) -> Result<Post, EntityError>;
 
     async fn get_by_id(&self, id: u64) -> Result<Post, EntityError>;
 
     async fn get_by_public_id(&self, public_id: &str) -> Result<Post, EntityError>;
 
+    /// Removed posts are left out of every listing, but can still be looked up directly.
+    async fn set_removed(&self, id: u64, is_removed: bool) -> Result<Post, EntityError>;
+
+    /// Publishes a held post, queueing the webhooks it skipped when it was submitted.
+    async fn publish(&self, id: u64) -> Result<Post, EntityError>;
+
+    /// Locked posts don't accept new comments.
+    async fn set_locked(&self, id: u64, is_locked: bool) -> Result<Post, EntityError>;
+
+    /// Leaves out shadowbanned authors' posts, except `viewer_id`'s own so they don't notice, or
+    /// none of them when `include_shadowbanned` is set for a moderator. Only pass a viewer who is
+    /// shadowbanned, everyone else shares the same cached listing.
     async fn get_recent(
         &self,
         start_index: Option<u64>,
         count: u8,
+        viewer_id: Option<u64>,
+        include_shadowbanned: bool,
+    ) -> Result<Vec<Post>, EntityError>;
+
+    /// Nothing when the author is shadowbanned, the listings below leave their posts out too.
+    async fn get_recent_by_author_id(
+        &self,
+        author_id: u64,
+        count: u8,
     ) -> Result<Vec<Post>, EntityError>;
+
+    /// Posts created since `since` with the most comments first, leaving out posts without any.
+    async fn get_top_since(
+        &self,
+        since: DateTime<Utc>,
+        count: u8,
+    ) -> Result<Vec<Post>, EntityError>;
+
+    async fn get_count(&self) -> Result<i64, EntityError>;
+
+    /// Posts oldest first, so a page keeps the same posts as new ones are added.
+    async fn get_page(&self, page: u64, page_size: u32) -> Result<Vec<Post>, EntityError>;
 }
@@ -0,0 +1,7 @@
+mod report;
+mod report_sql;
+mod report_store;
+
+pub use report::{Report, ReportReason, ReportResolution, ReportedItem};
+pub use report_sql::{SqlReportStore, MAX_DETAILS_LENGTH};
+pub use report_store::ReportStore;
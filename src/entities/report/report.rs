@@ -0,0 +1,213 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why a post or comment was reported.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Abuse,
+    OffTopic,
+    Other,
+}
+
+impl ReportReason {
+    pub const ALL: [ReportReason; 4] = [
+        ReportReason::Spam,
+        ReportReason::Abuse,
+        ReportReason::OffTopic,
+        ReportReason::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportReason::Spam => "spam",
+            ReportReason::Abuse => "abuse",
+            ReportReason::OffTopic => "off_topic",
+            ReportReason::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for ReportReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ReportReason {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ReportReason, Self::Err> {
+        match input {
+            "spam" => Ok(ReportReason::Spam),
+            "abuse" => Ok(ReportReason::Abuse),
+            "off_topic" => Ok(ReportReason::OffTopic),
+            "other" => Ok(ReportReason::Other),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What a moderator did about a reported post or comment.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportResolution {
+    /// Nothing wrong with it.
+    Dismissed,
+    /// It was removed.
+    Removed,
+    /// It was removed and its author banned.
+    Banned,
+}
+
+impl ReportResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportResolution::Dismissed => "dismissed",
+            ReportResolution::Removed => "removed",
+            ReportResolution::Banned => "banned",
+        }
+    }
+}
+
+impl fmt::Display for ReportResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ReportResolution {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ReportResolution, Self::Err> {
+        match input {
+            "dismissed" => Ok(ReportResolution::Dismissed),
+            "removed" => Ok(ReportResolution::Removed),
+            "banned" => Ok(ReportResolution::Banned),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Report {
+    pub id: u64,
+    /// None when the spam filter held the post or comment, rather than someone reporting it.
+    pub reporter_id: Option<u64>,
+    pub post_id: u64,
+    /// Only set when a comment is reported, rather than the post.
+    pub comment_id: Option<u64>,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+    pub resolution: Option<ReportResolution>,
+    pub resolver_id: Option<u64>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// Every open report on one post or comment, so moderators deal with it once however many
+/// people reported it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportedItem {
+    pub post_id: u64,
+    pub comment_id: Option<u64>,
+    pub reports: Vec<Report>,
+}
+
+impl ReportedItem {
+    /// Groups reports by what they're about, most reported first, then whichever has waited
+    /// longest.
+    pub fn group(reports: Vec<Report>) -> Vec<ReportedItem> {
+        let mut items: Vec<ReportedItem> = vec![];
+        for report in reports {
+            match items
+                .iter_mut()
+                .find(|i| i.post_id == report.post_id && i.comment_id == report.comment_id)
+            {
+                Some(item) => item.reports.push(report),
+                None => items.push(ReportedItem {
+                    post_id: report.post_id,
+                    comment_id: report.comment_id,
+                    reports: vec![report],
+                }),
+            }
+        }
+
+        items.sort_by(|a, b| {
+            b.reports
+                .len()
+                .cmp(&a.reports.len())
+                .then(a.first_reported().cmp(&b.first_reported()))
+        });
+        items
+    }
+
+    /// Whether the spam filter held it back, so it hasn't been published yet.
+    pub fn is_held(&self) -> bool {
+        self.reports.iter().any(|r| r.reporter_id.is_none())
+    }
+
+    pub fn first_reported(&self) -> Option<DateTime<Utc>> {
+        self.reports.iter().map(|r| r.created).min()
+    }
+
+    /// How many times each reason was given, leaving out reasons nobody gave.
+    pub fn reason_counts(&self) -> Vec<(ReportReason, usize)> {
+        ReportReason::ALL
+            .into_iter()
+            .map(|reason| {
+                (
+                    reason,
+                    self.reports.iter().filter(|r| r.reason == reason).count(),
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn report(id: u64, post_id: u64, comment_id: Option<u64>, reason: ReportReason) -> Report {
+        let created = Utc::now() - Duration::minutes(100 - id as i64);
+        Report {
+            id,
+            reporter_id: Some(id),
+            post_id,
+            comment_id,
+            reason,
+            details: None,
+            resolution: None,
+            resolver_id: None,
+            created,
+            updated: created,
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let items = ReportedItem::group(vec![
+            report(1, 1, None, ReportReason::Spam),
+            report(2, 2, Some(5), ReportReason::Abuse),
+            report(3, 2, None, ReportReason::OffTopic),
+            report(4, 2, Some(5), ReportReason::Spam),
+            report(5, 2, Some(5), ReportReason::Abuse),
+        ]);
+
+        let keys: Vec<(u64, Option<u64>)> =
+            items.iter().map(|i| (i.post_id, i.comment_id)).collect();
+        assert_eq!(keys, [(2, Some(5)), (1, None), (2, None)]);
+        assert_eq!(
+            items[0].reason_counts(),
+            [(ReportReason::Spam, 1), (ReportReason::Abuse, 2)]
+        );
+        assert_eq!(items[0].first_reported(), Some(items[0].reports[0].created));
+    }
+}
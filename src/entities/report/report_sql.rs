@@ -0,0 +1,278 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{Report, ReportReason, ReportResolution, ReportStore};
+
+pub const MAX_DETAILS_LENGTH: usize = 1024;
+
+#[derive(Clone)]
+pub struct SqlReportStore {
+    pool: MySqlPool,
+}
+
+impl SqlReportStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ReportEntity {
+    pub id: u64,
+    pub reporter_id: Option<u64>,
+    pub post_id: u64,
+    pub comment_id: Option<u64>,
+    pub reason: String,
+    pub details: Option<String>,
+    pub resolution: Option<String>,
+    pub resolver_id: Option<u64>,
+    pub created: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+impl TryFrom<ReportEntity> for Report {
+    type Error = EntityError;
+
+    fn try_from(report_entity: ReportEntity) -> Result<Self, Self::Error> {
+        let reason = match ReportReason::from_str(&report_entity.reason) {
+            Ok(reason) => reason,
+            Err(_) => {
+                return Err(EntityError::Internal(format!(
+                    "unknown report reason {}",
+                    report_entity.reason
+                )))
+            }
+        };
+        let resolution = match report_entity.resolution {
+            Some(resolution) => match ReportResolution::from_str(&resolution) {
+                Ok(resolution) => Some(resolution),
+                Err(_) => {
+                    return Err(EntityError::Internal(format!(
+                        "unknown report resolution {}",
+                        resolution
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            id: report_entity.id,
+            reporter_id: report_entity.reporter_id,
+            post_id: report_entity.post_id,
+            comment_id: report_entity.comment_id,
+            reason,
+            details: report_entity.details,
+            resolution,
+            resolver_id: report_entity.resolver_id,
+            created: Utc.from_utc_datetime(&report_entity.created),
+            updated: Utc.from_utc_datetime(&report_entity.updated),
+        })
+    }
+}
+
+#[async_trait]
+impl ReportStore for SqlReportStore {
+    async fn insert(
+        &self,
+        reporter_id: Option<u64>,
+        post_id: u64,
+        comment_id: Option<u64>,
+        reason: ReportReason,
+        details: &Option<String>,
+    ) -> Result<Report, EntityError> {
+        let id = insert(
+            &self.pool,
+            reporter_id,
+            post_id,
+            comment_id,
+            reason,
+            details,
+        )
+        .await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn get_by_id(&self, id: u64) -> Result<Report, EntityError> {
+        Report::try_from(get_by_id(&self.pool, id).await?)
+    }
+
+    async fn is_held(&self, post_id: u64, comment_id: Option<u64>) -> Result<bool, EntityError> {
+        is_held(&self.pool, post_id, comment_id).await
+    }
+
+    async fn get_open(&self, count: u32) -> Result<Vec<Report>, EntityError> {
+        get_open(&self.pool, count)
+            .await?
+            .into_iter()
+            .map(Report::try_from)
+            .collect()
+    }
+
+    async fn resolve(
+        &self,
+        post_id: u64,
+        comment_id: Option<u64>,
+        resolver_id: u64,
+        resolution: ReportResolution,
+    ) -> Result<u64, EntityError> {
+        resolve(&self.pool, post_id, comment_id, resolver_id, resolution).await
+    }
+}
+
+async fn insert(
+    pool: &MySqlPool,
+    reporter_id: Option<u64>,
+    post_id: u64,
+    comment_id: Option<u64>,
+    reason: ReportReason,
+    details: &Option<String>,
+) -> Result<u64, EntityError> {
+    let details = sanitize_details(details)?;
+
+    // Reporting the same thing twice would count twice in the queue
+    let existing = sqlx::query!(
+        r#"
+SELECT id
+FROM reports
+WHERE reporter_id <=> ? AND post_id = ? AND comment_id <=> ? AND resolution IS NULL
+        "#,
+        reporter_id,
+        post_id,
+        comment_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    if existing.is_some() {
+        return Err(EntityError::DuplicateKey);
+    }
+
+    let now = Utc::now().naive_utc();
+    let report_id = sqlx::query!(
+        r#"
+INSERT INTO reports (reporter_id, post_id, comment_id, reason, details, resolution, resolver_id, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        reporter_id,
+        post_id,
+        comment_id,
+        reason.as_str(),
+        details,
+        Option::<String>::None,
+        Option::<u64>::None,
+        now,
+        now
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(report_id)
+}
+
+async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<ReportEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        ReportEntity,
+        r#"
+SELECT *
+FROM reports
+WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn is_held(
+    pool: &MySqlPool,
+    post_id: u64,
+    comment_id: Option<u64>,
+) -> Result<bool, EntityError> {
+    let held = sqlx::query!(
+        r#"
+SELECT id
+FROM reports
+WHERE reporter_id IS NULL AND post_id = ? AND comment_id <=> ? AND resolution IS NULL
+        "#,
+        post_id,
+        comment_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(held.is_some())
+}
+
+async fn get_open(pool: &MySqlPool, count: u32) -> Result<Vec<ReportEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        ReportEntity,
+        r#"
+SELECT *
+FROM reports
+WHERE resolution IS NULL
+ORDER BY id ASC
+LIMIT ?
+        "#,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn resolve(
+    pool: &MySqlPool,
+    post_id: u64,
+    comment_id: Option<u64>,
+    resolver_id: u64,
+    resolution: ReportResolution,
+) -> Result<u64, EntityError> {
+    let result = sqlx::query!(
+        r#"
+UPDATE reports
+SET resolution = ?, resolver_id = ?, updated = ?
+WHERE post_id = ? AND comment_id <=> ? AND resolution IS NULL
+        "#,
+        resolution.as_str(),
+        resolver_id,
+        Utc::now().naive_utc(),
+        post_id,
+        comment_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+fn sanitize_details(details: &Option<String>) -> Result<Option<String>, EntityError> {
+    match details.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(details) if details.len() > MAX_DETAILS_LENGTH => {
+            Err(EntityError::InvalidInput("details", "too long"))
+        }
+        Some(details) => Ok(Some(details.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_details() {
+        assert_eq!(sanitize_details(&None).unwrap(), None);
+        assert_eq!(sanitize_details(&Some("  ".to_owned())).unwrap(), None);
+        assert_eq!(
+            sanitize_details(&Some(" selling pills ".to_owned())).unwrap(),
+            Some("selling pills".to_owned())
+        );
+        assert!(sanitize_details(&Some("a".repeat(MAX_DETAILS_LENGTH + 1))).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::{Report, ReportReason, ReportResolution};
+
+#[async_trait]
+pub trait ReportStore: Send + Sync + Clone {
+    /// Reports a post, or one of its comments. Fails with `DuplicateKey` if the reporter already
+    /// has an open report on it. Without a reporter, it's the spam filter holding it for review.
+    async fn insert(
+        &self,
+        reporter_id: Option<u64>,
+        post_id: u64,
+        comment_id: Option<u64>,
+        reason: ReportReason,
+        details: &Option<String>,
+    ) -> Result<Report, EntityError>;
+
+    async fn get_by_id(&self, id: u64) -> Result<Report, EntityError>;
+
+    /// Whether the spam filter is holding the post, or one of its comments, for a moderator to
+    /// review.
+    async fn is_held(&self, post_id: u64, comment_id: Option<u64>) -> Result<bool, EntityError>;
+
+    /// Reports no moderator has resolved yet, oldest first.
+    async fn get_open(&self, count: u32) -> Result<Vec<Report>, EntityError>;
+
+    /// Resolves every open report on the post, or one of its comments, returning how many there
+    /// were.
+    async fn resolve(
+        &self,
+        post_id: u64,
+        comment_id: Option<u64>,
+        resolver_id: u64,
+        resolution: ReportResolution,
+    ) -> Result<u64, EntityError>;
+}
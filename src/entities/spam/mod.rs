@@ -0,0 +1,7 @@
+mod spam;
+mod spam_sql;
+mod spam_store;
+
+pub use spam::{TokenCounts, TrainingTotals};
+pub use spam_sql::SqlSpamStore;
+pub use spam_store::SpamStore;
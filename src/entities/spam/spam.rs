@@ -0,0 +1,14 @@
+/// How many spam and legitimate ("ham") posts and comments a token has appeared in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenCounts {
+    pub token: String,
+    pub spam: u64,
+    pub ham: u64,
+}
+
+/// How many spam and ham posts and comments the filter has been trained on.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrainingTotals {
+    pub spam: u64,
+    pub ham: u64,
+}
@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::MySqlPool;
+
+use crate::entities::{utils, EntityError};
+
+use super::{SpamStore, TokenCounts, TrainingTotals};
+
+#[derive(Clone)]
+pub struct SqlSpamStore {
+    pool: MySqlPool,
+}
+
+impl SqlSpamStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SpamTokenEntity {
+    pub token: String,
+    pub spam_count: u64,
+    pub ham_count: u64,
+}
+
+impl From<SpamTokenEntity> for TokenCounts {
+    fn from(token_entity: SpamTokenEntity) -> Self {
+        Self {
+            token: token_entity.token,
+            spam: token_entity.spam_count,
+            ham: token_entity.ham_count,
+        }
+    }
+}
+
+#[async_trait]
+impl SpamStore for SqlSpamStore {
+    async fn get_totals(&self) -> Result<TrainingTotals, EntityError> {
+        Ok(TrainingTotals {
+            spam: get_training_count(&self.pool, true).await?,
+            ham: get_training_count(&self.pool, false).await?,
+        })
+    }
+
+    async fn get_token_counts(&self, tokens: &[String]) -> Result<Vec<TokenCounts>, EntityError> {
+        Ok(get_token_counts(&self.pool, tokens)
+            .await?
+            .into_iter()
+            .map(TokenCounts::from)
+            .collect())
+    }
+
+    async fn train(
+        &self,
+        post_id: u64,
+        comment_id: Option<u64>,
+        tokens: &[String],
+        is_spam: bool,
+    ) -> Result<bool, EntityError> {
+        train(&self.pool, post_id, comment_id, tokens, is_spam).await
+    }
+}
+
+async fn get_training_count(pool: &MySqlPool, is_spam: bool) -> Result<u64, EntityError> {
+    let result = sqlx::query!(
+        r#"
+SELECT COUNT(*) AS count
+FROM spam_training
+WHERE is_spam = ?
+        "#,
+        is_spam
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count as u64)
+}
+
+async fn get_token_counts(
+    pool: &MySqlPool,
+    tokens: &[String],
+) -> Result<Vec<SpamTokenEntity>, EntityError> {
+    if tokens.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT * FROM spam_tokens WHERE token IN ({})",
+        utils::placeholders(tokens.len())
+    );
+    let mut query = sqlx::query_as::<_, SpamTokenEntity>(&sql);
+    for token in tokens {
+        query = query.bind(token);
+    }
+
+    Ok(query.fetch_all(pool).await?)
+}
+
+async fn train(
+    pool: &MySqlPool,
+    post_id: u64,
+    comment_id: Option<u64>,
+    tokens: &[String],
+    is_spam: bool,
+) -> Result<bool, EntityError> {
+    let mut transaction = pool.begin().await?;
+    let now = Utc::now().naive_utc();
+
+    // Locked so two moderators deciding at once can't both count it, the unique key on the trained
+    // post or comment makes the second insert fail if neither found a row
+    let existing = sqlx::query!(
+        r#"
+SELECT id, is_spam
+FROM spam_training
+WHERE post_id = ? AND comment_id <=> ?
+FOR UPDATE
+        "#,
+        post_id,
+        comment_id
+    )
+    .fetch_optional(&mut transaction)
+    .await?;
+
+    match existing {
+        Some(row) if (row.is_spam > 0) == is_spam => return Ok(false),
+        Some(row) => {
+            sqlx::query!(
+                r#"
+UPDATE spam_training
+SET is_spam = ?, updated = ?
+WHERE id = ?
+                "#,
+                is_spam,
+                now,
+                row.id
+            )
+            .execute(&mut transaction)
+            .await?;
+
+            // Take back what the earlier verdict counted
+            let (spam, ham) = counts(!is_spam);
+            for token in tokens {
+                sqlx::query!(
+                    r#"
+UPDATE spam_tokens
+SET spam_count = spam_count - ?, ham_count = ham_count - ?
+WHERE token = ?
+                    "#,
+                    spam,
+                    ham,
+                    token
+                )
+                .execute(&mut transaction)
+                .await?;
+            }
+        }
+        None => {
+            sqlx::query!(
+                r#"
+INSERT INTO spam_training (post_id, comment_id, is_spam, created, updated)
+VALUES (?, ?, ?, ?, ?)
+                "#,
+                post_id,
+                comment_id,
+                is_spam,
+                now,
+                now
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+    }
+
+    let (spam, ham) = counts(is_spam);
+    for token in tokens {
+        sqlx::query!(
+            r#"
+INSERT INTO spam_tokens (token, spam_count, ham_count)
+VALUES (?, ?, ?)
+ON DUPLICATE KEY UPDATE
+    spam_count = spam_count + VALUES(spam_count),
+    ham_count = ham_count + VALUES(ham_count)
+            "#,
+            token,
+            spam,
+            ham
+        )
+        .execute(&mut transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(true)
+}
+
+// What one spam or ham post or comment adds to each of its tokens' counts
+fn counts(is_spam: bool) -> (u64, u64) {
+    match is_spam {
+        true => (1, 0),
+        false => (0, 1),
+    }
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::{TokenCounts, TrainingTotals};
+
+#[async_trait]
+pub trait SpamStore: Send + Sync + Clone {
+    async fn get_totals(&self) -> Result<TrainingTotals, EntityError>;
+
+    /// Counts for the tokens that have been seen in training, the rest are left out.
+    async fn get_token_counts(&self, tokens: &[String]) -> Result<Vec<TokenCounts>, EntityError>;
+
+    /// Trains on a post, or one of its comments. Each is only counted once, so training it again
+    /// with a different verdict moves its tokens over. Returns whether anything changed.
+    async fn train(
+        &self,
+        post_id: u64,
+        comment_id: Option<u64>,
+        tokens: &[String],
+        is_spam: bool,
+    ) -> Result<bool, EntityError>;
+}
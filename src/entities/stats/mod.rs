@@ -0,0 +1,7 @@
+mod stats;
+mod stats_sql;
+mod stats_store;
+
+pub use stats::{Growth, SiteStats};
+pub use stats_sql::SqlStatsStore;
+pub use stats_store::StatsStore;
@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+/// How many there are, and how many of them are new.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Growth {
+    pub total: i64,
+    pub last_day: i64,
+    pub last_week: i64,
+    pub last_month: i64,
+}
+
+/// Counts for the admin dashboard, leaving out deleted users and removed posts and comments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct SiteStats {
+    pub users: Growth,
+    pub posts: Growth,
+    pub comments: Growth,
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDateTime, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::EntityError;
+
+use super::{Growth, SiteStats, StatsStore};
+
+#[derive(Clone)]
+pub struct SqlStatsStore {
+    pool: MySqlPool,
+}
+
+impl SqlStatsStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct GrowthEntity {
+    pub total: i64,
+    pub last_day: i64,
+    pub last_week: i64,
+    pub last_month: i64,
+}
+
+impl From<GrowthEntity> for Growth {
+    fn from(growth_entity: GrowthEntity) -> Self {
+        Self {
+            total: growth_entity.total,
+            last_day: growth_entity.last_day,
+            last_week: growth_entity.last_week,
+            last_month: growth_entity.last_month,
+        }
+    }
+}
+
+#[async_trait]
+impl StatsStore for SqlStatsStore {
+    async fn get_site_stats(&self) -> Result<SiteStats, EntityError> {
+        let now = Utc::now().naive_utc();
+
+        Ok(SiteStats {
+            users: get_growth(&self.pool, "users", "is_deleted = 0", now).await?,
+            posts: get_growth(&self.pool, "posts", "is_removed = 0", now).await?,
+            comments: get_growth(&self.pool, "comments", "is_removed = 0", now).await?,
+        })
+    }
+}
+
+// The table and condition are always one of the constants above, never user input
+async fn get_growth(
+    pool: &MySqlPool,
+    table: &str,
+    condition: &str,
+    now: NaiveDateTime,
+) -> Result<Growth, EntityError> {
+    let sql = format!(
+        r#"
+SELECT
+    COUNT(id) AS total,
+    COUNT(CASE WHEN created >= ? THEN 1 END) AS last_day,
+    COUNT(CASE WHEN created >= ? THEN 1 END) AS last_week,
+    COUNT(CASE WHEN created >= ? THEN 1 END) AS last_month
+FROM {}
+WHERE {}
+        "#,
+        table, condition
+    );
+
+    let growth_entity = sqlx::query_as::<_, GrowthEntity>(&sql)
+        .bind(now - Duration::days(1))
+        .bind(now - Duration::weeks(1))
+        .bind(now - Duration::days(30))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Growth::from(growth_entity))
+}
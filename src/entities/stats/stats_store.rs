@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::SiteStats;
+
+#[async_trait]
+pub trait StatsStore: Send + Sync + Clone {
+    async fn get_site_stats(&self) -> Result<SiteStats, EntityError>;
+}
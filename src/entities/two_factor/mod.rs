@@ -0,0 +1,7 @@
+mod two_factor;
+mod two_factor_sql;
+mod two_factor_store;
+
+pub use two_factor::TwoFactor;
+pub use two_factor_sql::SqlTwoFactorStore;
+pub use two_factor_store::TwoFactorStore;
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TwoFactor {
+    pub id: u64,
+    pub user_id: u64,
+    pub secret: Vec<u8>,
+    pub is_enabled: bool,
+    pub last_used_step: Option<u64>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
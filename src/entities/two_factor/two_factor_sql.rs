@@ -0,0 +1,270 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use sqlx::MySqlPool;
+
+use crate::entities::{utils, EntityError};
+
+use super::{TwoFactor, TwoFactorStore};
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Clone)]
+pub struct SqlTwoFactorStore {
+    pool: MySqlPool,
+}
+
+impl SqlTwoFactorStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct TwoFactorEntity {
+    pub id: u64,
+    pub user_id: u64,
+    pub secret: Vec<u8>,
+    pub is_enabled: i8,
+    pub last_used_step: Option<u64>,
+    pub created: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+impl From<TwoFactorEntity> for TwoFactor {
+    fn from(two_factor_entity: TwoFactorEntity) -> Self {
+        Self {
+            id: two_factor_entity.id,
+            user_id: two_factor_entity.user_id,
+            secret: two_factor_entity.secret,
+            is_enabled: two_factor_entity.is_enabled > 0,
+            last_used_step: two_factor_entity.last_used_step,
+            created: Utc.from_utc_datetime(&two_factor_entity.created),
+            updated: Utc.from_utc_datetime(&two_factor_entity.updated),
+        }
+    }
+}
+
+#[async_trait]
+impl TwoFactorStore for SqlTwoFactorStore {
+    async fn get_by_user_id(&self, user_id: u64) -> Result<TwoFactor, EntityError> {
+        Ok(TwoFactor::from(get_by_user_id(&self.pool, user_id).await?))
+    }
+
+    async fn begin_enrollment(
+        &self,
+        user_id: u64,
+        secret: &[u8],
+    ) -> Result<TwoFactor, EntityError> {
+        begin_enrollment(&self.pool, user_id, secret).await?;
+
+        self.get_by_user_id(user_id).await
+    }
+
+    async fn enable(&self, user_id: u64) -> Result<Vec<String>, EntityError> {
+        enable(&self.pool, user_id).await?;
+
+        self.regenerate_recovery_codes(user_id).await
+    }
+
+    async fn regenerate_recovery_codes(&self, user_id: u64) -> Result<Vec<String>, EntityError> {
+        regenerate_recovery_codes(&self.pool, user_id).await
+    }
+
+    async fn use_step(&self, user_id: u64, step: u64) -> Result<(), EntityError> {
+        use_step(&self.pool, user_id, step).await
+    }
+
+    async fn use_recovery_code(&self, user_id: u64, code: &str) -> Result<(), EntityError> {
+        use_recovery_code(&self.pool, user_id, code).await
+    }
+
+    async fn delete(&self, user_id: u64) -> Result<(), EntityError> {
+        delete(&self.pool, user_id).await
+    }
+}
+
+async fn get_by_user_id(pool: &MySqlPool, user_id: u64) -> Result<TwoFactorEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        TwoFactorEntity,
+        r#"
+SELECT *
+FROM two_factors
+WHERE user_id = ?
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn begin_enrollment(
+    pool: &MySqlPool,
+    user_id: u64,
+    secret: &[u8],
+) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+DELETE FROM two_factors
+WHERE user_id = ? AND is_enabled = 0
+        "#,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    let now = Utc::now().naive_utc();
+    sqlx::query!(
+        r#"
+INSERT INTO two_factors (user_id, secret, is_enabled, last_used_step, created, updated)
+VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        user_id,
+        secret,
+        0,
+        Option::<u64>::None,
+        now,
+        now
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn enable(pool: &MySqlPool, user_id: u64) -> Result<(), EntityError> {
+    let result = sqlx::query!(
+        r#"
+UPDATE two_factors
+SET is_enabled = ?, updated = ?
+WHERE user_id = ? AND is_enabled = 0
+        "#,
+        1,
+        Utc::now().naive_utc(),
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    match result.rows_affected() {
+        0 => Err(EntityError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+async fn regenerate_recovery_codes(
+    pool: &MySqlPool,
+    user_id: u64,
+) -> Result<Vec<String>, EntityError> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+DELETE FROM recovery_codes
+WHERE user_id = ?
+        "#,
+        user_id
+    )
+    .execute(&mut transaction)
+    .await?;
+
+    let created = Utc::now().naive_utc();
+    let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+    for _ in 0..RECOVERY_CODE_COUNT {
+        let code = utils::generate_recovery_code();
+        let code_hash = utils::hash_recovery_code(&code)?;
+
+        sqlx::query!(
+            r#"
+INSERT INTO recovery_codes (user_id, code_hash, is_used, created)
+VALUES (?, ?, ?, ?)
+            "#,
+            user_id,
+            code_hash,
+            0,
+            created
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        codes.push(code);
+    }
+
+    transaction.commit().await?;
+
+    Ok(codes)
+}
+
+async fn use_step(pool: &MySqlPool, user_id: u64, step: u64) -> Result<(), EntityError> {
+    // Conditional update so two concurrent requests can't both use the same code
+    let result = sqlx::query!(
+        r#"
+UPDATE two_factors
+SET last_used_step = ?, updated = ?
+WHERE
+    user_id = ?
+    AND (last_used_step IS NULL OR last_used_step < ?)
+        "#,
+        step,
+        Utc::now().naive_utc(),
+        user_id,
+        step
+    )
+    .execute(pool)
+    .await?;
+
+    match result.rows_affected() {
+        0 => Err(EntityError::InvalidInput("code", "code already used")),
+        _ => Ok(()),
+    }
+}
+
+async fn use_recovery_code(pool: &MySqlPool, user_id: u64, code: &str) -> Result<(), EntityError> {
+    let code_hash = utils::hash_recovery_code(code)?;
+
+    let result = sqlx::query!(
+        r#"
+UPDATE recovery_codes
+SET is_used = ?
+WHERE user_id = ? AND code_hash = ? AND is_used = 0
+LIMIT 1
+        "#,
+        1,
+        user_id,
+        code_hash
+    )
+    .execute(pool)
+    .await?;
+
+    match result.rows_affected() {
+        0 => Err(EntityError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+async fn delete(pool: &MySqlPool, user_id: u64) -> Result<(), EntityError> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+DELETE FROM recovery_codes
+WHERE user_id = ?
+        "#,
+        user_id
+    )
+    .execute(&mut transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+DELETE FROM two_factors
+WHERE user_id = ?
+        "#,
+        user_id
+    )
+    .execute(&mut transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
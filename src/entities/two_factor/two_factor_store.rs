@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+
+use crate::entities::EntityError;
+
+use super::TwoFactor;
+
+#[async_trait]
+pub trait TwoFactorStore: Send + Sync + Clone {
+    async fn get_by_user_id(&self, user_id: u64) -> Result<TwoFactor, EntityError>;
+
+    /// Stores a new secret that isn't enabled until confirmed with `enable`, replacing any
+    /// unconfirmed one. Fails with `DuplicateKey` if the user already has 2FA enabled.
+    async fn begin_enrollment(&self, user_id: u64, secret: &[u8])
+        -> Result<TwoFactor, EntityError>;
+
+    /// Enables the pending secret and returns a fresh set of plaintext recovery codes.
+    async fn enable(&self, user_id: u64) -> Result<Vec<String>, EntityError>;
+
+    /// Replaces all of the user's recovery codes, returning the new plaintext codes.
+    async fn regenerate_recovery_codes(&self, user_id: u64) -> Result<Vec<String>, EntityError>;
+
+    /// Records `step` as used, failing with `InvalidInput` if it isn't newer than the last used step.
+    async fn use_step(&self, user_id: u64, step: u64) -> Result<(), EntityError>;
+
+    /// Marks a recovery code as used, failing with `NotFound` if it doesn't exist or was already used.
+    async fn use_recovery_code(&self, user_id: u64, code: &str) -> Result<(), EntityError>;
+
+    /// Removes the user's 2FA secret and recovery codes.
+    async fn delete(&self, user_id: u64) -> Result<(), EntityError>;
+}
@@ -3,6 +3,8 @@ mod user_cache;
 mod user_sql;
 mod user_store;
 
+pub use user::Permission;
+pub use user::Role;
 pub use user::User;
 pub use user_cache::CachedUserStore;
 pub use user_sql::SqlUserStore;
@@ -10,4 +12,5 @@ pub use user_sql::MAX_PASSWORD_LENGTH;
 pub use user_sql::MAX_USERNAME_LENGTH;
 pub use user_sql::MIN_PASSWORD_LENGTH;
 pub use user_sql::MIN_USERNAME_LENGTH;
+pub use user_sql::VERIFICATION_TOKEN_EXPIRY_HOURS;
 pub use user_store::UserStore;
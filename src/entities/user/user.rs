@@ -1,13 +1,119 @@
+use std::{fmt, str::FromStr};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// What a user is allowed to do beyond posting and commenting. Ordered from least to most
+/// privileged, so roles can be compared to check who outranks whom.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::User, Role::Moderator, Role::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::RemoveContent | Permission::LockThread | Permission::BanUser => {
+                *self >= Role::Moderator
+            }
+            Permission::ManageRoles
+            | Permission::ResetTwoFactor
+            | Permission::ResetPassword
+            | Permission::ManageWebhooks
+            | Permission::ViewModerationLog
+            | Permission::ViewDashboard => *self == Role::Admin,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Role, Self::Err> {
+        match input {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Privileged actions, each granted to some roles by `Role::can`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Permission {
+    RemoveContent,
+    LockThread,
+    BanUser,
+    ManageRoles,
+    ResetTwoFactor,
+    ResetPassword,
+    ManageWebhooks,
+    ViewModerationLog,
+    ViewDashboard,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct User {
     pub id: u64,
     pub public_id: String,
     pub name: String,
     pub email_id: u64,
+    /// Verified per user rather than per address, since several accounts can share an address.
+    pub is_email_verified: bool,
+    pub role: Role,
+    pub is_banned: bool,
+    /// Shadowbanned users' posts and comments are only shown to themselves and moderators.
+    pub is_shadowbanned: bool,
+    /// Set by an admin, the user has to choose a new password before they can log in again.
+    pub must_reset_password: bool,
     pub is_deleted: bool,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_can() {
+        assert!(!Role::User.can(Permission::RemoveContent));
+        assert!(Role::Moderator.can(Permission::RemoveContent));
+        assert!(Role::Moderator.can(Permission::BanUser));
+        assert!(!Role::Moderator.can(Permission::ManageRoles));
+        assert!(Role::Admin.can(Permission::LockThread));
+        assert!(Role::Admin.can(Permission::ManageWebhooks));
+        assert!(!Role::Moderator.can(Permission::ViewModerationLog));
+        assert!(!Role::Moderator.can(Permission::ViewDashboard));
+        assert!(Role::Admin.can(Permission::ResetPassword));
+    }
+
+    #[test]
+    fn test_role_from_str() {
+        for role in Role::ALL {
+            assert_eq!(role.as_str().parse(), Ok(role));
+        }
+        assert_eq!("root".parse::<Role>(), Err(()));
+        assert!(Role::Admin > Role::Moderator && Role::Moderator > Role::User);
+    }
+}
@@ -1,9 +1,13 @@
 use async_trait::async_trait;
+use chrono::Duration;
 use secrecy::Secret;
 
-use crate::entities::{cache::Cache, EntityError};
+use crate::entities::{
+    cache::{Cache, CacheStats},
+    EntityError,
+};
 
-use super::{User, UserStore};
+use super::{Role, User, UserStore};
 
 #[derive(Clone)]
 pub struct CachedUserStore<T>
@@ -21,6 +25,10 @@ where
     pub fn new(cache: Cache, source: T) -> Self {
         Self { cache, source }
     }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
 }
 
 #[async_trait]
@@ -43,6 +51,16 @@ where
             .await
     }
 
+    async fn insert_external(&self, name: &str, email: &str) -> Result<User, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.insert_external(name, email).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
     async fn get_by_name_password(
         &self,
         name: &str,
@@ -76,6 +94,18 @@ where
             .await
     }
 
+    async fn get_by_ids(&self, ids: &[u64]) -> Result<Vec<User>, EntityError> {
+        self.cache
+            .get_many_cached(
+                ids,
+                build_id_key,
+                |missing| async move { self.source.get_by_ids(&missing).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
     async fn get_by_public_id(&self, public_id: &str) -> Result<User, EntityError> {
         let key = build_public_id_key(public_id);
         self.cache
@@ -87,6 +117,114 @@ where
             )
             .await
     }
+
+    async fn get_count(&self) -> Result<i64, EntityError> {
+        let key = "count".to_owned();
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_count().await },
+                |_| vec![key],
+                Some(Duration::minutes(10)),
+            )
+            .await
+    }
+
+    async fn get_page(&self, page: u64, page_size: u32) -> Result<Vec<User>, EntityError> {
+        let key = format!("page:{}:{}", page, page_size);
+        self.cache
+            .get_cached(
+                key.clone(),
+                || async { self.source.get_page(page, page_size).await },
+                |_| vec![key],
+                Some(Duration::minutes(10)),
+            )
+            .await
+    }
+
+    async fn get_recent(&self, count: u32) -> Result<Vec<User>, EntityError> {
+        // Never cached, so the admin dashboard shows signups as they happen
+        self.source.get_recent(count).await
+    }
+
+    async fn search_by_name(&self, query: &str, count: u32) -> Result<Vec<User>, EntityError> {
+        self.source.search_by_name(query, count).await
+    }
+
+    async fn set_role(&self, id: u64, role: Role) -> Result<User, EntityError> {
+        // Overwrite the cached user so the new role applies on their next request
+        self.cache
+            .insert_cached(
+                || async { self.source.set_role(id, role).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn set_banned(&self, id: u64, is_banned: bool) -> Result<User, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.set_banned(id, is_banned).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn set_shadowbanned(&self, id: u64, is_shadowbanned: bool) -> Result<User, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.set_shadowbanned(id, is_shadowbanned).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn set_must_reset_password(
+        &self,
+        id: u64,
+        must_reset_password: bool,
+    ) -> Result<User, EntityError> {
+        self.cache
+            .insert_cached(
+                || async {
+                    self.source
+                        .set_must_reset_password(id, must_reset_password)
+                        .await
+                },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn set_password(&self, id: u64, password: &Secret<String>) -> Result<User, EntityError> {
+        self.cache
+            .insert_cached(
+                || async { self.source.set_password(id, password).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
+
+    async fn create_email_verification_token(&self, id: u64) -> Result<String, EntityError> {
+        // Never cache because it's a secret
+        self.source.create_email_verification_token(id).await
+    }
+
+    async fn verify_email(&self, token: &str) -> Result<User, EntityError> {
+        // Overwrite the cached (unverified) user with the updated one
+        self.cache
+            .insert_cached(
+                || async { self.source.verify_email(token).await },
+                build_keys,
+                None,
+            )
+            .await
+    }
 }
 
 fn build_keys(user: &User) -> Vec<String> {
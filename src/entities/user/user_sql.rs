@@ -1,7 +1,7 @@
 use std::str;
 
 use async_trait::async_trait;
-use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono::{Duration, NaiveDateTime, TimeZone, Utc};
 use hex::ToHex;
 use pbkdf2::pbkdf2_hmac_array;
 use secrecy::{ExposeSecret, Secret};
@@ -11,12 +11,17 @@ use uuid::Uuid;
 
 use crate::entities::{email::EmailStore, entity_stores::CachedSqlEmailStore, utils, EntityError};
 
-use super::{User, UserStore};
+use super::{Role, User, UserStore};
 
 pub const MIN_USERNAME_LENGTH: usize = 4;
 pub const MAX_USERNAME_LENGTH: usize = 32;
 pub const MIN_PASSWORD_LENGTH: usize = 8;
 pub const MAX_PASSWORD_LENGTH: usize = 256;
+pub const VERIFICATION_TOKEN_EXPIRY_HOURS: i64 = 48;
+
+// Stored instead of a password hash for users created by an external identity provider.
+// It has no salt or hash function, so no password can ever match it.
+const EXTERNAL_PASSWORD: &str = "external";
 
 #[derive(Clone)]
 pub struct SqlUserStore {
@@ -36,7 +41,12 @@ struct UserEntity {
     pub public_id: Vec<u8>,
     pub name: String,
     pub email_id: u64,
+    pub is_email_verified: i8,
     pub password: String,
+    pub role: String,
+    pub is_banned: i8,
+    pub is_shadowbanned: i8,
+    pub must_reset_password: i8,
     pub is_deleted: i8,
     pub created: NaiveDateTime,
     pub updated: NaiveDateTime,
@@ -49,6 +59,12 @@ impl From<UserEntity> for User {
             public_id: utils::get_readable_public_id(user_entity.public_id),
             name: user_entity.name,
             email_id: user_entity.email_id,
+            is_email_verified: user_entity.is_email_verified > 0,
+            // Unknown roles get no privileges rather than failing every page the user shows up on
+            role: user_entity.role.parse().unwrap_or(Role::User),
+            is_banned: user_entity.is_banned > 0,
+            is_shadowbanned: user_entity.is_shadowbanned > 0,
+            must_reset_password: user_entity.must_reset_password > 0,
             is_deleted: user_entity.is_deleted > 0,
             created: Utc.from_utc_datetime(&user_entity.created),
             updated: Utc.from_utc_datetime(&user_entity.updated),
@@ -56,6 +72,16 @@ impl From<UserEntity> for User {
     }
 }
 
+// A verification token along with the address the user has now, which may not be the one the
+// token was sent to
+#[derive(Debug, sqlx::FromRow)]
+struct EmailVerificationEntity {
+    pub user_id: u64,
+    pub email_id: u64,
+    pub current_email_id: u64,
+    pub expires: NaiveDateTime,
+}
+
 #[async_trait]
 impl UserStore for SqlUserStore {
     async fn insert(
@@ -69,6 +95,12 @@ impl UserStore for SqlUserStore {
         Ok(self.get_by_id(user_id).await?)
     }
 
+    async fn insert_external(&self, name: &str, email: &str) -> Result<User, EntityError> {
+        let user_id = insert_external(&self.pool, &self.email_store, name, email).await?;
+
+        Ok(self.get_by_id(user_id).await?)
+    }
+
     async fn get_by_name_password(
         &self,
         name: &str,
@@ -87,11 +119,91 @@ impl UserStore for SqlUserStore {
         Ok(User::from(get_by_id(&self.pool, id).await?))
     }
 
+    async fn get_by_ids(&self, ids: &[u64]) -> Result<Vec<User>, EntityError> {
+        Ok(get_by_ids(&self.pool, ids)
+            .await?
+            .into_iter()
+            .map(User::from)
+            .collect())
+    }
+
     async fn get_by_public_id(&self, public_id: &str) -> Result<User, EntityError> {
         let public_id = utils::parse_public_id(public_id)?;
 
         Ok(User::from(get_by_public_id(&self.pool, public_id).await?))
     }
+
+    async fn get_count(&self) -> Result<i64, EntityError> {
+        get_count(&self.pool).await
+    }
+
+    async fn get_page(&self, page: u64, page_size: u32) -> Result<Vec<User>, EntityError> {
+        Ok(get_page(&self.pool, page, page_size)
+            .await?
+            .into_iter()
+            .map(User::from)
+            .collect())
+    }
+
+    async fn get_recent(&self, count: u32) -> Result<Vec<User>, EntityError> {
+        Ok(get_recent(&self.pool, count)
+            .await?
+            .into_iter()
+            .map(User::from)
+            .collect())
+    }
+
+    async fn search_by_name(&self, query: &str, count: u32) -> Result<Vec<User>, EntityError> {
+        Ok(search_by_name(&self.pool, query, count)
+            .await?
+            .into_iter()
+            .map(User::from)
+            .collect())
+    }
+
+    async fn set_role(&self, id: u64, role: Role) -> Result<User, EntityError> {
+        set_role(&self.pool, id, role).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn set_banned(&self, id: u64, is_banned: bool) -> Result<User, EntityError> {
+        set_banned(&self.pool, id, is_banned).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn set_shadowbanned(&self, id: u64, is_shadowbanned: bool) -> Result<User, EntityError> {
+        set_shadowbanned(&self.pool, id, is_shadowbanned).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn set_must_reset_password(
+        &self,
+        id: u64,
+        must_reset_password: bool,
+    ) -> Result<User, EntityError> {
+        set_must_reset_password(&self.pool, id, must_reset_password).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn set_password(&self, id: u64, password: &Secret<String>) -> Result<User, EntityError> {
+        set_password(&self.pool, id, password).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn create_email_verification_token(&self, id: u64) -> Result<String, EntityError> {
+        create_email_verification_token(&self.pool, id).await
+    }
+
+    async fn verify_email(&self, token: &str) -> Result<User, EntityError> {
+        let user_id = verify_email(&self.pool, token).await?;
+
+        self.get_by_id(user_id).await
+    }
 }
 
 async fn insert(
@@ -101,38 +213,50 @@ async fn insert(
     email: &str,
     password: &Secret<String>,
 ) -> Result<u64, EntityError> {
-    if password.expose_secret().len() > MAX_PASSWORD_LENGTH {
-        return Err(EntityError::InvalidInput(
-            "password",
-            "password is too long",
-        ));
-    }
-    if password.expose_secret().len() < MIN_PASSWORD_LENGTH {
-        return Err(EntityError::InvalidInput(
-            "password",
-            "password is too short",
-        ));
-    }
+    let password = hash_new_password(password)?;
 
     let email = email_store.get_or_create(email).await?;
 
-    let public_id = Uuid::new_v4().into_bytes();
+    insert_with_password(pool, name, email.id, false, &password).await
+}
 
-    let salt_uuid = Uuid::new_v4().simple().to_string();
-    let salt = salt_uuid[..6].as_bytes();
-    let password = hash_password(password, salt);
+async fn insert_external(
+    pool: &MySqlPool,
+    email_store: &CachedSqlEmailStore,
+    name: &str,
+    email: &str,
+) -> Result<u64, EntityError> {
+    let email = email_store.get_or_create(email).await?;
+
+    // Providers only share an email address they've verified themselves
+    insert_with_password(pool, name, email.id, true, EXTERNAL_PASSWORD).await
+}
+
+async fn insert_with_password(
+    pool: &MySqlPool,
+    name: &str,
+    email_id: u64,
+    is_email_verified: bool,
+    password: &str,
+) -> Result<u64, EntityError> {
+    let public_id = Uuid::new_v4().into_bytes();
 
     let created = Utc::now().naive_utc();
 
     let user_id = sqlx::query!(
         r#"
-INSERT INTO users (public_id, name, email_id, password, is_deleted, created, updated)
-VALUES (?, ?, ?, ?, ?, ?, ?)
+INSERT INTO users (public_id, name, email_id, is_email_verified, password, role, is_banned, is_shadowbanned, must_reset_password, is_deleted, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         &public_id[..],
         sanitize_name(name)?,
-        email.id,
+        email_id,
+        is_email_verified,
         password,
+        Role::User.as_str(),
+        0,
+        0,
+        0,
         0,
         created,
         created
@@ -150,6 +274,9 @@ async fn get_by_name_password(
     password: &Secret<String>,
 ) -> Result<UserEntity, EntityError> {
     let user_entity = get_by_name(pool, name).await?;
+    if user_entity.password == EXTERNAL_PASSWORD {
+        return Err(EntityError::InvalidInput("password", "no password set"));
+    }
 
     // password verification
     let parts: Vec<&str> = user_entity.password.split(':').collect();
@@ -182,6 +309,23 @@ WHERE name = ?
     Ok(user_entity)
 }
 
+async fn get_by_ids(pool: &MySqlPool, ids: &[u64]) -> Result<Vec<UserEntity>, EntityError> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT * FROM users WHERE id IN ({})",
+        utils::placeholders(ids.len())
+    );
+    let mut query = sqlx::query_as::<_, UserEntity>(&sql);
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    Ok(query.fetch_all(pool).await?)
+}
+
 async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<UserEntity, EntityError> {
     let user_entity = sqlx::query_as!(
         UserEntity,
@@ -215,6 +359,307 @@ WHERE public_id = ?
     Ok(user_entity)
 }
 
+async fn get_count(pool: &MySqlPool) -> Result<i64, EntityError> {
+    let count = sqlx::query!(
+        r#"
+SELECT
+    COUNT(id) as count
+FROM users
+WHERE is_deleted = 0
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count.count)
+}
+
+async fn get_page(
+    pool: &MySqlPool,
+    page: u64,
+    page_size: u32,
+) -> Result<Vec<UserEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        UserEntity,
+        r#"
+SELECT *
+FROM users
+WHERE is_deleted = 0
+ORDER BY id ASC
+LIMIT ?
+OFFSET ?
+        "#,
+        page_size,
+        page * page_size as u64
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn get_recent(pool: &MySqlPool, count: u32) -> Result<Vec<UserEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        UserEntity,
+        r#"
+SELECT *
+FROM users
+WHERE is_deleted = 0
+ORDER BY id DESC
+LIMIT ?
+        "#,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn search_by_name(
+    pool: &MySqlPool,
+    query: &str,
+    count: u32,
+) -> Result<Vec<UserEntity>, EntityError> {
+    // Names are stored lowercase, and LIKE's wildcards in the query are matched literally
+    let pattern = format!(
+        "%{}%",
+        query
+            .trim()
+            .to_lowercase()
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    Ok(sqlx::query_as!(
+        UserEntity,
+        r#"
+SELECT *
+FROM users
+WHERE name LIKE ?
+ORDER BY CHAR_LENGTH(name) ASC, name ASC
+LIMIT ?
+        "#,
+        pattern,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn set_role(pool: &MySqlPool, id: u64, role: Role) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE users
+SET role = ?, updated = ?
+WHERE id = ?
+        "#,
+        role.as_str(),
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn set_banned(pool: &MySqlPool, id: u64, is_banned: bool) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE users
+SET is_banned = ?, updated = ?
+WHERE id = ?
+        "#,
+        is_banned,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn set_shadowbanned(
+    pool: &MySqlPool,
+    id: u64,
+    is_shadowbanned: bool,
+) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE users
+SET is_shadowbanned = ?, updated = ?
+WHERE id = ?
+        "#,
+        is_shadowbanned,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn set_must_reset_password(
+    pool: &MySqlPool,
+    id: u64,
+    must_reset_password: bool,
+) -> Result<(), EntityError> {
+    sqlx::query!(
+        r#"
+UPDATE users
+SET must_reset_password = ?, updated = ?
+WHERE id = ?
+        "#,
+        must_reset_password,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn set_password(
+    pool: &MySqlPool,
+    id: u64,
+    password: &Secret<String>,
+) -> Result<(), EntityError> {
+    let password = hash_new_password(password)?;
+
+    sqlx::query!(
+        r#"
+UPDATE users
+SET password = ?, must_reset_password = 0, updated = ?
+WHERE id = ?
+        "#,
+        password,
+        Utc::now().naive_utc(),
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_email_verification_token(
+    pool: &MySqlPool,
+    user_id: u64,
+) -> Result<String, EntityError> {
+    let token = utils::generate_secret_token();
+    let token_hash = utils::hash_secret_token(&token)?;
+
+    let created = Utc::now().naive_utc();
+
+    sqlx::query!(
+        r#"
+INSERT INTO email_verifications (user_id, email_id, token_hash, created, expires)
+SELECT id, email_id, ?, ?, ?
+FROM users
+WHERE id = ?
+        "#,
+        token_hash,
+        created,
+        verification_expires(created),
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(token)
+}
+
+async fn verify_email(pool: &MySqlPool, token: &str) -> Result<u64, EntityError> {
+    let token_hash = utils::hash_secret_token(token)?;
+
+    let verification = sqlx::query_as!(
+        EmailVerificationEntity,
+        r#"
+SELECT v.user_id, v.email_id, u.email_id AS current_email_id, v.expires
+FROM email_verifications v
+JOIN users u ON u.id = v.user_id
+WHERE v.token_hash = ?
+        "#,
+        token_hash
+    )
+    .fetch_one(pool)
+    .await?;
+    check_verification(&verification, Utc::now().naive_utc())?;
+
+    sqlx::query!(
+        r#"
+UPDATE users
+SET is_email_verified = 1, updated = ?
+WHERE id = ?
+        "#,
+        Utc::now().naive_utc(),
+        verification.user_id
+    )
+    .execute(pool)
+    .await?;
+
+    // Any other outstanding tokens for this user are no longer needed
+    sqlx::query!(
+        r#"
+DELETE FROM email_verifications
+WHERE user_id = ?
+        "#,
+        verification.user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(verification.user_id)
+}
+
+fn verification_expires(created: NaiveDateTime) -> NaiveDateTime {
+    created + Duration::hours(VERIFICATION_TOKEN_EXPIRY_HOURS)
+}
+
+/// Tokens verify the user they were sent to, and only until they expire or the user's address
+/// changes.
+fn check_verification(
+    verification: &EmailVerificationEntity,
+    now: NaiveDateTime,
+) -> Result<(), EntityError> {
+    if verification.expires <= now {
+        return Err(EntityError::InvalidInput(
+            "token",
+            "verification link has expired",
+        ));
+    }
+    if verification.email_id != verification.current_email_id {
+        return Err(EntityError::InvalidInput(
+            "token",
+            "verification link was sent to a different email address",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the password's length and hashes it with a new salt.
+fn hash_new_password(password: &Secret<String>) -> Result<String, EntityError> {
+    if password.expose_secret().len() > MAX_PASSWORD_LENGTH {
+        return Err(EntityError::InvalidInput(
+            "password",
+            "password is too long",
+        ));
+    }
+    if password.expose_secret().len() < MIN_PASSWORD_LENGTH {
+        return Err(EntityError::InvalidInput(
+            "password",
+            "password is too short",
+        ));
+    }
+
+    let salt_uuid = Uuid::new_v4().simple().to_string();
+    let salt = salt_uuid[..6].as_bytes();
+
+    Ok(hash_password(password, salt))
+}
+
 fn hash_password(password: &Secret<String>, salt: &[u8]) -> String {
     const HASH_FUNC: &str = "sha256_1024";
     const SEPARATOR: &str = ":";
@@ -235,3 +680,47 @@ fn sanitize_name(name: &str) -> Result<String, EntityError> {
             .to_lowercase(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verification(expires: NaiveDateTime, current_email_id: u64) -> EmailVerificationEntity {
+        EmailVerificationEntity {
+            user_id: 1,
+            email_id: 2,
+            current_email_id,
+            expires,
+        }
+    }
+
+    #[test]
+    fn test_verification_expires() {
+        let created = Utc::now().naive_utc();
+        let expires = verification_expires(created);
+
+        assert_eq!(
+            expires - created,
+            Duration::hours(VERIFICATION_TOKEN_EXPIRY_HOURS)
+        );
+        assert!(check_verification(&verification(expires, 2), created).is_ok());
+        assert!(
+            check_verification(&verification(expires, 2), expires - Duration::seconds(1)).is_ok()
+        );
+        assert!(matches!(
+            check_verification(&verification(expires, 2), expires),
+            Err(EntityError::InvalidInput("token", _))
+        ));
+    }
+
+    #[test]
+    fn test_check_verification_other_address() {
+        let now = Utc::now().naive_utc();
+        let expires = verification_expires(now);
+
+        assert!(matches!(
+            check_verification(&verification(expires, 3), now),
+            Err(EntityError::InvalidInput("token", _))
+        ));
+    }
+}
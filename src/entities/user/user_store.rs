@@ -3,7 +3,7 @@ use secrecy::Secret;
 
 use crate::entities::EntityError;
 
-use super::User;
+use super::{Role, User};
 
 #[async_trait]
 pub trait UserStore: Send + Sync + Clone {
@@ -14,6 +14,10 @@ pub trait UserStore: Send + Sync + Clone {
         password: &Secret<String>,
     ) -> Result<User, EntityError>;
 
+    /// Creates a user who logs in with an external identity provider and has no password. The
+    /// email address must be one the provider verified, the user is created as verified.
+    async fn insert_external(&self, name: &str, email: &str) -> Result<User, EntityError>;
+
     async fn get_by_name_password(
         &self,
         name: &str,
@@ -24,5 +28,46 @@ pub trait UserStore: Send + Sync + Clone {
 
     async fn get_by_id(&self, id: u64) -> Result<User, EntityError>;
 
+    /// Users in no particular order, ids that don't exist are left out.
+    async fn get_by_ids(&self, ids: &[u64]) -> Result<Vec<User>, EntityError>;
+
     async fn get_by_public_id(&self, public_id: &str) -> Result<User, EntityError>;
+
+    /// The number of users who haven't been deleted.
+    async fn get_count(&self) -> Result<i64, EntityError>;
+
+    /// Users who haven't been deleted, oldest first so a page keeps the same users as new ones
+    /// sign up.
+    async fn get_page(&self, page: u64, page_size: u32) -> Result<Vec<User>, EntityError>;
+
+    /// The most recent signups who haven't been deleted, newest first.
+    async fn get_recent(&self, count: u32) -> Result<Vec<User>, EntityError>;
+
+    /// Users whose name contains the query, shortest names first so an exact match comes first.
+    async fn search_by_name(&self, query: &str, count: u32) -> Result<Vec<User>, EntityError>;
+
+    async fn set_role(&self, id: u64, role: Role) -> Result<User, EntityError>;
+
+    /// Banned users can't log in, and any sessions or tokens they already have stop working.
+    async fn set_banned(&self, id: u64, is_banned: bool) -> Result<User, EntityError>;
+
+    /// Shadowbanned users can keep posting, but no one else sees it.
+    async fn set_shadowbanned(&self, id: u64, is_shadowbanned: bool) -> Result<User, EntityError>;
+
+    /// Flagged users can't use the sessions they already have, and have to choose a new password
+    /// the next time they log in.
+    async fn set_must_reset_password(
+        &self,
+        id: u64,
+        must_reset_password: bool,
+    ) -> Result<User, EntityError>;
+
+    /// Replaces the user's password and clears the flag set by `set_must_reset_password`.
+    async fn set_password(&self, id: u64, password: &Secret<String>) -> Result<User, EntityError>;
+
+    /// A token that verifies the user's current email address when passed to `verify_email`.
+    async fn create_email_verification_token(&self, id: u64) -> Result<String, EntityError>;
+
+    /// Marks the token's user as verified, if it hasn't expired and their address hasn't changed.
+    async fn verify_email(&self, token: &str) -> Result<User, EntityError>;
 }
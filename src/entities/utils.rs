@@ -68,3 +68,50 @@ pub fn hash_content(content: &str, min: usize, max: usize) -> Result<Vec<u8>, En
     let hash = hasher.finalize()[..].to_vec();
     Ok(hash)
 }
+
+/// `query!` can't bind a list, so `IN` clauses are built with one placeholder per value.
+pub fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+pub const SECRET_TOKEN_LENGTH: usize = 64;
+
+pub fn generate_secret_token() -> String {
+    let first = Uuid::new_v4().simple().to_string();
+    let second = Uuid::new_v4().simple().to_string();
+
+    first + &second
+}
+
+// This must not be changed without special considerations.
+// It is used to hash secret tokens stored in the DB, and changing it would invalidate all outstanding tokens
+pub fn hash_secret_token(token: &str) -> Result<Vec<u8>, EntityError> {
+    match hash_content(token, SECRET_TOKEN_LENGTH, SECRET_TOKEN_LENGTH) {
+        Ok(hash) => Ok(hash),
+        Err(_) => Err(EntityError::InvalidInput("token", "invalid token")),
+    }
+}
+
+pub const RECOVERY_CODE_LENGTH: usize = 10;
+
+/// Recovery codes are shown to the user as two groups of five characters, e.g. `3f9a1-c04be`.
+pub fn generate_recovery_code() -> String {
+    let code = Uuid::new_v4().simple().to_string();
+
+    format!("{}-{}", &code[..5], &code[5..RECOVERY_CODE_LENGTH])
+}
+
+// This must not be changed without special considerations.
+// It is used to hash recovery codes stored in the DB, and changing it would invalidate all unused codes
+pub fn hash_recovery_code(code: &str) -> Result<Vec<u8>, EntityError> {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+
+    match hash_content(&normalized, RECOVERY_CODE_LENGTH, RECOVERY_CODE_LENGTH) {
+        Ok(hash) => Ok(hash),
+        Err(_) => Err(EntityError::InvalidInput("code", "invalid recovery code")),
+    }
+}
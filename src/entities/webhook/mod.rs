@@ -0,0 +1,7 @@
+mod webhook;
+mod webhook_sql;
+mod webhook_store;
+
+pub use webhook::{Webhook, WebhookDelivery, WebhookEvent};
+pub use webhook_sql::{SqlWebhookStore, MAX_URL_LENGTH};
+pub use webhook_store::WebhookStore;
@@ -0,0 +1,73 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Something that happened which webhooks can subscribe to.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "post.created")]
+    PostCreated,
+    #[serde(rename = "comment.created")]
+    CommentCreated,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 2] = [WebhookEvent::PostCreated, WebhookEvent::CommentCreated];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::PostCreated => "post.created",
+            WebhookEvent::CommentCreated => "comment.created",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<WebhookEvent, Self::Err> {
+        match input {
+            "post.created" => Ok(WebhookEvent::PostCreated),
+            "comment.created" => Ok(WebhookEvent::CommentCreated),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An admin configured url that's sent a signed JSON payload for each event it subscribes to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Webhook {
+    pub id: u64,
+    pub public_id: String,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub is_deleted: bool,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// One event sent to one webhook, along with how its latest attempt went.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WebhookDelivery {
+    pub id: u64,
+    pub public_id: String,
+    pub webhook_id: u64,
+    pub event: WebhookEvent,
+    pub subject_id: u64,
+    pub payload: Option<String>,
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub is_delivered: bool,
+    pub next_attempt: Option<DateTime<Utc>>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
@@ -0,0 +1,442 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use log::warn;
+use sqlx::MySqlPool;
+use url::Url;
+use uuid::Uuid;
+
+use crate::entities::{utils, EntityError};
+
+use super::{Webhook, WebhookDelivery, WebhookEvent, WebhookStore};
+
+pub const MAX_URL_LENGTH: usize = 1024;
+const MAX_ERROR_LENGTH: usize = 1024;
+
+const EVENT_SEPARATOR: &str = ",";
+
+#[derive(Clone)]
+pub struct SqlWebhookStore {
+    pool: MySqlPool,
+}
+
+impl SqlWebhookStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct WebhookEntity {
+    pub id: u64,
+    pub public_id: Vec<u8>,
+    pub url: String,
+    pub secret: String,
+    pub events: String,
+    pub is_deleted: i8,
+    pub created: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+impl From<WebhookEntity> for Webhook {
+    fn from(webhook_entity: WebhookEntity) -> Self {
+        Self {
+            id: webhook_entity.id,
+            public_id: utils::get_readable_public_id(webhook_entity.public_id),
+            url: webhook_entity.url,
+            secret: webhook_entity.secret,
+            events: parse_events(&webhook_entity.events),
+            is_deleted: webhook_entity.is_deleted > 0,
+            created: Utc.from_utc_datetime(&webhook_entity.created),
+            updated: Utc.from_utc_datetime(&webhook_entity.updated),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct WebhookDeliveryEntity {
+    pub id: u64,
+    pub public_id: Vec<u8>,
+    pub webhook_id: u64,
+    pub event: String,
+    pub subject_id: u64,
+    pub payload: Option<String>,
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub is_delivered: i8,
+    pub next_attempt: Option<NaiveDateTime>,
+    pub created: NaiveDateTime,
+    pub updated: NaiveDateTime,
+}
+
+impl TryFrom<WebhookDeliveryEntity> for WebhookDelivery {
+    type Error = EntityError;
+
+    fn try_from(delivery_entity: WebhookDeliveryEntity) -> Result<Self, Self::Error> {
+        let event = match WebhookEvent::from_str(&delivery_entity.event) {
+            Ok(event) => event,
+            Err(_) => {
+                return Err(EntityError::Internal(format!(
+                    "unknown webhook event {}",
+                    delivery_entity.event
+                )))
+            }
+        };
+
+        Ok(Self {
+            id: delivery_entity.id,
+            public_id: utils::get_readable_public_id(delivery_entity.public_id),
+            webhook_id: delivery_entity.webhook_id,
+            event,
+            subject_id: delivery_entity.subject_id,
+            payload: delivery_entity.payload,
+            attempts: delivery_entity.attempts,
+            status_code: delivery_entity.status_code,
+            error: delivery_entity.error,
+            is_delivered: delivery_entity.is_delivered > 0,
+            next_attempt: delivery_entity
+                .next_attempt
+                .map(|next_attempt| Utc.from_utc_datetime(&next_attempt)),
+            created: Utc.from_utc_datetime(&delivery_entity.created),
+            updated: Utc.from_utc_datetime(&delivery_entity.updated),
+        })
+    }
+}
+
+#[async_trait]
+impl WebhookStore for SqlWebhookStore {
+    async fn insert(&self, url: &str, events: &[WebhookEvent]) -> Result<Webhook, EntityError> {
+        let id = insert(&self.pool, url, events).await?;
+
+        self.get_by_id(id).await
+    }
+
+    async fn get_all(&self) -> Result<Vec<Webhook>, EntityError> {
+        Ok(get_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(Webhook::from)
+            .collect())
+    }
+
+    async fn get_by_id(&self, id: u64) -> Result<Webhook, EntityError> {
+        Ok(Webhook::from(get_by_id(&self.pool, id).await?))
+    }
+
+    async fn delete(&self, public_id: &str) -> Result<(), EntityError> {
+        let public_id = utils::parse_public_id(public_id)?;
+
+        delete(&self.pool, public_id).await
+    }
+
+    async fn enqueue(&self, event: WebhookEvent, subject_id: u64) -> Result<(), EntityError> {
+        for webhook in self.get_all().await? {
+            if webhook.events.contains(&event) {
+                insert_delivery(&self.pool, webhook.id, event, subject_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        count: u8,
+    ) -> Result<Vec<WebhookDelivery>, EntityError> {
+        get_due_deliveries(&self.pool, now, count)
+            .await?
+            .into_iter()
+            .map(WebhookDelivery::try_from)
+            .collect()
+    }
+
+    async fn claim_delivery(
+        &self,
+        delivery: &WebhookDelivery,
+        until: DateTime<Utc>,
+    ) -> Result<bool, EntityError> {
+        claim_delivery(&self.pool, delivery, until).await
+    }
+
+    async fn record_attempt(&self, delivery: &WebhookDelivery) -> Result<(), EntityError> {
+        record_attempt(&self.pool, delivery).await
+    }
+
+    async fn get_deliveries_by_webhook_id(
+        &self,
+        webhook_id: u64,
+        count: u8,
+    ) -> Result<Vec<WebhookDelivery>, EntityError> {
+        get_deliveries_by_webhook_id(&self.pool, webhook_id, count)
+            .await?
+            .into_iter()
+            .map(WebhookDelivery::try_from)
+            .collect()
+    }
+}
+
+async fn insert(pool: &MySqlPool, url: &str, events: &[WebhookEvent]) -> Result<u64, EntityError> {
+    let url = verify_url(url)?;
+    if events.is_empty() {
+        return Err(EntityError::InvalidInput("events", "no events selected"));
+    }
+
+    let public_id = Uuid::new_v4().into_bytes();
+    let secret = utils::generate_secret_token();
+    let now = Utc::now().naive_utc();
+
+    let webhook_id = sqlx::query!(
+        r#"
+INSERT INTO webhooks (public_id, url, secret, events, is_deleted, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+        &public_id[..],
+        url,
+        secret,
+        format_events(events),
+        0,
+        now,
+        now
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(webhook_id)
+}
+
+async fn get_all(pool: &MySqlPool) -> Result<Vec<WebhookEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        WebhookEntity,
+        r#"
+SELECT *
+FROM webhooks
+WHERE is_deleted = 0
+ORDER BY id DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn get_by_id(pool: &MySqlPool, id: u64) -> Result<WebhookEntity, EntityError> {
+    Ok(sqlx::query_as!(
+        WebhookEntity,
+        r#"
+SELECT *
+FROM webhooks
+WHERE id = ? AND is_deleted = 0
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+async fn delete(pool: &MySqlPool, public_id: Uuid) -> Result<(), EntityError> {
+    let public_id_bytes = public_id.into_bytes();
+
+    let result = sqlx::query!(
+        r#"
+UPDATE webhooks
+SET is_deleted = ?, updated = ?
+WHERE public_id = ? AND is_deleted = 0
+        "#,
+        1,
+        Utc::now().naive_utc(),
+        &public_id_bytes[..]
+    )
+    .execute(pool)
+    .await?;
+
+    match result.rows_affected() {
+        0 => Err(EntityError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+async fn insert_delivery(
+    pool: &MySqlPool,
+    webhook_id: u64,
+    event: WebhookEvent,
+    subject_id: u64,
+) -> Result<u64, EntityError> {
+    let public_id = Uuid::new_v4().into_bytes();
+    let now = Utc::now().naive_utc();
+
+    let delivery_id = sqlx::query!(
+        r#"
+INSERT INTO webhook_deliveries (public_id, webhook_id, event, subject_id, payload, attempts, status_code, error, is_delivered, next_attempt, created, updated)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        &public_id[..],
+        webhook_id,
+        event.as_str(),
+        subject_id,
+        Option::<String>::None,
+        0,
+        Option::<u16>::None,
+        Option::<String>::None,
+        0,
+        now,
+        now,
+        now
+    )
+    .execute(pool)
+    .await?
+    .last_insert_id();
+
+    Ok(delivery_id)
+}
+
+async fn get_due_deliveries(
+    pool: &MySqlPool,
+    now: DateTime<Utc>,
+    count: u8,
+) -> Result<Vec<WebhookDeliveryEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        WebhookDeliveryEntity,
+        r#"
+SELECT *
+FROM webhook_deliveries
+WHERE next_attempt <= ?
+ORDER BY next_attempt ASC
+LIMIT ?
+        "#,
+        now.naive_utc(),
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn claim_delivery(
+    pool: &MySqlPool,
+    delivery: &WebhookDelivery,
+    until: DateTime<Utc>,
+) -> Result<bool, EntityError> {
+    let result = sqlx::query!(
+        r#"
+UPDATE webhook_deliveries
+SET next_attempt = ?, updated = ?
+WHERE id = ? AND next_attempt = ?
+        "#,
+        until.naive_utc(),
+        Utc::now().naive_utc(),
+        delivery.id,
+        delivery
+            .next_attempt
+            .map(|next_attempt| next_attempt.naive_utc())
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn record_attempt(pool: &MySqlPool, delivery: &WebhookDelivery) -> Result<(), EntityError> {
+    let error = delivery
+        .error
+        .as_ref()
+        .map(|e| e.chars().take(MAX_ERROR_LENGTH).collect::<String>());
+
+    sqlx::query!(
+        r#"
+UPDATE webhook_deliveries
+SET payload = ?, attempts = ?, status_code = ?, error = ?, is_delivered = ?, next_attempt = ?, updated = ?
+WHERE id = ?
+        "#,
+        delivery.payload,
+        delivery.attempts,
+        delivery.status_code,
+        error,
+        delivery.is_delivered,
+        delivery.next_attempt.map(|next_attempt| next_attempt.naive_utc()),
+        Utc::now().naive_utc(),
+        delivery.id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn get_deliveries_by_webhook_id(
+    pool: &MySqlPool,
+    webhook_id: u64,
+    count: u8,
+) -> Result<Vec<WebhookDeliveryEntity>, EntityError> {
+    Ok(sqlx::query_as!(
+        WebhookDeliveryEntity,
+        r#"
+SELECT *
+FROM webhook_deliveries
+WHERE webhook_id = ?
+ORDER BY id DESC
+LIMIT ?
+        "#,
+        webhook_id,
+        count
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+fn verify_url(url: &str) -> Result<String, EntityError> {
+    let url = url.trim();
+    if url.len() > MAX_URL_LENGTH {
+        return Err(EntityError::InvalidInput("url", "too long"));
+    }
+
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url.to_owned()),
+        Ok(_) => Err(EntityError::InvalidInput("url", "must be http or https")),
+        Err(e) => {
+            warn!("Invalid webhook url {}: {}", url, e);
+            Err(EntityError::InvalidInput("url", "invalid url"))
+        }
+    }
+}
+
+fn format_events(events: &[WebhookEvent]) -> String {
+    events
+        .iter()
+        .map(|event| event.as_str())
+        .collect::<Vec<_>>()
+        .join(EVENT_SEPARATOR)
+}
+
+// Unknown events are dropped rather than failing, so an event can be retired without breaking old rows
+fn parse_events(events: &str) -> Vec<WebhookEvent> {
+    events
+        .split(EVENT_SEPARATOR)
+        .filter_map(|event| WebhookEvent::from_str(event.trim()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_round_trip() {
+        let events = [WebhookEvent::PostCreated, WebhookEvent::CommentCreated];
+
+        assert_eq!(format_events(&events), "post.created,comment.created");
+        assert_eq!(parse_events("post.created,comment.created"), events);
+        assert_eq!(
+            parse_events("comment.created,post.deleted"),
+            [WebhookEvent::CommentCreated]
+        );
+    }
+
+    #[test]
+    fn test_verify_url() {
+        assert!(verify_url(" https://ci.example.com/hook ").is_ok());
+        assert!(verify_url("ftp://example.com").is_err());
+        assert!(verify_url("not a url").is_err());
+    }
+}
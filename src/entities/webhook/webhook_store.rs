@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::entities::EntityError;
+
+use super::{Webhook, WebhookDelivery, WebhookEvent};
+
+#[async_trait]
+pub trait WebhookStore: Send + Sync + Clone {
+    /// Creates a webhook with a new random signing secret.
+    async fn insert(&self, url: &str, events: &[WebhookEvent]) -> Result<Webhook, EntityError>;
+
+    /// All webhooks that haven't been deleted, newest first.
+    async fn get_all(&self) -> Result<Vec<Webhook>, EntityError>;
+
+    async fn get_by_id(&self, id: u64) -> Result<Webhook, EntityError>;
+
+    /// Fails with `NotFound` if the webhook doesn't exist or was already deleted.
+    async fn delete(&self, public_id: &str) -> Result<(), EntityError>;
+
+    /// Queues a delivery of the event to every webhook subscribed to it.
+    async fn enqueue(&self, event: WebhookEvent, subject_id: u64) -> Result<(), EntityError>;
+
+    /// Deliveries waiting for an attempt at or before `now`, oldest first.
+    async fn get_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        count: u8,
+    ) -> Result<Vec<WebhookDelivery>, EntityError>;
+
+    /// Pushes a due delivery's next attempt back to `until` while it's being attempted. Returns
+    /// false if another instance got to it first.
+    async fn claim_delivery(
+        &self,
+        delivery: &WebhookDelivery,
+        until: DateTime<Utc>,
+    ) -> Result<bool, EntityError>;
+
+    /// Stores the outcome of an attempt, along with its payload and when to try again, if ever.
+    async fn record_attempt(&self, delivery: &WebhookDelivery) -> Result<(), EntityError>;
+
+    /// A webhook's deliveries, newest first.
+    async fn get_deliveries_by_webhook_id(
+        &self,
+        webhook_id: u64,
+        count: u8,
+    ) -> Result<Vec<WebhookDelivery>, EntityError>;
+}
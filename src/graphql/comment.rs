@@ -0,0 +1,124 @@
+use async_graphql::{Context, Object, Result, ID};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    entities::{
+        comment::{Comment, CommentStore},
+        post::PostStore,
+        EntityStores,
+    },
+    routes::models::Viewer,
+};
+
+use super::{
+    content::ContentObject, error, page_size, post::PostObject, user::UserObject,
+    MAX_CHILD_COMMENTS,
+};
+
+pub struct CommentObject(pub Comment);
+
+/// A page of a post's comments under `parent_id`, or its top level comments if there's no parent.
+/// Shadowbanned authors' comments are left out, along with the replies under them.
+pub async fn load_comments(
+    ctx: &Context<'_>,
+    post_id: u64,
+    parent_id: Option<u64>,
+    after: Option<ID>,
+    count: u8,
+) -> Result<Vec<CommentObject>> {
+    let stores = ctx.data::<EntityStores>()?;
+    let start_index = match after {
+        Some(after) => Some(
+            stores
+                .comment_store
+                .get_by_public_id(&after)
+                .await
+                .map_err(|e| error::entity_error(e, "comment"))?
+                .id,
+        ),
+        None => None,
+    };
+
+    let comments = stores
+        .comment_store
+        .get_by_post_id_parent_id(post_id, parent_id, start_index, count)
+        .await
+        .map_err(|e| error::entity_error(e, "comment"))?;
+
+    Ok(Viewer::anonymous()
+        .visible_comments(stores, comments)
+        .await
+        .map_err(|e| error::entity_error(e, "comment"))?
+        .into_iter()
+        .map(CommentObject)
+        .collect())
+}
+
+#[Object(name = "Comment")]
+impl CommentObject {
+    async fn id(&self) -> ID {
+        ID(self.0.public_id.clone())
+    }
+
+    async fn created(&self) -> DateTime<Utc> {
+        self.0.created
+    }
+
+    async fn author(&self, ctx: &Context<'_>) -> Result<UserObject> {
+        UserObject::load(ctx, self.0.author_id).await
+    }
+
+    /// Null once a moderator has removed the comment.
+    async fn content(&self, ctx: &Context<'_>) -> Result<Option<ContentObject>> {
+        match self.0.is_removed {
+            true => Ok(None),
+            false => Ok(Some(ContentObject::load(ctx, self.0.content_id).await?)),
+        }
+    }
+
+    async fn is_removed(&self) -> bool {
+        self.0.is_removed
+    }
+
+    async fn post(&self, ctx: &Context<'_>) -> Result<PostObject> {
+        let stores = ctx.data::<EntityStores>()?;
+
+        match stores.post_store.get_by_id(self.0.post_id).await {
+            Ok(post) => Ok(PostObject(post)),
+            Err(e) => Err(error::entity_error(e, "post")),
+        }
+    }
+
+    async fn parent(&self, ctx: &Context<'_>) -> Result<Option<CommentObject>> {
+        let parent_id = match self.0.parent_id {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let stores = ctx.data::<EntityStores>()?;
+
+        match stores.comment_store.get_by_id(parent_id).await {
+            Ok(parent) => Ok(Some(CommentObject(parent))),
+            Err(e) => Err(error::entity_error(e, "comment")),
+        }
+    }
+
+    /// Replies to this comment, oldest first.
+    #[graphql(
+        complexity = "page_size(first, MAX_CHILD_COMMENTS, MAX_CHILD_COMMENTS) as usize * child_complexity"
+    )]
+    async fn children(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u8>,
+        after: Option<ID>,
+    ) -> Result<Vec<CommentObject>> {
+        load_comments(
+            ctx,
+            self.0.post_id,
+            Some(self.0.id),
+            after,
+            page_size(first, MAX_CHILD_COMMENTS, MAX_CHILD_COMMENTS),
+        )
+        .await
+    }
+}
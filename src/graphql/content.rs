@@ -0,0 +1,30 @@
+use async_graphql::{dataloader::DataLoader, Context, Object, Result};
+
+use crate::entities::content::Content;
+
+use super::{error, loaders::ContentLoader};
+
+pub struct ContentObject(pub Content);
+
+impl ContentObject {
+    pub async fn load(ctx: &Context<'_>, id: u64) -> Result<Self> {
+        match ctx.data::<DataLoader<ContentLoader>>()?.load_one(id).await {
+            Ok(Some(content)) => Ok(Self(content)),
+            Ok(None) => Err(error::not_found("content")),
+            Err(e) => Err(error::entity_error(e, "content")),
+        }
+    }
+}
+
+#[Object(name = "Content")]
+impl ContentObject {
+    /// The markdown as it was written.
+    async fn body(&self) -> &str {
+        &self.0.body
+    }
+
+    /// The markdown rendered to sanitized html.
+    async fn body_html(&self) -> &str {
+        &self.0.body_html
+    }
+}
@@ -0,0 +1,25 @@
+use async_graphql::{Error, ErrorExtensions};
+use log::error;
+
+use crate::entities::EntityError;
+
+/// Maps entity errors the same way `ApiError::from_entity_error` does for the JSON API, so
+/// internal errors aren't leaked to clients.
+pub fn entity_error(error: EntityError, entity_type: &str) -> Error {
+    match error {
+        EntityError::NotFound | EntityError::InvalidInput("public_id", _) => not_found(entity_type),
+        EntityError::InvalidInput(field, message) => Error::new(message).extend_with(|_, e| {
+            e.set("code", "INVALID_INPUT");
+            e.set("field", field);
+        }),
+        _ => {
+            error!("🔥 Entity Error: {:?}", error);
+            Error::new("something went wrong, please try again")
+                .extend_with(|_, e| e.set("code", "INTERNAL"))
+        }
+    }
+}
+
+pub fn not_found(entity_type: &str) -> Error {
+    Error::new(format!("{} not found", entity_type)).extend_with(|_, e| e.set("code", "NOT_FOUND"))
+}
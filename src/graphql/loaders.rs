@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use async_graphql::dataloader::Loader;
+
+use crate::entities::{
+    content::{Content, ContentStore},
+    user::{User, UserStore},
+    EntityError, EntityStores,
+};
+
+/// Batches the author lookups of every post and comment in a query into one store call.
+pub struct UserLoader {
+    stores: EntityStores,
+}
+
+impl UserLoader {
+    pub fn new(stores: EntityStores) -> Self {
+        Self { stores }
+    }
+}
+
+impl Loader<u64> for UserLoader {
+    type Value = User;
+    type Error = EntityError;
+
+    async fn load(&self, ids: &[u64]) -> Result<HashMap<u64, User>, EntityError> {
+        Ok(self
+            .stores
+            .user_store
+            .get_by_ids(ids)
+            .await?
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect())
+    }
+}
+
+/// Batches the content lookups of every post and comment in a query into one store call.
+pub struct ContentLoader {
+    stores: EntityStores,
+}
+
+impl ContentLoader {
+    pub fn new(stores: EntityStores) -> Self {
+        Self { stores }
+    }
+}
+
+impl Loader<u64> for ContentLoader {
+    type Value = Content;
+    type Error = EntityError;
+
+    async fn load(&self, ids: &[u64]) -> Result<HashMap<u64, Content>, EntityError> {
+        Ok(self
+            .stores
+            .content_store
+            .get_by_ids(ids)
+            .await?
+            .into_iter()
+            .map(|content| (content.id, content))
+            .collect())
+    }
+}
@@ -0,0 +1,98 @@
+mod comment;
+mod content;
+mod error;
+mod loaders;
+mod post;
+mod query;
+mod user;
+
+use async_graphql::{
+    dataloader::DataLoader, EmptyMutation, EmptySubscription, Schema, SchemaBuilder,
+};
+
+use crate::entities::EntityStores;
+
+use loaders::{ContentLoader, UserLoader};
+use query::QueryRoot;
+
+pub type GraphQlSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+const DEFAULT_PAGE_SIZE: u8 = 15;
+const MAX_PAGE_SIZE: u8 = 50;
+const MAX_TOP_LEVEL_COMMENTS: u8 = 50;
+const MAX_CHILD_COMMENTS: u8 = 8;
+
+// Enough for a page of posts, or a post with three levels of comments and their authors and
+// content, but not for a query that fans out across the whole comment tree
+const MAX_DEPTH: usize = 16;
+const MAX_COMPLEXITY: usize = 20_000;
+
+/// Builds the read only schema served at `/graphql`.
+pub fn build_schema(stores: EntityStores) -> GraphQlSchema {
+    schema_builder()
+        .data(DataLoader::new(
+            UserLoader::new(stores.clone()),
+            actix_web::rt::spawn,
+        ))
+        .data(DataLoader::new(
+            ContentLoader::new(stores.clone()),
+            actix_web::rt::spawn,
+        ))
+        .data(stores)
+        .finish()
+}
+
+fn schema_builder() -> SchemaBuilder<QueryRoot, EmptyMutation, EmptySubscription> {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+}
+
+// Also used by the complexity of list fields, so a query is charged for the page it asked for
+fn page_size(first: Option<u8>, default: u8, max: u8) -> u8 {
+    first.unwrap_or(default).clamp(1, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn limit_errors(query: &str) -> Vec<String> {
+        let response = schema_builder().finish().execute(query).await;
+
+        response
+            .errors
+            .into_iter()
+            .map(|e| e.message)
+            .filter(|m| m.contains("too deep") || m.contains("too complex"))
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn test_query_limits() {
+        let post_page = r#"{
+            post(id: "x") {
+                title author { name } content { bodyHtml }
+                comments {
+                    id author { name } content { bodyHtml }
+                    children {
+                        id author { name } content { bodyHtml }
+                        children { id author { name } content { bodyHtml } }
+                    }
+                }
+            }
+        }"#;
+        assert!(limit_errors(post_page).await.is_empty());
+
+        let deep = format!(
+            r#"{{ comment(id: "x") {{ {} id {} }} }}"#,
+            "parent { ".repeat(MAX_DEPTH),
+            "}".repeat(MAX_DEPTH)
+        );
+        assert_eq!(limit_errors(&deep).await, ["Query is nested too deep."]);
+
+        let wide =
+            r#"{ posts(first: 50) { comments(first: 50) { children { children { id } } } } }"#;
+        assert_eq!(limit_errors(wide).await, ["Query is too complex."]);
+    }
+}
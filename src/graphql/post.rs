@@ -0,0 +1,80 @@
+use async_graphql::{Context, Object, Result, ID};
+use chrono::{DateTime, Utc};
+
+use crate::entities::{comment::CommentStore, post::Post, EntityStores};
+
+use super::{
+    comment::{self, CommentObject},
+    content::ContentObject,
+    error, page_size,
+    user::UserObject,
+    MAX_TOP_LEVEL_COMMENTS,
+};
+
+pub struct PostObject(pub Post);
+
+#[Object(name = "Post")]
+impl PostObject {
+    async fn id(&self) -> ID {
+        ID(self.0.public_id.clone())
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn link(&self) -> Option<&str> {
+        self.0.link.as_deref()
+    }
+
+    async fn created(&self) -> DateTime<Utc> {
+        self.0.created
+    }
+
+    /// Locked posts don't accept new comments.
+    async fn is_locked(&self) -> bool {
+        self.0.is_locked
+    }
+
+    async fn author(&self, ctx: &Context<'_>) -> Result<UserObject> {
+        UserObject::load(ctx, self.0.author_id).await
+    }
+
+    /// Link posts may not have any content.
+    async fn content(&self, ctx: &Context<'_>) -> Result<Option<ContentObject>> {
+        match self.0.content_id {
+            Some(id) => Ok(Some(ContentObject::load(ctx, id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn comment_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let stores = ctx.data::<EntityStores>()?;
+
+        stores
+            .comment_store
+            .get_count_by_post_id(&self.0.id)
+            .await
+            .map_err(|e| error::entity_error(e, "comment"))
+    }
+
+    /// Top level comments, oldest first.
+    #[graphql(
+        complexity = "page_size(first, MAX_TOP_LEVEL_COMMENTS, MAX_TOP_LEVEL_COMMENTS) as usize * child_complexity"
+    )]
+    async fn comments(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u8>,
+        after: Option<ID>,
+    ) -> Result<Vec<CommentObject>> {
+        comment::load_comments(
+            ctx,
+            self.0.id,
+            None,
+            after,
+            page_size(first, MAX_TOP_LEVEL_COMMENTS, MAX_TOP_LEVEL_COMMENTS),
+        )
+        .await
+    }
+}
@@ -0,0 +1,117 @@
+use async_graphql::{Context, Object, Result, ID};
+
+use crate::{
+    entities::{
+        comment::CommentStore, post::PostStore, user::UserStore, EntityError, EntityStores,
+    },
+    routes::models::Viewer,
+};
+
+use super::{
+    comment::CommentObject, error, page_size, post::PostObject, user::UserObject,
+    DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+};
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn post(&self, ctx: &Context<'_>, id: ID) -> Result<Option<PostObject>> {
+        let stores = ctx.data::<EntityStores>()?;
+
+        // Removed posts are only shown to moderators, on the site
+        let post = match optional(stores.post_store.get_by_public_id(&id).await, "post")? {
+            Some(p) if !p.is_removed => p,
+            _ => return Ok(None),
+        };
+
+        match is_visible(stores, post.author_id, "post").await? {
+            true => Ok(Some(PostObject(post))),
+            false => Ok(None),
+        }
+    }
+
+    /// Recent posts, newest first.
+    #[graphql(
+        complexity = "page_size(first, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE) as usize * child_complexity"
+    )]
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<u8>,
+        before: Option<ID>,
+    ) -> Result<Vec<PostObject>> {
+        let stores = ctx.data::<EntityStores>()?;
+        let start_index = match before {
+            Some(before) => Some(
+                stores
+                    .post_store
+                    .get_by_public_id(&before)
+                    .await
+                    .map_err(|e| error::entity_error(e, "post"))?
+                    .id,
+            ),
+            None => None,
+        };
+
+        Ok(stores
+            .post_store
+            .get_recent(
+                start_index,
+                page_size(first, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE),
+                None,
+                false,
+            )
+            .await
+            .map_err(|e| error::entity_error(e, "post"))?
+            .into_iter()
+            .map(PostObject)
+            .collect())
+    }
+
+    async fn comment(&self, ctx: &Context<'_>, id: ID) -> Result<Option<CommentObject>> {
+        let stores = ctx.data::<EntityStores>()?;
+
+        let comment = match optional(stores.comment_store.get_by_public_id(&id).await, "comment")? {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        match is_visible(stores, comment.author_id, "comment").await? {
+            true => Ok(Some(CommentObject(comment))),
+            false => Ok(None),
+        }
+    }
+
+    async fn user(&self, ctx: &Context<'_>, id: ID) -> Result<Option<UserObject>> {
+        let stores = ctx.data::<EntityStores>()?;
+
+        optional(stores.user_store.get_by_public_id(&id).await, "user").map(|u| u.map(UserObject))
+    }
+
+    async fn user_by_name(&self, ctx: &Context<'_>, name: String) -> Result<Option<UserObject>> {
+        let stores = ctx.data::<EntityStores>()?;
+
+        optional(stores.user_store.get_by_name(&name).await, "user").map(|u| u.map(UserObject))
+    }
+}
+
+// Queries aren't made as anyone, so shadowbanned authors' posts and comments are hidden
+async fn is_visible(stores: &EntityStores, author_id: u64, entity_type: &str) -> Result<bool> {
+    let author = stores
+        .user_store
+        .get_by_id(author_id)
+        .await
+        .map_err(|e| error::entity_error(e, entity_type))?;
+
+    Ok(Viewer::anonymous().can_see(&author))
+}
+
+// Looking up something that doesn't exist isn't an error, the field is just null
+fn optional<T>(result: Result<T, EntityError>, entity_type: &str) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput("public_id", _)) => Ok(None),
+        Err(e) => Err(error::entity_error(e, entity_type)),
+    }
+}
@@ -0,0 +1,33 @@
+use async_graphql::{dataloader::DataLoader, Context, Object, Result, ID};
+use chrono::{DateTime, Utc};
+
+use crate::entities::user::User;
+
+use super::{error, loaders::UserLoader};
+
+pub struct UserObject(pub User);
+
+impl UserObject {
+    pub async fn load(ctx: &Context<'_>, id: u64) -> Result<Self> {
+        match ctx.data::<DataLoader<UserLoader>>()?.load_one(id).await {
+            Ok(Some(user)) => Ok(Self(user)),
+            Ok(None) => Err(error::not_found("user")),
+            Err(e) => Err(error::entity_error(e, "user")),
+        }
+    }
+}
+
+#[Object(name = "User")]
+impl UserObject {
+    async fn id(&self) -> ID {
+        ID(self.0.public_id.clone())
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn created(&self) -> DateTime<Utc> {
+        self.0.created
+    }
+}
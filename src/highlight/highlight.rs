@@ -0,0 +1,100 @@
+use std::sync::OnceLock;
+
+use syntect::{
+    html::{ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// Every class highlight.css styles. Highlighted spans get a class for each part of their
+/// scope, like `hl-keyword hl-control hl-rust`, and the rest are dropped when the HTML is
+/// cleaned.
+pub const TOKEN_CLASSES: [&str; 13] = [
+    "hl-comment",
+    "hl-string",
+    "hl-constant",
+    "hl-keyword",
+    "hl-operator",
+    "hl-storage",
+    "hl-entity",
+    "hl-name",
+    "hl-function",
+    "hl-variable",
+    "hl-support",
+    "hl-macro",
+    "hl-tag",
+];
+
+// Loading the syntaxes takes a while, so it's only done once
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// A fenced code block as HTML, with its code highlighted when the language is one we know and
+/// escaped as is when it isn't.
+pub fn code_block(info: &str, code: &str) -> String {
+    let name = info.split_whitespace().next().unwrap_or_default();
+    let body = highlight(name, code).unwrap_or_else(|| escape(code));
+
+    match name.is_empty() {
+        true => format!("<pre><code>{}</code></pre>\n", body),
+        false => format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>\n",
+            escape(name),
+            body
+        ),
+    }
+}
+
+fn highlight(name: &str, code: &str) -> Option<String> {
+    if name.is_empty() {
+        return None;
+    }
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(name)?;
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+
+    Some(generator.finalize())
+}
+
+fn escape(text: &str) -> String {
+    html_escape::encode_double_quoted_attribute(text).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_block() {
+        let html = code_block("rust", "let s = \"<b>\"; // hi\n");
+
+        assert!(html
+            .starts_with("<pre><code class=\"language-rust\"><span class=\"hl-source hl-rust\">"));
+        assert!(html.contains("<span class=\"hl-storage hl-type hl-rust\">let</span>"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(html.contains("<span class=\"hl-comment hl-line hl-double-slash hl-rust\">"));
+        assert!(html.ends_with("</code></pre>\n"));
+    }
+
+    #[test]
+    fn test_code_block_unknown_language() {
+        assert_eq!(
+            code_block("brainfuck extra", "<+>"),
+            "<pre><code class=\"language-brainfuck\">&lt;+&gt;</code></pre>\n"
+        );
+        assert_eq!(
+            code_block("", "a & b"),
+            "<pre><code>a &amp; b</code></pre>\n"
+        );
+    }
+}
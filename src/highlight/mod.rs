@@ -0,0 +1,3 @@
+mod highlight;
+
+pub use highlight::{code_block, TOKEN_CLASSES};
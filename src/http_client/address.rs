@@ -0,0 +1,127 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use tokio::net::lookup_host;
+use url::{Host, Url};
+
+use super::HttpError;
+
+/// The addresses to connect to for `url`, refusing anything on a private network unless
+/// `allow_private` is set.
+pub async fn resolve(url: &Url, allow_private: bool) -> Result<Vec<SocketAddr>, HttpError> {
+    match url.scheme() {
+        "http" | "https" => (),
+        scheme => {
+            return Err(HttpError::InvalidUrl(format!(
+                "unsupported url scheme {}",
+                scheme
+            )))
+        }
+    }
+    let port = url
+        .port_or_known_default()
+        .ok_or(HttpError::InvalidUrl(format!("no port for {}", url)))?;
+
+    let addrs: Vec<SocketAddr> = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![SocketAddr::new(IpAddr::V4(ip), port)],
+        Some(Host::Ipv6(ip)) => vec![SocketAddr::new(IpAddr::V6(ip), port)],
+        Some(Host::Domain(domain)) => lookup_host((domain, port)).await?.collect(),
+        None => return Err(HttpError::InvalidUrl(format!("no host in {}", url))),
+    };
+
+    // Every address is checked, not just the one connected to, so a host can't slip a private
+    // address in behind a public one
+    if !allow_private {
+        if let Some(addr) = addrs.iter().find(|a| !is_public(a.ip())) {
+            return Err(HttpError::PrivateAddress(format!(
+                "{} resolves to {}",
+                url,
+                addr.ip()
+            )));
+        }
+    }
+
+    match addrs.is_empty() {
+        true => Err(HttpError::Connection(format!("no addresses for {}", url))),
+        false => Ok(addrs),
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // "this network"
+        || a == 0
+        // shared address space for carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // benchmarking
+        || (a == 198 && (18..20).contains(&b))
+        // reserved
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // unique local
+        || (segments[0] & 0xfe00) == 0xfc00
+        // link local
+        || (segments[0] & 0xffc0) == 0xfe80
+        // documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+        // NAT64 and the deprecated IPv4-compatible addresses, either can reach private IPv4
+        || (segments[0] == 0x64 && segments[1] == 0xff9b)
+        || segments[..6] == [0; 6])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public() {
+        for ip in ["93.184.216.34", "2606:2800:220:1::1"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+            "::127.0.0.1",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use reqwest::{header::HeaderMap, redirect, Client, Method};
+use tokio::time::timeout;
+use url::{Host, Url};
+
+use super::{address, HttpError};
+
+/// Limits on a request to someone else's server, which can answer with anything.
+#[derive(Clone, Debug)]
+pub struct HttpPolicy {
+    pub timeout: Duration,
+    /// Anything after is dropped.
+    pub max_bytes: u64,
+    /// Whether the server may be on a private network, like the server's own.
+    pub allow_private: bool,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_bytes: 512 * 1024,
+            allow_private: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+
+/// Sends one request, redirects are left to the caller so each hop gets checked.
+pub async fn send(
+    method: Method,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    policy: &HttpPolicy,
+) -> Result<Response, HttpError> {
+    // Covers looking up the host and reading the body, not just the request
+    match timeout(
+        policy.timeout,
+        send_request(method, url, headers, body, policy),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => Err(HttpError::Timeout),
+    }
+}
+
+async fn send_request(
+    method: Method,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    policy: &HttpPolicy,
+) -> Result<Response, HttpError> {
+    let addrs = address::resolve(url, policy.allow_private).await?;
+
+    // Connects to the addresses that were checked instead of resolving the host again, so the
+    // host's DNS can't answer with a private address the second time. A proxy would resolve it
+    // on its own.
+    let mut client = Client::builder()
+        .redirect(redirect::Policy::none())
+        .no_proxy();
+    if let Some(Host::Domain(domain)) = url.host() {
+        client = client.resolve_to_addrs(domain, &addrs);
+    }
+    let client = client.build()?;
+
+    let mut request = client.request(method, url.clone());
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    if !body.is_empty() {
+        request = request.body(body.to_vec());
+    }
+    let mut response = request.send().await?;
+
+    let mut body = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 >= policy.max_bytes {
+            body.truncate(policy.max_bytes as usize);
+            break;
+        }
+    }
+
+    Ok(Response {
+        status: response.status().as_u16(),
+        headers: response.headers().clone(),
+        body,
+    })
+}
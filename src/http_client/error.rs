@@ -0,0 +1,40 @@
+use std::error::Error;
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum HttpError {
+    #[error("invalid url")]
+    InvalidUrl(String),
+    #[error("url points to a private address")]
+    PrivateAddress(String),
+    #[error("error sending request")]
+    Connection(String),
+    #[error("invalid response")]
+    InvalidResponse(String),
+    #[error("request timed out")]
+    Timeout,
+}
+
+impl From<std::io::Error> for HttpError {
+    fn from(err: std::io::Error) -> Self {
+        HttpError::Connection(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(err: reqwest::Error) -> Self {
+        // reqwest's own message leaves out why, like the refused connection or bad certificate
+        let mut message = err.to_string();
+        let mut source = err.source();
+        while let Some(s) = source {
+            message.push_str(&format!(": {}", s));
+            source = s.source();
+        }
+
+        match err {
+            e if e.is_timeout() => HttpError::Timeout,
+            e if e.is_builder() => HttpError::InvalidUrl(message),
+            e if e.is_body() || e.is_decode() => HttpError::InvalidResponse(message),
+            _ => HttpError::Connection(message),
+        }
+    }
+}
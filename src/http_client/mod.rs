@@ -0,0 +1,7 @@
+mod address;
+mod client;
+mod error;
+
+pub use client::{send, HttpPolicy, Response};
+pub use error::HttpError;
+pub use reqwest::Method;
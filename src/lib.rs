@@ -1,4 +1,16 @@
+mod digest;
 mod entities;
+mod graphql;
+mod highlight;
+mod http_client;
+mod live;
+mod mailer;
+mod oauth;
+mod rate_limit;
 mod routes;
+mod spam;
+mod totp;
+mod unfurl;
+mod webhooks;
 
 pub mod server;
@@ -0,0 +1,122 @@
+mod redis_relay;
+
+use log::error;
+use redis::{aio::ConnectionManager, AsyncCommands, RedisError};
+use tokio::sync::broadcast;
+
+// Slow subscribers miss events past this many, rather than holding up everyone else
+const CAPACITY: usize = 1024;
+const CHANNEL: &str = "effward-dev:comments";
+
+/// A comment that was just posted, as announced to everyone reading its post.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CommentEvent {
+    pub post_id: u64,
+    pub comment_id: u64,
+}
+
+impl CommentEvent {
+    fn encode(&self) -> String {
+        format!("{}:{}", self.post_id, self.comment_id)
+    }
+
+    fn decode(message: &str) -> Option<Self> {
+        let (post_id, comment_id) = message.split_once(':')?;
+
+        Some(Self {
+            post_id: post_id.parse().ok()?,
+            comment_id: comment_id.parse().ok()?,
+        })
+    }
+}
+
+/// Tells open post pages about new comments. Events are broadcast in process, or through Redis
+/// pub/sub when the site runs on several instances, so readers on every instance see every
+/// comment.
+#[derive(Clone)]
+pub struct CommentBus {
+    sender: broadcast::Sender<CommentEvent>,
+    redis: Option<ConnectionManager>,
+}
+
+impl CommentBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+
+        Self {
+            sender,
+            redis: None,
+        }
+    }
+
+    /// Publishes through Redis and relays everything published there, including by this
+    /// instance, to local subscribers.
+    pub async fn with_redis(client: redis::Client) -> Result<Self, RedisError> {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        let publisher = ConnectionManager::new(client.clone()).await?;
+        redis_relay::spawn(client, sender.clone());
+
+        Ok(Self {
+            sender,
+            redis: Some(publisher),
+        })
+    }
+
+    /// Never fails, readers only miss out on seeing the comment without refreshing.
+    pub async fn publish(&self, event: CommentEvent) {
+        if let Some(redis) = &self.redis {
+            let mut redis = redis.clone();
+            match redis.publish::<_, _, ()>(CHANNEL, event.encode()).await {
+                Ok(()) => return,
+                // Readers on this instance can still see it
+                Err(e) => error!("Error publishing comment {}: {:?}", event.comment_id, e),
+            }
+        }
+
+        // Only fails when nobody is listening
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CommentEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for CommentBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = CommentEvent {
+            post_id: 12,
+            comment_id: 345,
+        };
+
+        assert_eq!(CommentEvent::decode(&event.encode()), Some(event));
+        assert_eq!(CommentEvent::decode("12"), None);
+        assert_eq!(CommentEvent::decode("12:abc"), None);
+    }
+
+    #[actix_web::test]
+    async fn test_publish_reaches_subscribers() {
+        let bus = CommentBus::new();
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let event = CommentEvent {
+            post_id: 1,
+            comment_id: 2,
+        };
+
+        bus.publish(event).await;
+
+        assert_eq!(first.recv().await.unwrap(), event);
+        assert_eq!(second.recv().await.unwrap(), event);
+    }
+}
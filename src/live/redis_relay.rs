@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use log::{error, warn};
+use redis::RedisResult;
+use tokio::sync::broadcast;
+
+use super::{CommentEvent, CHANNEL};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Relays comment events published to Redis by any instance to this instance's subscribers,
+/// reconnecting whenever the subscription drops.
+pub fn spawn(client: redis::Client, sender: broadcast::Sender<CommentEvent>) {
+    actix_web::rt::spawn(async move {
+        loop {
+            match relay(&client, &sender).await {
+                Ok(()) => warn!("💬 Comment subscription ended, resubscribing"),
+                Err(e) => error!("💬 Comment subscription failed, resubscribing: {:?}", e),
+            }
+            actix_web::rt::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+async fn relay(
+    client: &redis::Client,
+    sender: &broadcast::Sender<CommentEvent>,
+) -> RedisResult<()> {
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    pubsub.subscribe(CHANNEL).await?;
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: String = message.get_payload()?;
+        match CommentEvent::decode(&payload) {
+            // Only fails when nobody is listening
+            Some(event) => {
+                let _ = sender.send(event);
+            }
+            None => warn!("💬 Ignoring unknown comment event {}", payload),
+        }
+    }
+
+    Ok(())
+}
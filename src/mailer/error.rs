@@ -0,0 +1,13 @@
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum MailerError {
+    #[error("mailer configuration error")]
+    Configuration(String),
+    #[error("invalid email address")]
+    InvalidAddress(String),
+    #[error("error rendering email template")]
+    Template(String),
+    #[error("error building email message")]
+    Message(String),
+    #[error("error delivering email")]
+    Delivery(String),
+}
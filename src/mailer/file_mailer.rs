@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use log::info;
+use uuid::Uuid;
+
+use super::{EmailMessage, Mailer, MailerError};
+
+/// Writes each outgoing email to its own `.eml` file in a directory, for testing emails locally
+/// without an SMTP server. Most mail clients can open the files.
+#[derive(Clone)]
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: &str) -> Result<Self, MailerError> {
+        let dir = PathBuf::from(dir);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            return Err(MailerError::Configuration(format!(
+                "{}: {}",
+                dir.display(),
+                e
+            )));
+        }
+
+        Ok(Self { dir })
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        // Sorts by when it was sent
+        let name = format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            Uuid::new_v4().simple()
+        );
+        let path = self.dir.join(name);
+
+        match tokio::fs::write(&path, format_message(message)).await {
+            Ok(()) => {
+                info!("📧 Email to {} written to {}", message.to, path.display());
+                Ok(())
+            }
+            Err(e) => Err(MailerError::Delivery(format!("{}: {}", path.display(), e))),
+        }
+    }
+}
+
+fn format_message(message: &EmailMessage) -> String {
+    let mut eml = format!(
+        "To: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n",
+        message.to, message.subject
+    );
+    if let Some(url) = &message.unsubscribe_url {
+        eml.push_str(&format!(
+            "List-Unsubscribe: <{}>\r\nList-Unsubscribe-Post: List-Unsubscribe=One-Click\r\n",
+            url
+        ));
+    }
+    eml.push_str("\r\n");
+    eml.push_str(&message.body);
+
+    eml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_send_writes_file() {
+        let dir = std::env::temp_dir().join(format!("mail-drop-{}", Uuid::new_v4().simple()));
+        let mailer = FileMailer::new(dir.to_str().unwrap()).unwrap();
+        let message = EmailMessage {
+            to: "sasquatch@example.com".to_owned(),
+            subject: "hello".to_owned(),
+            body: "some text".to_owned(),
+            unsubscribe_url: None,
+        }
+        .with_unsubscribe_url("https://effward.dev/unsubscribe/abc");
+
+        mailer.send(&message).await.unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let eml = std::fs::read_to_string(files[0].as_ref().unwrap().path()).unwrap();
+        assert!(eml.starts_with("To: sasquatch@example.com\r\nSubject: hello\r\n"));
+        assert!(eml.contains("List-Unsubscribe: <https://effward.dev/unsubscribe/abc>\r\n"));
+        assert!(eml.ends_with("\r\n\r\nsome text"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use log::warn;
+
+use super::{EmailMessage, Mailer, MailerError};
+
+/// Writes outgoing emails to the log instead of delivering them, for local development.
+#[derive(Clone, Default)]
+pub struct LogMailer;
+
+impl LogMailer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        warn!(
+            "📧 Email to: {}\nsubject: {}\n\n{}",
+            message.to, message.subject, message.body
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use tera::{Context, Tera};
+
+use super::MailerError;
+
+#[derive(Clone, Debug)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    // Sent as `List-Unsubscribe`, so mail clients can offer a one-click unsubscribe button
+    pub unsubscribe_url: Option<String>,
+}
+
+impl EmailMessage {
+    /// Renders the plain text body of the email from a Tera template.
+    pub fn render(
+        tera: &Tera,
+        template: &str,
+        context: &Context,
+        to: &str,
+        subject: &str,
+    ) -> Result<Self, MailerError> {
+        match tera.render(template, context) {
+            Ok(body) => Ok(Self {
+                to: to.to_owned(),
+                subject: subject.to_owned(),
+                body,
+                unsubscribe_url: None,
+            }),
+            Err(e) => Err(MailerError::Template(format!("{}: {:?}", template, e))),
+        }
+    }
+
+    pub fn with_unsubscribe_url(mut self, url: &str) -> Self {
+        self.unsubscribe_url = Some(url.to_owned());
+        self
+    }
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError>;
+}
@@ -0,0 +1,11 @@
+mod error;
+mod file_mailer;
+mod log_mailer;
+mod mailer;
+mod smtp_mailer;
+
+pub use error::MailerError;
+pub use file_mailer::FileMailer;
+pub use log_mailer::LogMailer;
+pub use mailer::{EmailMessage, Mailer};
+pub use smtp_mailer::SmtpMailer;
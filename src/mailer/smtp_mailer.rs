@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{
+        header::{ContentType, HeaderName, HeaderValue},
+        Mailbox,
+    },
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use super::{EmailMessage, Mailer, MailerError};
+
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(smtp_url: &str, from: &str) -> Result<Self, MailerError> {
+        let transport = match AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url) {
+            Ok(builder) => builder.build(),
+            Err(e) => return Err(MailerError::Configuration(e.to_string())),
+        };
+
+        let from = parse_mailbox(from)?;
+
+        Ok(Self { transport, from })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .to(parse_mailbox(&message.to)?)
+            .subject(&message.subject)
+            .header(ContentType::TEXT_PLAIN);
+        if let Some(url) = &message.unsubscribe_url {
+            // RFC 8058, the mail client POSTs to the url instead of opening it in a browser
+            builder = builder
+                .raw_header(HeaderValue::new(
+                    HeaderName::new_from_ascii_str("List-Unsubscribe"),
+                    format!("<{}>", url),
+                ))
+                .raw_header(HeaderValue::new(
+                    HeaderName::new_from_ascii_str("List-Unsubscribe-Post"),
+                    "List-Unsubscribe=One-Click".to_owned(),
+                ));
+        }
+
+        let email = match builder.body(message.body.clone()) {
+            Ok(email) => email,
+            Err(e) => return Err(MailerError::Message(e.to_string())),
+        };
+
+        match self.transport.send(email).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(MailerError::Delivery(e.to_string())),
+        }
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, MailerError> {
+    match address.parse::<Mailbox>() {
+        Ok(mailbox) => Ok(mailbox),
+        Err(e) => Err(MailerError::InvalidAddress(format!("{}: {}", address, e))),
+    }
+}
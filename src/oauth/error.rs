@@ -0,0 +1,39 @@
+use crate::http_client::HttpError;
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum OAuthError {
+    #[error("oauth configuration error")]
+    Configuration(String),
+    #[error("unknown oauth provider")]
+    UnknownProvider(String),
+    #[error("error communicating with identity provider")]
+    Http(String),
+    #[error("identity provider returned an error")]
+    Provider(String),
+    #[error("invalid response from identity provider")]
+    InvalidResponse(String),
+    #[error("invalid id token")]
+    InvalidIdToken(&'static str),
+}
+
+impl From<HttpError> for OAuthError {
+    fn from(err: HttpError) -> Self {
+        match err {
+            HttpError::InvalidUrl(e) | HttpError::PrivateAddress(e) => OAuthError::Configuration(e),
+            HttpError::Connection(e) | HttpError::InvalidResponse(e) => OAuthError::Http(e),
+            HttpError::Timeout => OAuthError::Http("timed out".to_owned()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for OAuthError {
+    fn from(err: serde_json::Error) -> Self {
+        OAuthError::InvalidResponse(err.to_string())
+    }
+}
+
+impl From<url::ParseError> for OAuthError {
+    fn from(err: url::ParseError) -> Self {
+        OAuthError::Configuration(err.to_string())
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use url::{form_urlencoded, Url};
+
+use super::OAuthError;
+use crate::http_client::{self, HttpPolicy, Method, Response};
+
+const USER_AGENT: &str = "effward.dev";
+// Providers are configured by the admin, so unlike links posted by users they may be on the
+// server's own network
+const POLICY: HttpPolicy = HttpPolicy {
+    timeout: Duration::from_secs(10),
+    max_bytes: 1024 * 1024,
+    allow_private: true,
+};
+
+/// Response from one of the provider's endpoints.
+#[derive(Debug)]
+pub struct HttpResponse(Response);
+
+impl HttpResponse {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, OAuthError> {
+        if !self.0.is_success() {
+            return Err(OAuthError::Provider(format!(
+                "status {}: {}",
+                self.0.status,
+                String::from_utf8_lossy(&self.0.body)
+            )));
+        }
+
+        Ok(serde_json::from_slice(&self.0.body)?)
+    }
+}
+
+pub async fn get(url: &Url, bearer_token: Option<&str>) -> Result<HttpResponse, OAuthError> {
+    let authorization = bearer_token.map(|token| format!("Bearer {}", token));
+    let mut headers = vec![("User-Agent", USER_AGENT), ("Accept", "application/json")];
+    if let Some(authorization) = &authorization {
+        headers.push(("Authorization", authorization));
+    }
+
+    send(Method::GET, url, &headers, &[]).await
+}
+
+pub async fn post_form(url: &Url, params: &[(&str, &str)]) -> Result<HttpResponse, OAuthError> {
+    let body = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+    let headers = [
+        ("User-Agent", USER_AGENT),
+        ("Accept", "application/json"),
+        ("Content-Type", "application/x-www-form-urlencoded"),
+    ];
+
+    send(Method::POST, url, &headers, body.as_bytes()).await
+}
+
+async fn send(
+    method: Method,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> Result<HttpResponse, OAuthError> {
+    let response = http_client::send(method, url, headers, body, &POLICY).await?;
+
+    Ok(HttpResponse(response))
+}
@@ -0,0 +1,12 @@
+mod error;
+mod http;
+mod id_token;
+mod pkce;
+mod provider;
+mod providers;
+
+pub use error::OAuthError;
+pub use id_token::IdTokenClaims;
+pub use pkce::{code_challenge, random_token};
+pub use provider::{ExternalIdentity, OAuthProvider, ProviderKind, ProviderSummary};
+pub use providers::OAuthProviders;
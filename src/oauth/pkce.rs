@@ -0,0 +1,43 @@
+use base64::{engine::general_purpose, Engine};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+const RANDOM_TOKEN_LENGTH: usize = 64;
+
+/// Random URL-safe string, used for the `state`, `nonce` and PKCE code verifier.
+pub fn random_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(RANDOM_TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// RFC 7636 `S256` code challenge for a PKCE code verifier.
+pub fn code_challenge(code_verifier: &str) -> String {
+    let hash = Sha256::digest(code_verifier.as_bytes());
+
+    general_purpose::URL_SAFE_NO_PAD.encode(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc7636_code_challenge() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_random_token() {
+        let token = random_token();
+
+        assert_eq!(token.len(), RANDOM_TOKEN_LENGTH);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(token, random_token());
+    }
+}
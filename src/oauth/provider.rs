@@ -0,0 +1,223 @@
+use chrono::Utc;
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{http, IdTokenClaims, OAuthError};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProviderKind {
+    /// Any OpenID Connect provider, the user's identity comes from the ID token.
+    Oidc { issuer: String },
+    /// GitHub only supports plain OAuth2, so the user's identity comes from its REST API.
+    GitHub,
+}
+
+/// An identity as reported by an external provider.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub id_token: Option<String>,
+}
+
+/// Shown on the login page.
+#[derive(Clone, Debug, Serialize)]
+pub struct ProviderSummary {
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct OAuthProvider {
+    pub name: String,
+    pub display_name: String,
+    pub kind: ProviderKind,
+    pub client_id: String,
+    pub client_secret: Secret<String>,
+    pub auth_url: Url,
+    pub token_url: Url,
+    pub userinfo_url: Option<Url>,
+    pub scopes: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: u64,
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    sub: String,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    preferred_username: Option<String>,
+    nickname: Option<String>,
+}
+
+const GITHUB_EMAILS_URL: &str = "https://api.github.com/user/emails";
+
+impl OAuthProvider {
+    pub fn summary(&self) -> ProviderSummary {
+        ProviderSummary {
+            name: self.name.clone(),
+            display_name: self.display_name.clone(),
+        }
+    }
+
+    /// Where to send the user to sign in, using the authorization code flow with PKCE.
+    pub fn authorization_url(
+        &self,
+        redirect_uri: &str,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Url {
+        let mut url = self.auth_url.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("scope", &self.scopes)
+            .append_pair("state", state)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        if let ProviderKind::Oidc { .. } = self.kind {
+            url.query_pairs_mut().append_pair("nonce", nonce);
+        }
+
+        url
+    }
+
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+    ) -> Result<TokenResponse, OAuthError> {
+        http::post_form(
+            &self.token_url,
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("client_id", &self.client_id),
+                ("client_secret", self.client_secret.expose_secret()),
+                ("code_verifier", code_verifier),
+            ],
+        )
+        .await?
+        .json()
+    }
+
+    pub async fn get_identity(
+        &self,
+        tokens: &TokenResponse,
+        nonce: &str,
+    ) -> Result<ExternalIdentity, OAuthError> {
+        match &self.kind {
+            ProviderKind::Oidc { issuer } => self.get_oidc_identity(issuer, tokens, nonce).await,
+            ProviderKind::GitHub => self.get_github_identity(tokens).await,
+        }
+    }
+
+    async fn get_oidc_identity(
+        &self,
+        issuer: &str,
+        tokens: &TokenResponse,
+        nonce: &str,
+    ) -> Result<ExternalIdentity, OAuthError> {
+        let id_token = tokens
+            .id_token
+            .as_ref()
+            .ok_or(OAuthError::InvalidIdToken("no id token in token response"))?;
+        let claims = IdTokenClaims::decode(id_token)?;
+        claims.validate(issuer, &self.client_id, nonce, Utc::now().timestamp())?;
+
+        let mut identity = ExternalIdentity {
+            provider: self.name.clone(),
+            subject: claims.sub,
+            username: claims.preferred_username.or(claims.nickname),
+            email: verified_email(claims.email, claims.email_verified),
+        };
+
+        // Some providers only include profile claims in the userinfo response
+        if identity.email.is_none() || identity.username.is_none() {
+            if let Some(userinfo_url) = &self.userinfo_url {
+                let userinfo: UserInfo = http::get(userinfo_url, Some(&tokens.access_token))
+                    .await?
+                    .json()?;
+                if userinfo.sub != identity.subject {
+                    return Err(OAuthError::InvalidResponse(
+                        "userinfo subject doesn't match id token".to_owned(),
+                    ));
+                }
+
+                identity.username = identity
+                    .username
+                    .or(userinfo.preferred_username)
+                    .or(userinfo.nickname);
+                identity.email = identity
+                    .email
+                    .or(verified_email(userinfo.email, userinfo.email_verified));
+            }
+        }
+
+        Ok(identity)
+    }
+
+    async fn get_github_identity(
+        &self,
+        tokens: &TokenResponse,
+    ) -> Result<ExternalIdentity, OAuthError> {
+        let userinfo_url = self
+            .userinfo_url
+            .as_ref()
+            .ok_or(OAuthError::Configuration("no github user url".to_owned()))?;
+        let user: GitHubUser = http::get(userinfo_url, Some(&tokens.access_token))
+            .await?
+            .json()?;
+
+        // The profile email is optional, so use the primary address if it's been verified
+        let emails: Vec<GitHubEmail> =
+            http::get(&Url::parse(GITHUB_EMAILS_URL)?, Some(&tokens.access_token))
+                .await?
+                .json()
+                .unwrap_or_default();
+        let email = emails
+            .into_iter()
+            .find(|e| e.primary && e.verified)
+            .map(|e| e.email);
+
+        Ok(ExternalIdentity {
+            provider: self.name.clone(),
+            subject: user.id.to_string(),
+            username: Some(user.login),
+            email,
+        })
+    }
+}
+
+// Unverified addresses can't be trusted, anyone could claim someone else's email
+fn verified_email(email: Option<String>, email_verified: Option<bool>) -> Option<String> {
+    match email_verified {
+        Some(true) => email,
+        _ => None,
+    }
+}
@@ -0,0 +1,170 @@
+use std::env;
+
+use secrecy::Secret;
+use serde::Deserialize;
+use url::Url;
+
+use super::{http, OAuthError, OAuthProvider, ProviderKind, ProviderSummary};
+
+const GITHUB_AUTH_URL: &str = "https://github.com/login/oauth/authorize";
+const GITHUB_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const GITHUB_USER_URL: &str = "https://api.github.com/user";
+const GITHUB_SCOPES: &str = "read:user user:email";
+const GITLAB_ISSUER: &str = "https://gitlab.com";
+const OIDC_SCOPES: &str = "openid profile email";
+
+#[derive(Debug, Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+}
+
+/// The external identity providers users can log in with.
+#[derive(Clone, Debug, Default)]
+pub struct OAuthProviders {
+    providers: Vec<OAuthProvider>,
+}
+
+impl OAuthProviders {
+    pub fn new(providers: Vec<OAuthProvider>) -> Self {
+        Self { providers }
+    }
+
+    /// Reads the comma separated provider names in `OAUTH_PROVIDERS`, each configured by
+    /// `OAUTH_<NAME>_CLIENT_ID` and `OAUTH_<NAME>_CLIENT_SECRET`. `github` and `gitlab` work out of
+    /// the box, any other name must also set `OAUTH_<NAME>_ISSUER` to an OpenID Connect issuer.
+    /// `OAUTH_<NAME>_DISPLAY_NAME` optionally sets the name shown on the login page.
+    /// Every endpoint has to use https unless `allow_http` is set, for a provider running locally.
+    pub async fn from_env(allow_http: bool) -> Result<Self, OAuthError> {
+        let names = match env::var("OAUTH_PROVIDERS") {
+            Ok(n) => n,
+            Err(_) => return Ok(Self::default()),
+        };
+
+        let mut providers = vec![];
+        for name in names.split(',').map(|n| n.trim().to_lowercase()) {
+            if name.is_empty() {
+                continue;
+            }
+            providers.push(provider_from_env(&name, allow_http).await?);
+        }
+
+        Ok(Self::new(providers))
+    }
+
+    pub fn get(&self, name: &str) -> Result<&OAuthProvider, OAuthError> {
+        self.providers
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or(OAuthError::UnknownProvider(name.to_owned()))
+    }
+
+    pub fn summaries(&self) -> Vec<ProviderSummary> {
+        self.providers.iter().map(|p| p.summary()).collect()
+    }
+}
+
+async fn provider_from_env(name: &str, allow_http: bool) -> Result<OAuthProvider, OAuthError> {
+    let client_id = required_var(name, "CLIENT_ID")?;
+    let client_secret = Secret::new(required_var(name, "CLIENT_SECRET")?);
+    let display_name = optional_var(name, "DISPLAY_NAME").unwrap_or(name.to_owned());
+
+    if name == "github" {
+        return Ok(OAuthProvider {
+            name: name.to_owned(),
+            display_name,
+            kind: ProviderKind::GitHub,
+            client_id,
+            client_secret,
+            auth_url: Url::parse(GITHUB_AUTH_URL)?,
+            token_url: Url::parse(GITHUB_TOKEN_URL)?,
+            userinfo_url: Some(Url::parse(GITHUB_USER_URL)?),
+            scopes: GITHUB_SCOPES.to_owned(),
+        });
+    }
+
+    let issuer = match optional_var(name, "ISSUER") {
+        Some(i) => i,
+        None if name == "gitlab" => GITLAB_ISSUER.to_owned(),
+        None => {
+            return Err(OAuthError::Configuration(format!(
+                "set {} to the provider's OpenID Connect issuer",
+                var_name(name, "ISSUER")
+            )))
+        }
+    };
+    let discovery = discover(&issuer, allow_http).await?;
+
+    Ok(OAuthProvider {
+        name: name.to_owned(),
+        display_name,
+        kind: ProviderKind::Oidc {
+            issuer: discovery.issuer,
+        },
+        client_id,
+        client_secret,
+        auth_url: endpoint(&discovery.authorization_endpoint, allow_http)?,
+        token_url: endpoint(&discovery.token_endpoint, allow_http)?,
+        userinfo_url: match discovery.userinfo_endpoint {
+            Some(u) => Some(endpoint(&u, allow_http)?),
+            None => None,
+        },
+        scopes: OIDC_SCOPES.to_owned(),
+    })
+}
+
+async fn discover(issuer: &str, allow_http: bool) -> Result<DiscoveryDocument, OAuthError> {
+    let url = endpoint(
+        &format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        ),
+        allow_http,
+    )?;
+
+    http::get(&url, None).await?.json()
+}
+
+// ID tokens are trusted because they come straight from the provider over TLS, so plain http
+// would let anyone on the way log in as anyone
+fn endpoint(url: &str, allow_http: bool) -> Result<Url, OAuthError> {
+    let url = Url::parse(url)?;
+    match url.scheme() {
+        "https" => Ok(url),
+        "http" if allow_http => Ok(url),
+        _ => Err(OAuthError::Configuration(format!("{} must use https", url))),
+    }
+}
+
+fn var_name(name: &str, suffix: &str) -> String {
+    format!("OAUTH_{}_{}", name.to_uppercase(), suffix)
+}
+
+fn optional_var(name: &str, suffix: &str) -> Option<String> {
+    env::var(var_name(name, suffix)).ok()
+}
+
+fn required_var(name: &str, suffix: &str) -> Result<String, OAuthError> {
+    optional_var(name, suffix).ok_or(OAuthError::Configuration(format!(
+        "{} is not set",
+        var_name(name, suffix)
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        assert!(endpoint("https://id.example.com/token", false).is_ok());
+        assert!(endpoint("http://localhost:8080/token", true).is_ok());
+        assert!(matches!(
+            endpoint("http://id.example.com/token", false),
+            Err(OAuthError::Configuration(_))
+        ));
+        assert!(endpoint("ftp://id.example.com/token", true).is_err());
+    }
+}
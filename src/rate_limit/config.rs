@@ -0,0 +1,92 @@
+use std::{collections::HashMap, env, str::FromStr};
+
+use super::{RateLimitError, RateLimitRule};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RouteLimits {
+    pub per_ip: Option<RateLimitRule>,
+    pub per_user: Option<RateLimitRule>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitConfig {
+    routes: HashMap<&'static str, RouteLimits>,
+}
+
+impl RateLimitConfig {
+    pub const LOGIN: &'static str = "login";
+    pub const SIGNUP: &'static str = "signup";
+    pub const SUBMIT: &'static str = "submit";
+    pub const COMMENT: &'static str = "comment";
+
+    pub fn new() -> Self {
+        Self {
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Builds the default limits for each route, overridden by `RATE_LIMIT_<ROUTE>_<IP|USER>`
+    /// environment variables, e.g. `RATE_LIMIT_LOGIN_IP=10/60`, or `off` to disable a limit.
+    pub fn from_env() -> Result<Self, RateLimitError> {
+        let defaults = [
+            (
+                Self::LOGIN,
+                RouteLimits {
+                    per_ip: Some(RateLimitRule::new(10, 60)),
+                    per_user: None,
+                },
+            ),
+            (
+                Self::SIGNUP,
+                RouteLimits {
+                    per_ip: Some(RateLimitRule::new(5, 60 * 60)),
+                    per_user: None,
+                },
+            ),
+            (
+                Self::SUBMIT,
+                RouteLimits {
+                    per_ip: Some(RateLimitRule::new(30, 60 * 60)),
+                    per_user: Some(RateLimitRule::new(10, 60 * 60)),
+                },
+            ),
+            (
+                Self::COMMENT,
+                RouteLimits {
+                    per_ip: Some(RateLimitRule::new(120, 60 * 60)),
+                    per_user: Some(RateLimitRule::new(30, 10 * 60)),
+                },
+            ),
+        ];
+
+        let mut config = Self::new();
+        for (route, limits) in defaults {
+            let per_ip = rule_from_env(route, "IP", limits.per_ip)?;
+            let per_user = rule_from_env(route, "USER", limits.per_user)?;
+            config.set(route, RouteLimits { per_ip, per_user });
+        }
+
+        Ok(config)
+    }
+
+    pub fn set(&mut self, route: &'static str, limits: RouteLimits) {
+        self.routes.insert(route, limits);
+    }
+
+    pub fn get(&self, route: &str) -> RouteLimits {
+        self.routes.get(route).copied().unwrap_or_default()
+    }
+}
+
+fn rule_from_env(
+    route: &str,
+    kind: &str,
+    default: Option<RateLimitRule>,
+) -> Result<Option<RateLimitRule>, RateLimitError> {
+    let var = format!("RATE_LIMIT_{}_{}", route.to_uppercase(), kind);
+    match env::var(var) {
+        Ok(value) if value.eq_ignore_ascii_case("off") => Ok(None),
+        Ok(value) => Ok(Some(RateLimitRule::from_str(&value)?)),
+        Err(_) => Ok(default),
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum RateLimitError {
+    #[error("invalid rate limit configuration")]
+    Configuration(String),
+    #[error("rate limit store error")]
+    Store(String),
+}
+
+impl From<redis::RedisError> for RateLimitError {
+    fn from(err: redis::RedisError) -> Self {
+        RateLimitError::Store(err.to_string())
+    }
+}
@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+use super::{RateLimitDecision, RateLimitError, RateLimitRule, RateLimitStore};
+
+const MAX_BUCKETS: usize = 100_000;
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryRateLimitStore {
+    buckets: DashMap<String, Bucket>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self {
+            buckets: DashMap::new(),
+        }
+    }
+
+    fn take_at(&self, key: &str, rule: &RateLimitRule, now: Instant) -> RateLimitDecision {
+        if self.buckets.len() >= MAX_BUCKETS {
+            self.prune(now);
+        }
+
+        let capacity = rule.capacity as f64;
+        let rate = rule.tokens_per_second();
+
+        let mut bucket = self.buckets.entry(key.to_owned()).or_insert(Bucket {
+            tokens: capacity,
+            refilled: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.refilled = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            RateLimitDecision::Allowed
+        } else {
+            let retry_after = Duration::from_secs_f64((1.0 - bucket.tokens) / rate);
+            RateLimitDecision::Limited { retry_after }
+        }
+    }
+
+    // Buckets untouched for an hour are almost certainly full again, so they can be recreated on demand
+    fn prune(&self, now: Instant) {
+        let max_idle = Duration::from_secs(60 * 60);
+        self.buckets
+            .retain(|_, bucket| now.saturating_duration_since(bucket.refilled) < max_idle);
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        rule: &RateLimitRule,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        Ok(self.take_at(key, rule, Instant::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_until_empty() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(3, 60);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(store.take_at("key", &rule, now), RateLimitDecision::Allowed);
+        }
+
+        match store.take_at("key", &rule, now) {
+            RateLimitDecision::Limited { retry_after } => {
+                assert_eq!(retry_after.as_secs(), 20);
+            }
+            RateLimitDecision::Allowed => panic!("expected bucket to be empty"),
+        }
+    }
+
+    #[test]
+    fn test_refill_over_time() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(2, 10);
+        let now = Instant::now();
+
+        assert_eq!(store.take_at("key", &rule, now), RateLimitDecision::Allowed);
+        assert_eq!(store.take_at("key", &rule, now), RateLimitDecision::Allowed);
+        assert!(matches!(
+            store.take_at("key", &rule, now),
+            RateLimitDecision::Limited { .. }
+        ));
+
+        let later = now + Duration::from_secs(5);
+        assert_eq!(
+            store.take_at("key", &rule, later),
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn test_keys_are_independent() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(1, 60);
+        let now = Instant::now();
+
+        assert_eq!(store.take_at("a", &rule, now), RateLimitDecision::Allowed);
+        assert_eq!(store.take_at("b", &rule, now), RateLimitDecision::Allowed);
+        assert!(matches!(
+            store.take_at("a", &rule, now),
+            RateLimitDecision::Limited { .. }
+        ));
+    }
+}
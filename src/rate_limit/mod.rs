@@ -0,0 +1,15 @@
+mod config;
+mod error;
+mod memory_store;
+mod rate_limit_store;
+mod rate_limiter;
+mod redis_store;
+mod rule;
+
+pub use config::RateLimitConfig;
+pub use error::RateLimitError;
+pub use memory_store::MemoryRateLimitStore;
+pub use rate_limit_store::{RateLimitDecision, RateLimitStore};
+pub use rate_limiter::RateLimiter;
+pub use redis_store::RedisRateLimitStore;
+pub use rule::RateLimitRule;
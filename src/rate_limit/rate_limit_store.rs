@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{RateLimitError, RateLimitRule};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RateLimitDecision {
+    Allowed,
+    Limited { retry_after: Duration },
+}
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a single token from the bucket identified by `key`, creating a full bucket if none exists.
+    async fn take(
+        &self,
+        key: &str,
+        rule: &RateLimitRule,
+    ) -> Result<RateLimitDecision, RateLimitError>;
+}
@@ -0,0 +1,53 @@
+use std::{sync::Arc, time::Duration};
+
+use log::error;
+
+use super::{RateLimitConfig, RateLimitDecision, RateLimitRule, RateLimitStore};
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    config: RateLimitConfig,
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>, config: RateLimitConfig) -> Self {
+        Self { store, config }
+    }
+
+    /// Takes a token from the route's per-IP and per-user buckets, returning how long the caller
+    /// must wait if either is empty. Store failures are logged and the request is allowed.
+    pub async fn check(
+        &self,
+        route: &str,
+        ip: Option<&str>,
+        user_id: Option<&str>,
+    ) -> Option<Duration> {
+        let limits = self.config.get(route);
+
+        let per_ip = match (limits.per_ip, ip) {
+            (Some(rule), Some(ip)) => self.take(&format!("{}:ip:{}", route, ip), &rule).await,
+            _ => None,
+        };
+        let per_user = match (limits.per_user, user_id) {
+            (Some(rule), Some(user_id)) => {
+                self.take(&format!("{}:user:{}", route, user_id), &rule)
+                    .await
+            }
+            _ => None,
+        };
+
+        per_ip.max(per_user)
+    }
+
+    async fn take(&self, key: &str, rule: &RateLimitRule) -> Option<Duration> {
+        match self.store.take(key, rule).await {
+            Ok(RateLimitDecision::Allowed) => None,
+            Ok(RateLimitDecision::Limited { retry_after }) => Some(retry_after),
+            Err(e) => {
+                error!("Error checking rate limit for {}: {:?}", key, e);
+                None
+            }
+        }
+    }
+}
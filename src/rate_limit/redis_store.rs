@@ -0,0 +1,85 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, Script};
+
+use super::{RateLimitDecision, RateLimitError, RateLimitRule, RateLimitStore};
+
+const KEY_PREFIX: &str = "rate_limit:";
+
+// Refills and takes from the bucket atomically, returning the milliseconds until a token is
+// available (0 if one was taken)
+const TAKE_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local ttl = tonumber(ARGV[4])
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'refilled')
+local tokens = tonumber(bucket[1]) or capacity
+local refilled = tonumber(bucket[2]) or now
+
+tokens = math.min(capacity, tokens + math.max(0, now - refilled) * rate)
+
+local wait = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+else
+    wait = math.ceil((1 - tokens) / rate)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'refilled', now)
+redis.call('PEXPIRE', KEYS[1], ttl)
+
+return wait
+"#;
+
+#[derive(Clone)]
+pub struct RedisRateLimitStore {
+    connection: ConnectionManager,
+    script: Script,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self {
+            connection,
+            script: Script::new(TAKE_SCRIPT),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        rule: &RateLimitRule,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        let now_ms = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as u64,
+            Err(e) => return Err(RateLimitError::Store(e.to_string())),
+        };
+        let tokens_per_ms = rule.tokens_per_second() / 1000.0;
+        let ttl_ms = rule.period.as_millis() as u64;
+
+        let mut connection = self.connection.clone();
+        let wait_ms: u64 = self
+            .script
+            .key(format!("{}{}", KEY_PREFIX, key))
+            .arg(rule.capacity)
+            .arg(tokens_per_ms)
+            .arg(now_ms)
+            .arg(ttl_ms)
+            .invoke_async(&mut connection)
+            .await?;
+
+        if wait_ms == 0 {
+            Ok(RateLimitDecision::Allowed)
+        } else {
+            Ok(RateLimitDecision::Limited {
+                retry_after: Duration::from_millis(wait_ms),
+            })
+        }
+    }
+}
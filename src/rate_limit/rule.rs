@@ -0,0 +1,47 @@
+use std::{str::FromStr, time::Duration};
+
+use super::RateLimitError;
+
+/// A token bucket holding up to `capacity` tokens, refilled evenly over `period`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitRule {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl RateLimitRule {
+    pub const fn new(capacity: u32, period_secs: u64) -> Self {
+        Self {
+            capacity,
+            period: Duration::from_secs(period_secs),
+        }
+    }
+
+    pub fn tokens_per_second(&self) -> f64 {
+        self.capacity as f64 / self.period.as_secs_f64()
+    }
+}
+
+/// Parses rules of the form `capacity/seconds`, e.g. `10/60` for 10 requests per minute.
+impl FromStr for RateLimitRule {
+    type Err = RateLimitError;
+
+    fn from_str(input: &str) -> Result<RateLimitRule, Self::Err> {
+        let invalid = || {
+            RateLimitError::Configuration(format!(
+                "'{}' is not a valid rule, expected capacity/seconds",
+                input
+            ))
+        };
+
+        let (capacity, period_secs) = input.trim().split_once('/').ok_or_else(invalid)?;
+        let capacity: u32 = capacity.trim().parse().map_err(|_| invalid())?;
+        let period_secs: u64 = period_secs.trim().parse().map_err(|_| invalid())?;
+
+        if capacity == 0 || period_secs == 0 {
+            return Err(invalid());
+        }
+
+        Ok(RateLimitRule::new(capacity, period_secs))
+    }
+}
@@ -0,0 +1,178 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::MySqlPool;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        cache::CacheStats,
+        stats::StatsStore,
+        user::{Permission, Role, User, UserStore},
+        EntityStores,
+    },
+    routes::{
+        models::UserModel,
+        permissions, two_factor,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::{Admins, MAX_DB_CONNECTIONS},
+};
+
+const RECENT_SIGNUPS: u32 = 10;
+const MAX_SEARCH_RESULTS: u32 = 25;
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    q: Option<String>,
+}
+
+#[derive(Serialize)]
+struct UserRow {
+    user: UserModel,
+    role: Role,
+    is_banned: bool,
+    is_shadowbanned: bool,
+    must_reset_password: bool,
+    is_deleted: bool,
+    has_two_factor: bool,
+    can_ban: bool,
+    // Admins can't change their own role or force their own password reset
+    is_self: bool,
+}
+
+#[derive(Serialize)]
+struct CacheRow {
+    name: &'static str,
+    stats: CacheStats,
+    hit_rate: String,
+}
+
+#[derive(Serialize)]
+struct PoolStatus {
+    size: u32,
+    idle: usize,
+    max_connections: u32,
+    is_closed: bool,
+}
+
+/// Site statistics and user management for admins.
+pub async fn dashboard(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    query: web::Query<DashboardQuery>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    pool: web::Data<MySqlPool>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let admin = match permissions::require_permission(
+        session.clone(),
+        &stores,
+        &admins,
+        Permission::ViewDashboard,
+        "/",
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let stats = match stores.stats_store.get_site_stats().await {
+        Ok(s) => s,
+        Err(e) => return utils::redirect_entity_error(e, "site statistics"),
+    };
+    let recent_signups = match stores.user_store.get_recent(RECENT_SIGNUPS).await {
+        Ok(users) => translate_users(&stores, &admins, &admin, users).await,
+        Err(e) => return utils::redirect_entity_error(e, "users"),
+    };
+
+    let search = query.q.as_deref().map(str::trim).unwrap_or_default();
+    let search_results = match search.is_empty() {
+        true => None,
+        false => match stores
+            .user_store
+            .search_by_name(search, MAX_SEARCH_RESULTS)
+            .await
+        {
+            Ok(users) => Some(translate_users(&stores, &admins, &admin, users).await),
+            Err(e) => return utils::redirect_entity_error(e, "users"),
+        },
+    };
+
+    let caches: Vec<CacheRow> = stores
+        .cache_stats()
+        .into_iter()
+        .map(|(name, stats)| CacheRow {
+            name,
+            stats,
+            hit_rate: match stats.hit_rate() {
+                Some(rate) => format!("{:.1}%", rate),
+                None => "-".to_owned(),
+            },
+        })
+        .collect();
+    let pool_status = PoolStatus {
+        size: pool.size(),
+        idle: pool.num_idle(),
+        max_connections: MAX_DB_CONNECTIONS,
+        is_closed: pool.is_closed(),
+    };
+
+    let mut user_context =
+        user_context::build(session, flash_messages, &stores, "admin", None).await;
+    user_context.context.insert("stats", &stats);
+    user_context
+        .context
+        .insert("recent_signups", &recent_signups);
+    user_context.context.insert("search", search);
+    user_context
+        .context
+        .insert("search_results", &search_results);
+    user_context.context.insert("caches", &caches);
+    user_context.context.insert("pool", &pool_status);
+    user_context.context.insert("roles", &Role::ALL);
+
+    // TODO: handle error
+    let rendered = tera.render("admin.html", &user_context.context).unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
+
+async fn translate_users(
+    stores: &EntityStores,
+    admins: &Admins,
+    admin: &User,
+    users: Vec<User>,
+) -> Vec<UserRow> {
+    let mut rows = vec![];
+    for user in users {
+        // Only hides the reset button, so failing to check is just logged
+        let has_two_factor = match two_factor::is_enabled(stores, user.id).await {
+            Ok(is_enabled) => is_enabled,
+            Err(e) => {
+                error!(
+                    "Error getting user {}'s two-factor authentication: {:?}",
+                    user.id, e
+                );
+                false
+            }
+        };
+        rows.push(UserRow {
+            role: admins.role_of(&user),
+            is_banned: user.is_banned,
+            is_shadowbanned: user.is_shadowbanned,
+            must_reset_password: user.must_reset_password,
+            is_deleted: user.is_deleted,
+            has_two_factor,
+            can_ban: permissions::can_ban(admin, &user, admins),
+            is_self: user.id == admin.id,
+            user: UserModel::from(user),
+        });
+    }
+
+    rows
+}
@@ -0,0 +1,3 @@
+pub mod get;
+
+pub const ADMIN_PATH: &str = "/admin";
@@ -0,0 +1,162 @@
+use std::fmt;
+
+use actix_web::{
+    error::{JsonPayloadError, QueryPayloadError},
+    http::StatusCode,
+    HttpRequest, HttpResponse, ResponseError,
+};
+use log::error;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{entities::EntityError, routes::user_context::UserContextError};
+
+/// Error returned by API endpoints, serialized as `{"error": {"code": ..., "message": ...}}`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    field: Option<&'static str>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorDetail {
+    code: String,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: &str) -> Self {
+        Self {
+            status,
+            code,
+            message: message.to_owned(),
+            field: None,
+        }
+    }
+
+    pub fn bad_request(message: &str) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn not_found(entity_type: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            &format!("{} not found", entity_type),
+        )
+    }
+
+    /// Maps entity errors the same way `utils::redirect_entity_error` does for the HTML routes.
+    pub fn from_entity_error(error: EntityError, entity_type: &str) -> Self {
+        match error {
+            EntityError::NotFound | EntityError::InvalidInput("public_id", _) => {
+                Self::not_found(entity_type)
+            }
+            EntityError::InvalidInput(field, message) => Self {
+                field: Some(field),
+                ..Self::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_input", message)
+            },
+            EntityError::DuplicateKey => Self::new(
+                StatusCode::CONFLICT,
+                "conflict",
+                &format!("{} already exists", entity_type),
+            ),
+            _ => {
+                error!("🔥 Entity Error: {:?}", error);
+                Self::internal()
+            }
+        }
+    }
+
+    pub fn from_user_context_error(error: UserContextError) -> Self {
+        match error {
+            UserContextError::EntityError(e) => Self::from_entity_error(e, "user"),
+            UserContextError::Unverified => Self::new(
+                StatusCode::FORBIDDEN,
+                "email_unverified",
+                "you must verify your email address first",
+            ),
+            UserContextError::Banned => Self::new(
+                StatusCode::FORBIDDEN,
+                "banned",
+                "this account has been banned",
+            ),
+            _ => {
+                error!("Error getting authenticated user: {:?}", error);
+                Self::internal()
+            }
+        }
+    }
+
+    /// Used as the scope's `JsonConfig` error handler, so malformed bodies get a JSON error too.
+    pub fn from_json_error(error: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+        Self::bad_request(&error.to_string()).into()
+    }
+
+    pub fn from_query_error(error: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+        Self::bad_request(&error.to_string()).into()
+    }
+
+    fn internal() -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            "something went wrong, please try again",
+        )
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(ErrorBody {
+            error: ErrorDetail {
+                code: self.code.to_owned(),
+                message: self.message.clone(),
+                field: self.field.map(str::to_owned),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::to_bytes;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_entity_error_response() {
+        let error =
+            ApiError::from_entity_error(EntityError::InvalidInput("title", "too short"), "post");
+        assert_eq!(error.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = to_bytes(error.error_response().into_body()).await.unwrap();
+        assert_eq!(
+            body,
+            r#"{"error":{"code":"invalid_input","message":"too short","field":"title"}}"#
+        );
+
+        let error = ApiError::from_entity_error(EntityError::NotFound, "post");
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(error.to_string(), "not_found: post not found");
+    }
+}
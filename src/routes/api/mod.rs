@@ -0,0 +1,17 @@
+mod error;
+
+pub mod openapi;
+pub mod v1;
+
+pub use error::{ApiError, ErrorBody};
+
+use actix_web::HttpRequest;
+
+const API_PREFIX: &str = "/api/";
+const GRAPHQL_PATH: &str = "/graphql";
+
+/// API requests get JSON error bodies rather than redirects to the HTML error pages.
+/// The API only ever authenticates with bearer tokens, never the session cookie.
+pub fn is_api_request(req: &HttpRequest) -> bool {
+    req.path().starts_with(API_PREFIX) || req.path() == GRAPHQL_PATH
+}
@@ -0,0 +1,88 @@
+use actix_web::{body::BoxBody, http::header, HttpRequest, HttpResponse, Responder};
+use serde::Serialize;
+use utoipa::{
+    openapi::{
+        security::{Http, HttpAuthScheme, SecurityScheme},
+        OpenApi,
+    },
+    Modify, ToSchema,
+};
+
+/// Name of the bearer token scheme endpoints list under `security`.
+pub const SECURITY_SCHEME: &str = "bearerAuth";
+
+/// A 200 response with a JSON body.
+pub struct ApiJson<T: Serialize>(pub T);
+
+/// A 201 response with a JSON body and the `Location` of the new resource.
+pub struct Created<T: Serialize> {
+    pub location: String,
+    pub body: T,
+}
+
+/// Body of a submission the spam filter held for a moderator to review, `status` is `held`.
+#[derive(Serialize, ToSchema)]
+pub struct Held {
+    status: String,
+    message: String,
+}
+
+impl Held {
+    pub fn new(message: &str) -> Self {
+        Self {
+            status: "held".to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+/// A 201 like `Created`, or a 202 with a `Held` body when the submission was held for review,
+/// since nobody else can see it yet.
+pub enum CreatedOrHeld<T: Serialize> {
+    Created(Created<T>),
+    Held(Held),
+}
+
+impl<T: Serialize> Responder for ApiJson<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        HttpResponse::Ok().json(self.0)
+    }
+}
+
+impl<T: Serialize> Responder for Created<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        HttpResponse::Created()
+            .insert_header((header::LOCATION, self.location))
+            .json(self.body)
+    }
+}
+
+impl<T: Serialize> Responder for CreatedOrHeld<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        match self {
+            CreatedOrHeld::Created(created) => created.respond_to(req),
+            CreatedOrHeld::Held(held) => HttpResponse::Accepted().json(held),
+        }
+    }
+}
+
+/// Adds the personal access token scheme the endpoints' `security` refers to.
+pub struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                SECURITY_SCHEME,
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+    }
+}
@@ -0,0 +1,152 @@
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    entities::{comment::CommentStore, post::PostStore, user::UserStore, EntityStores},
+    routes::{
+        api::{openapi::ApiJson, v1, ApiError, ErrorBody},
+        bearer::{BearerError, BearerUser, ReadScope},
+        models::{self, CommentModel, Viewer},
+    },
+    server::Admins,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CommentsQuery {
+    after: Option<String>,
+    limit: Option<u8>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CommentsPage {
+    pub comments: Vec<CommentModel>,
+    pub next: Option<String>,
+}
+
+/// A post's top level comments, oldest first, each with its replies.
+#[utoipa::path(
+    get,
+    path = "/posts/{post}/comments",
+    tag = "comments",
+    summary = "A post's top level comments, oldest first",
+    params(("post" = String, Path), CommentsQuery),
+    responses(
+        (status = 200, description = "OK", body = CommentsPage),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the read scope", body = BearerError),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("read"))),
+)]
+pub async fn comments(
+    bearer_user: BearerUser<ReadScope>,
+    path: web::Path<String>,
+    query: web::Query<CommentsQuery>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> Result<ApiJson<CommentsPage>, ApiError> {
+    let post = stores
+        .post_store
+        .get_by_public_id(&path)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+    if post.is_removed {
+        return Err(ApiError::not_found("post"));
+    }
+    let viewer = Viewer::load(&stores, Some(&bearer_user.user), &admins).await;
+    check_visible(&stores, &viewer, post.author_id, "post").await?;
+
+    let start_index = match &query.after {
+        Some(after) => Some(
+            stores
+                .comment_store
+                .get_by_public_id(after)
+                .await
+                .map_err(|e| ApiError::from_entity_error(e, "comment"))?
+                .id,
+        ),
+        None => None,
+    };
+
+    let page_size = v1::page_size(query.limit);
+    let comment_entities = stores
+        .comment_store
+        .get_by_post_id_parent_id(post.id, None, start_index, page_size)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "comment"))?;
+    // Paged by what was fetched, so a page that lost comments to blocks doesn't look like the last
+    let next = v1::next_cursor(&comment_entities, page_size, |c| c.public_id.clone());
+    let comment_entities = viewer
+        .visible_comments(&stores, comment_entities)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "comment"))?;
+
+    let mut comments: Vec<CommentModel> = vec![];
+    for comment_entity in comment_entities.iter() {
+        comments.push(
+            models::translate_comment(&stores, &viewer, comment_entity, 0)
+                .await
+                .map_err(|e| ApiError::from_entity_error(e, "comment"))?,
+        );
+    }
+
+    Ok(ApiJson(CommentsPage { next, comments }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/comments/{comment}",
+    tag = "comments",
+    summary = "A comment and its replies",
+    params(("comment" = String, Path)),
+    responses(
+        (status = 200, description = "OK", body = CommentModel),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the read scope", body = BearerError),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("read"))),
+)]
+pub async fn comment(
+    bearer_user: BearerUser<ReadScope>,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> Result<ApiJson<CommentModel>, ApiError> {
+    let comment = stores
+        .comment_store
+        .get_by_public_id(&path)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "comment"))?;
+    let viewer = Viewer::load(&stores, Some(&bearer_user.user), &admins).await;
+    check_visible(&stores, &viewer, comment.author_id, "comment").await?;
+
+    let comment_model = models::translate_comment(&stores, &viewer, &comment, 0)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "comment"))?;
+
+    Ok(ApiJson(comment_model))
+}
+
+// Hidden posts and comments are reported as not found, like removed ones
+async fn check_visible(
+    stores: &EntityStores,
+    viewer: &Viewer,
+    author_id: u64,
+    entity: &'static str,
+) -> Result<(), ApiError> {
+    let author = stores
+        .user_store
+        .get_by_id(author_id)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, entity))?;
+
+    match viewer.can_see(&author) {
+        true => Ok(()),
+        false => Err(ApiError::not_found(entity)),
+    }
+}
@@ -0,0 +1,136 @@
+use actix_web::{http::StatusCode, web};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    entities::{comment::CommentStore, post::PostStore, user::Permission, EntityStores},
+    live::{CommentBus, CommentEvent},
+    routes::{
+        api::{
+            openapi::{Created, CreatedOrHeld, Held},
+            ApiError, ErrorBody,
+        },
+        bearer::{BearerError, BearerUser, CommentScope},
+        models::{self, CommentModel, Viewer},
+        permissions,
+        rate_limited::{Comment, RateLimited},
+        reports,
+        user_context::user_context,
+    },
+    server::{Admins, VerificationPolicy},
+    spam::SpamFilter,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CommentRequest {
+    parent_id: Option<String>,
+    content: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/posts/{post}/comments",
+    tag = "comments",
+    summary = "Comment on a post, or reply to one of its comments",
+    params(("post" = String, Path)),
+    request_body = CommentRequest,
+    responses(
+        (status = 201, description = "Created", body = CommentModel, headers(("Location" = String, description = "Where the new comment is"))),
+        (status = 202, description = "Held for a moderator to review", body = Held),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the comment scope", body = BearerError),
+        (status = 429, description = "Rate limited", headers(("Retry-After" = u32, description = "Seconds until the request can be retried"))),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("comment"))),
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_comment(
+    _rate_limited: RateLimited<Comment>,
+    bearer_user: BearerUser<CommentScope>,
+    path: web::Path<String>,
+    data: web::Json<CommentRequest>,
+    stores: web::Data<EntityStores>,
+    verification_policy: web::Data<VerificationPolicy>,
+    comment_bus: web::Data<CommentBus>,
+    admins: web::Data<Admins>,
+    spam_filter: web::Data<SpamFilter>,
+) -> Result<CreatedOrHeld<CommentModel>, ApiError> {
+    let author = user_context::check_verified(bearer_user.user, &verification_policy)
+        .map_err(ApiError::from_user_context_error)?;
+
+    let post = stores
+        .post_store
+        .get_by_public_id(&path)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+    if post.is_removed {
+        return Err(ApiError::not_found("post"));
+    }
+    if post.is_locked {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "locked",
+            "this thread is locked, so it isn't accepting new comments",
+        ));
+    }
+
+    let parent_id = match &data.parent_id {
+        Some(parent_id) => {
+            let parent = stores
+                .comment_store
+                .get_by_public_id(parent_id)
+                .await
+                .map_err(|e| ApiError::from_entity_error(e, "parent comment"))?;
+            if parent.post_id != post.id {
+                return Err(ApiError::bad_request(
+                    "parent comment belongs to a different post",
+                ));
+            }
+            Some(parent.id)
+        }
+        None => None,
+    };
+
+    // Moderators' own comments are never held
+    let held_score = match permissions::has_permission(&author, &admins, Permission::RemoveContent)
+    {
+        true => None,
+        false => spam_filter.check_comment(&stores, &data.content).await,
+    };
+
+    let comment = stores
+        .comment_store
+        .insert(
+            &author.id,
+            &post.id,
+            &parent_id,
+            &data.content,
+            held_score.is_some(),
+        )
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "comment"))?;
+    if let Some(score) = held_score {
+        reports::hold(&stores, post.id, Some(comment.id), score).await;
+        return Ok(CreatedOrHeld::Held(Held::new(
+            "your comment is being held for a moderator to review",
+        )));
+    }
+    comment_bus
+        .publish(CommentEvent {
+            post_id: post.id,
+            comment_id: comment.id,
+        })
+        .await;
+
+    // A new comment has no replies to hide yet
+    let comment_model = models::translate_comment(&stores, &Viewer::anonymous(), &comment, 0)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "comment"))?;
+
+    Ok(CreatedOrHeld::Created(Created {
+        location: format!("/api/v1/comments/{}", comment_model.id),
+        body: comment_model,
+    }))
+}
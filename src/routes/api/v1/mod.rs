@@ -0,0 +1,198 @@
+use actix_web::{http::StatusCode, web, HttpResponse};
+use utoipa::{
+    openapi::{self, Server},
+    OpenApi,
+};
+
+use super::{openapi::BearerAuth, ApiError};
+
+pub mod comments;
+pub mod posts;
+pub mod users;
+
+const BASE_PATH: &str = "/api/v1";
+const TITLE: &str = "effward.dev API";
+const VERSION: &str = "1";
+
+const DEFAULT_PAGE_SIZE: u8 = 15;
+const MAX_PAGE_SIZE: u8 = 50;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = TITLE, version = VERSION),
+    paths(
+        posts::get::posts,
+        posts::post::create_post,
+        posts::get::post,
+        comments::get::comments,
+        comments::post::create_comment,
+        comments::get::comment,
+        users::get::user,
+        users::get::me,
+    ),
+    modifiers(&BearerAuth)
+)]
+struct ApiDoc;
+
+/// Registers the API's endpoints and its OpenAPI document on the `/api/v1` scope.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default().error_handler(ApiError::from_json_error))
+        .app_data(web::QueryConfig::default().error_handler(ApiError::from_query_error))
+        .route("/openapi.json", web::get().to(openapi_json))
+        .route("/posts", web::get().to(posts::get::posts))
+        .route("/posts", web::post().to(posts::post::create_post))
+        .route("/posts/{post}", web::get().to(posts::get::post))
+        .route(
+            "/posts/{post}/comments",
+            web::get().to(comments::get::comments),
+        )
+        .route(
+            "/posts/{post}/comments",
+            web::post().to(comments::post::create_comment),
+        )
+        .route("/comments/{comment}", web::get().to(comments::get::comment))
+        .route("/users/{user}", web::get().to(users::get::user))
+        .route("/user", web::get().to(users::get::me))
+        .default_service(web::to(unknown_endpoint));
+}
+
+/// The OpenAPI 3 document describing the API, public so that clients can be generated from it.
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(openapi_document())
+}
+
+fn openapi_document() -> openapi::OpenApi {
+    let mut document = ApiDoc::openapi();
+    // Left out rather than filled in from the crate's empty Cargo.toml fields
+    document.info.description = None;
+    document.info.license = None;
+    document.servers = Some(vec![Server::new(BASE_PATH)]);
+    document
+}
+
+/// Default service for the API scope, so unknown endpoints get a JSON 404 as well.
+pub async fn unknown_endpoint() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::new(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        "no such endpoint",
+    ))
+}
+
+fn page_size(limit: Option<u8>) -> u8 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+// A full page means there may be more, so the last id is the cursor for the next one
+fn next_cursor<T>(items: &[T], page_size: u8, id: impl Fn(&T) -> String) -> Option<String> {
+    match items.len() == page_size as usize {
+        true => items.last().map(id),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use actix_web::{
+        http::Method,
+        test::{call_service, init_service, TestRequest},
+        web::scope,
+        App,
+    };
+
+    use serde_json::Value;
+
+    use super::*;
+
+    const OPENAPI_SNAPSHOT: &str = "docs/api/v1/openapi.json";
+
+    #[test]
+    fn test_paging() {
+        assert_eq!(page_size(None), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size(Some(0)), 1);
+        assert_eq!(page_size(Some(255)), MAX_PAGE_SIZE);
+
+        let ids = vec![3, 2, 1];
+        assert_eq!(
+            next_cursor(&ids, 3, |i| i.to_string()).as_deref(),
+            Some("1")
+        );
+        assert_eq!(next_cursor(&ids, 4, |i| i.to_string()), None);
+    }
+
+    // Fails when a handler's request or response types change without the committed document
+    #[test]
+    fn test_openapi_document_is_current() {
+        let document = serde_json::to_string_pretty(&openapi_document()).unwrap() + "\n";
+        let snapshot = Path::new(env!("CARGO_MANIFEST_DIR")).join(OPENAPI_SNAPSHOT);
+        if env::var_os("UPDATE_OPENAPI").is_some() {
+            fs::write(&snapshot, &document).unwrap();
+        }
+
+        let committed = fs::read_to_string(&snapshot).unwrap_or_default();
+        assert!(
+            committed == document,
+            "{} is out of date, run `UPDATE_OPENAPI=1 cargo test` and commit the changes",
+            OPENAPI_SNAPSHOT
+        );
+    }
+
+    #[test]
+    fn test_openapi_references_resolve() {
+        fn check_references(value: &Value, document: &Value) {
+            match value {
+                Value::Object(object) => {
+                    if let Some(Value::String(reference)) = object.get("$ref") {
+                        let pointer = reference.trim_start_matches('#');
+                        assert!(
+                            document.pointer(pointer).is_some_and(|v| !v.is_null()),
+                            "unresolved {}",
+                            reference
+                        );
+                    }
+                    object.values().for_each(|v| check_references(v, document));
+                }
+                Value::Array(array) => array.iter().for_each(|v| check_references(v, document)),
+                _ => {}
+            }
+        }
+
+        let document = serde_json::to_value(openapi_document()).unwrap();
+        check_references(&document, &document);
+    }
+
+    #[actix_web::test]
+    async fn test_endpoints_require_token() {
+        let app = init_service(App::new().service(scope(BASE_PATH).configure(configure))).await;
+
+        // Every documented endpoint is routed, and checks the token before anything else
+        for (path, item) in openapi_document().paths.paths {
+            let methods = [
+                (Method::GET, item.get.is_some()),
+                (Method::POST, item.post.is_some()),
+            ];
+            for (method, _) in methods.into_iter().filter(|(_, documented)| *documented) {
+                let req = TestRequest::default()
+                    .method(method.clone())
+                    .uri(&format!("{}{}", BASE_PATH, path.replace(['{', '}'], "")))
+                    .to_request();
+                let res = call_service(&app, req).await;
+
+                assert_eq!(
+                    res.status(),
+                    StatusCode::UNAUTHORIZED,
+                    "{} {}",
+                    method,
+                    path
+                );
+            }
+        }
+
+        let req = TestRequest::get()
+            .uri(&format!("{}/openapi.json", BASE_PATH))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+    }
+}
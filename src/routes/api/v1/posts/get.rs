@@ -0,0 +1,136 @@
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    entities::{post::PostStore, user::UserStore, EntityStores},
+    routes::{
+        api::{openapi::ApiJson, v1, ApiError, ErrorBody},
+        bearer::{BearerError, BearerUser, ReadScope},
+        models::{self, PostModel, PostSummary, Viewer},
+    },
+    server::Admins,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PostsQuery {
+    before: Option<String>,
+    limit: Option<u8>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PostsPage {
+    pub posts: Vec<PostSummary>,
+    pub next: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/posts",
+    tag = "posts",
+    summary = "Recent posts, newest first",
+    params(PostsQuery),
+    responses(
+        (status = 200, description = "OK", body = PostsPage),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the read scope", body = BearerError),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("read"))),
+)]
+pub async fn posts(
+    bearer_user: BearerUser<ReadScope>,
+    query: web::Query<PostsQuery>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> Result<ApiJson<PostsPage>, ApiError> {
+    let start_index = match &query.before {
+        Some(before) => Some(
+            stores
+                .post_store
+                .get_by_public_id(before)
+                .await
+                .map_err(|e| ApiError::from_entity_error(e, "post"))?
+                .id,
+        ),
+        None => None,
+    };
+
+    let page_size = v1::page_size(query.limit);
+    let viewer = Viewer::load(&stores, Some(&bearer_user.user), &admins).await;
+    let post_entities = stores
+        .post_store
+        .get_recent(
+            start_index,
+            page_size,
+            viewer.shadowbanned_id(),
+            viewer.sees_shadowbanned(),
+        )
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+    // Paged by what was fetched, so a page that lost posts to blocks doesn't look like the last
+    let next = v1::next_cursor(&post_entities, page_size, |p| p.public_id.clone());
+    let post_entities = viewer
+        .visible_posts(&stores, post_entities)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+
+    let mut posts: Vec<PostSummary> = vec![];
+    for post_entity in post_entities.iter() {
+        posts.push(
+            models::translate_post_summary(post_entity, &stores, 0)
+                .await
+                .map_err(|e| ApiError::from_entity_error(e, "post"))?,
+        );
+    }
+
+    Ok(ApiJson(PostsPage { next, posts }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/posts/{post}",
+    tag = "posts",
+    summary = "A post and its comments",
+    params(("post" = String, Path)),
+    responses(
+        (status = 200, description = "OK", body = PostModel),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the read scope", body = BearerError),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("read"))),
+)]
+pub async fn post(
+    bearer_user: BearerUser<ReadScope>,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> Result<ApiJson<PostModel>, ApiError> {
+    let post = stores
+        .post_store
+        .get_by_public_id(&path)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+    if post.is_removed {
+        return Err(ApiError::not_found("post"));
+    }
+    let viewer = Viewer::load(&stores, Some(&bearer_user.user), &admins).await;
+    let author = stores
+        .user_store
+        .get_by_id(post.author_id)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+    if !viewer.can_see(&author) {
+        return Err(ApiError::not_found("post"));
+    }
+
+    let post_model = models::translate_post(&post, &stores, &viewer)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+
+    Ok(ApiJson(post_model))
+}
@@ -0,0 +1,102 @@
+use actix_web::web;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{
+    entities::{post::PostStore, user::Permission, EntityStores},
+    routes::{
+        api::{
+            openapi::{Created, CreatedOrHeld, Held},
+            ApiError, ErrorBody,
+        },
+        bearer::{BearerError, BearerUser, PostScope},
+        models::{self, PostSummary},
+        permissions,
+        rate_limited::{RateLimited, Submit},
+        reports,
+        user_context::user_context,
+    },
+    server::{Admins, VerificationPolicy},
+    spam::SpamFilter,
+    unfurl::LinkPreviewer,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubmitRequest {
+    title: String,
+    link: Option<String>,
+    content: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/posts",
+    tag = "posts",
+    summary = "Submit a post",
+    request_body = SubmitRequest,
+    responses(
+        (status = 201, description = "Created", body = PostSummary, headers(("Location" = String, description = "Where the new post is"))),
+        (status = 202, description = "Held for a moderator to review", body = Held),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the post scope", body = BearerError),
+        (status = 429, description = "Rate limited", headers(("Retry-After" = u32, description = "Seconds until the request can be retried"))),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("post"))),
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_post(
+    _rate_limited: RateLimited<Submit>,
+    bearer_user: BearerUser<PostScope>,
+    data: web::Json<SubmitRequest>,
+    stores: web::Data<EntityStores>,
+    verification_policy: web::Data<VerificationPolicy>,
+    link_previewer: web::Data<LinkPreviewer>,
+    admins: web::Data<Admins>,
+    spam_filter: web::Data<SpamFilter>,
+) -> Result<CreatedOrHeld<PostSummary>, ApiError> {
+    let author = user_context::check_verified(bearer_user.user, &verification_policy)
+        .map_err(ApiError::from_user_context_error)?;
+
+    // Moderators' own posts are never held
+    let held_score = match permissions::has_permission(&author, &admins, Permission::RemoveContent)
+    {
+        true => None,
+        false => {
+            spam_filter
+                .check_post(&stores, &data.title, &data.link, &data.content)
+                .await
+        }
+    };
+
+    let post = stores
+        .post_store
+        .insert(
+            &author.id,
+            &data.title,
+            &data.link,
+            &data.content,
+            held_score.is_some(),
+        )
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+
+    // A held post is previewed once a moderator approves it
+    if let Some(score) = held_score {
+        reports::hold(&stores, post.id, None, score).await;
+        return Ok(CreatedOrHeld::Held(Held::new(
+            "your post is being held for a moderator to review",
+        )));
+    }
+    link_previewer.spawn(&stores, &post);
+
+    let post_summary = models::translate_post_summary(&post, &stores, 0)
+        .await
+        .map_err(|e| ApiError::from_entity_error(e, "post"))?;
+
+    Ok(CreatedOrHeld::Created(Created {
+        location: format!("/api/v1/posts/{}", post_summary.id),
+        body: post_summary,
+    }))
+}
@@ -0,0 +1,64 @@
+use actix_web::web;
+
+use crate::{
+    entities::{user::UserStore, EntityError, EntityStores},
+    routes::{
+        api::{openapi::ApiJson, ApiError, ErrorBody},
+        bearer::{BearerError, BearerUser, ReadScope},
+        models::UserModel,
+    },
+};
+
+/// Looks a user up by id, or by name like the `/user/{user}` page.
+#[utoipa::path(
+    get,
+    path = "/users/{user}",
+    tag = "users",
+    summary = "A user, by id or name",
+    params(("user" = String, Path)),
+    responses(
+        (status = 200, description = "OK", body = UserModel),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the read scope", body = BearerError),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("read"))),
+)]
+pub async fn user(
+    _bearer_user: BearerUser<ReadScope>,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+) -> Result<ApiJson<UserModel>, ApiError> {
+    let path_user = path.into_inner();
+    let user = match stores.user_store.get_by_public_id(&path_user).await {
+        Ok(u) => u,
+        Err(EntityError::InvalidInput("public_id", _)) => stores
+            .user_store
+            .get_by_name(&path_user)
+            .await
+            .map_err(|e| ApiError::from_entity_error(e, "user"))?,
+        Err(e) => return Err(ApiError::from_entity_error(e, "user")),
+    };
+
+    Ok(ApiJson(UserModel::from(user)))
+}
+
+/// The user the token belongs to.
+#[utoipa::path(
+    get,
+    path = "/user",
+    tag = "users",
+    summary = "The user the token belongs to",
+    responses(
+        (status = 200, description = "OK", body = UserModel),
+        (status = 401, description = "Missing, invalid or revoked token", body = BearerError),
+        (status = 403, description = "Token lacks the read scope", body = BearerError),
+        (status = "default", description = "Error", body = ErrorBody),
+    ),
+    security(("bearerAuth" = [])),
+    extensions(("x-required-scope" = json!("read"))),
+)]
+pub async fn me(bearer_user: BearerUser<ReadScope>) -> Result<ApiJson<UserModel>, ApiError> {
+    Ok(ApiJson(UserModel::from(bearer_user.user)))
+}
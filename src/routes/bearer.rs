@@ -0,0 +1,242 @@
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+use actix_web::{
+    dev::{Payload, ServiceResponse},
+    error::{ErrorInternalServerError, InternalError},
+    http::header,
+    middleware::ErrorHandlerResponse,
+    web, FromRequest, HttpRequest, HttpResponse, Result,
+};
+use log::{error, warn};
+use serde::Serialize;
+
+use crate::entities::{
+    api_token::{ApiScope, ApiTokenStore},
+    user::{User, UserStore},
+    EntityError, EntityStores,
+};
+
+use utoipa::ToSchema;
+
+const BEARER_PREFIX: &str = "Bearer ";
+// Routes outside the API that take `MaybeBearerUser`, when a token is sent it's used instead of
+// the session
+const BEARER_ROUTES: [&str; 2] = ["/submit", "/comment"];
+
+/// The scope a route requires of personal access tokens.
+pub trait RequiredScope {
+    const SCOPE: ApiScope;
+}
+
+pub struct ReadScope;
+pub struct PostScope;
+pub struct CommentScope;
+
+impl RequiredScope for ReadScope {
+    const SCOPE: ApiScope = ApiScope::Read;
+}
+
+impl RequiredScope for PostScope {
+    const SCOPE: ApiScope = ApiScope::Post;
+}
+
+impl RequiredScope for CommentScope {
+    const SCOPE: ApiScope = ApiScope::Comment;
+}
+
+/// Error body for rejected tokens, shaped as in RFC 6750 rather than like other API errors.
+#[derive(Serialize, ToSchema)]
+pub struct BearerError {
+    error: String,
+    error_description: String,
+}
+
+/// Extractor that resolves an `Authorization: Bearer` personal access token to its user,
+/// rejecting the request before the handler runs if the token is missing, invalid, revoked
+/// or lacks scope `S`.
+pub struct BearerUser<S: RequiredScope> {
+    pub user: User,
+    scope: PhantomData<S>,
+}
+
+impl<S: RequiredScope + 'static> FromRequest for BearerUser<S> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+
+        Box::pin(async move {
+            let token = match bearer_token(&req) {
+                Some(t) => t,
+                None => return Err(unauthorized(None, "missing bearer token")),
+            };
+            let stores = match req.app_data::<web::Data<EntityStores>>() {
+                Some(s) => s.clone(),
+                None => {
+                    error!("EntityStores missing from app data, can't check bearer token");
+                    return Err(ErrorInternalServerError("missing entity stores"));
+                }
+            };
+
+            let api_token = match stores.api_token_store.get_by_token(&token).await {
+                Ok(t) => t,
+                Err(EntityError::NotFound) => {
+                    warn!(
+                        "🔑 Rejected invalid or revoked bearer token for {}",
+                        req.path()
+                    );
+                    return Err(unauthorized(
+                        Some("invalid_token"),
+                        "invalid or revoked token",
+                    ));
+                }
+                Err(e) => {
+                    error!("Error getting api token: {:?}", e);
+                    return Err(ErrorInternalServerError("error checking token"));
+                }
+            };
+            if !api_token.has_scope(S::SCOPE) {
+                return Err(insufficient_scope(S::SCOPE));
+            }
+
+            match stores.user_store.get_by_id(api_token.user_id).await {
+                Ok(user) if user.is_banned => Err(unauthorized(
+                    Some("invalid_token"),
+                    "the token's user has been banned",
+                )),
+                Ok(user) if !user.is_deleted => Ok(BearerUser {
+                    user,
+                    scope: PhantomData,
+                }),
+                Ok(_) | Err(EntityError::NotFound) => Err(unauthorized(
+                    Some("invalid_token"),
+                    "the token's user no longer exists",
+                )),
+                Err(e) => {
+                    error!("Error getting api token user: {:?}", e);
+                    Err(ErrorInternalServerError("error checking token"))
+                }
+            }
+        })
+    }
+}
+
+/// Like `BearerUser`, but lets requests without an `Authorization` header through so that the
+/// route can fall back to the session. A token that is present must still be valid.
+pub struct MaybeBearerUser<S: RequiredScope>(pub Option<BearerUser<S>>);
+
+impl<S: RequiredScope + 'static> FromRequest for MaybeBearerUser<S> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if !req.headers().contains_key(header::AUTHORIZATION) {
+            return Box::pin(async { Ok(MaybeBearerUser(None)) });
+        }
+
+        let bearer_user = BearerUser::<S>::from_request(req, payload);
+        Box::pin(async move { Ok(MaybeBearerUser(Some(bearer_user.await?))) })
+    }
+}
+
+/// Whether the request is to a route that authenticates with `MaybeBearerUser` and carries a valid
+/// token. Those routes then ignore the session cookie, so another site can't forge the request
+/// without the token and it doesn't need a csrf token. Every other route still uses the session,
+/// whatever the request's `Authorization` header says.
+pub async fn has_valid_bearer_token(req: &HttpRequest) -> bool {
+    if !BEARER_ROUTES.contains(&req.path()) {
+        return false;
+    }
+    let (token, stores) = match (bearer_token(req), req.app_data::<web::Data<EntityStores>>()) {
+        (Some(token), Some(stores)) => (token, stores),
+        _ => return false,
+    };
+
+    stores.api_token_store.get_by_token(&token).await.is_ok()
+}
+
+/// Lets 401 and 403 responses through untouched, so scripts get the JSON error instead of a
+/// redirect to the generic error page.
+pub fn render_auth_error<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+    Ok(ErrorHandlerResponse::Response(res.map_into_left_body()))
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    let authorization = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let token = authorization.strip_prefix(BEARER_PREFIX)?.trim();
+
+    match token.is_empty() {
+        true => None,
+        false => Some(token.to_owned()),
+    }
+}
+
+// Error responses follow RFC 6750 section 3
+fn unauthorized(error_code: Option<&str>, description: &str) -> actix_web::Error {
+    let challenge = match error_code {
+        Some(code) => format!("Bearer error=\"{}\"", code),
+        None => "Bearer".to_owned(),
+    };
+    let response = HttpResponse::Unauthorized()
+        .insert_header((header::WWW_AUTHENTICATE, challenge))
+        .json(BearerError {
+            error: error_code.unwrap_or("unauthorized").to_owned(),
+            error_description: description.to_owned(),
+        });
+
+    InternalError::from_response(description.to_owned(), response).into()
+}
+
+fn insufficient_scope(scope: ApiScope) -> actix_web::Error {
+    let description = format!("token requires the {} scope", scope);
+    let response = HttpResponse::Forbidden()
+        .insert_header((
+            header::WWW_AUTHENTICATE,
+            format!("Bearer error=\"insufficient_scope\", scope=\"{}\"", scope),
+        ))
+        .json(BearerError {
+            error: "insufficient_scope".to_owned(),
+            error_description: description.clone(),
+        });
+
+    InternalError::from_response(description, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[test]
+    fn test_bearer_token() {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer abc123"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req).as_deref(), Some("abc123"));
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Basic abc123"))
+            .to_http_request();
+        assert_eq!(bearer_token(&req), None);
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer "))
+            .to_http_request();
+        assert_eq!(bearer_token(&req), None);
+    }
+
+    #[actix_web::test]
+    async fn test_has_valid_bearer_token() {
+        // Other routes never skip the csrf check, whatever the token
+        let req = TestRequest::post()
+            .uri("/logout")
+            .insert_header((header::AUTHORIZATION, "Bearer abc123"))
+            .to_http_request();
+        assert!(!has_valid_bearer_token(&req).await);
+
+        let req = TestRequest::post().uri("/submit").to_http_request();
+        assert!(!has_valid_bearer_token(&req).await);
+    }
+}
@@ -1,12 +1,28 @@
-use actix_web::{web, Responder};
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, warn};
 use serde::Deserialize;
 
 use crate::{
-    entities::{comment::CommentStore, post::PostStore, EntityStores},
+    entities::{
+        comment::CommentStore,
+        moderation_log::{ModerationAction, ModerationTarget},
+        post::PostStore,
+        report::{ReportResolution, ReportStore},
+        user::{Permission, User},
+        EntityError, EntityStores,
+    },
+    live::{CommentBus, CommentEvent},
     routes::{
+        bearer::{CommentScope, MaybeBearerUser},
+        moderation_log::{self, ModerationRequest},
+        permissions,
+        rate_limited::{Comment, RateLimited},
+        reports,
         user_context::{session_state::TypedSession, user_context, UserContextError},
         utils,
     },
+    server::{Admins, VerificationPolicy},
+    spam::{self, SpamFilter},
 };
 
 #[derive(Debug, Deserialize)]
@@ -16,12 +32,27 @@ pub struct CommentRequest {
     content: String,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn process_comment(
+    _rate_limited: RateLimited<Comment>,
+    bearer_user: MaybeBearerUser<CommentScope>,
     session: TypedSession,
     data: web::Form<CommentRequest>,
     stores: web::Data<EntityStores>,
+    verification_policy: web::Data<VerificationPolicy>,
+    comment_bus: web::Data<CommentBus>,
+    admins: web::Data<Admins>,
+    spam_filter: web::Data<SpamFilter>,
 ) -> impl Responder {
-    match user_context::get_auth_user_entity(session, &stores).await {
+    let auth_user_entity = match bearer_user.0 {
+        Some(bearer_user) => user_context::check_verified(bearer_user.user, &verification_policy),
+        None => {
+            user_context::get_verified_auth_user_entity(session, &stores, &verification_policy)
+                .await
+        }
+    };
+
+    match auth_user_entity {
         Ok(auth_user_entity) => {
             let post = match stores.post_store.get_by_public_id(&data.post_id).await {
                 Ok(p) => p,
@@ -29,6 +60,12 @@ pub async fn process_comment(
                     return utils::redirect_entity_error(entity_error, "post");
                 }
             };
+            if post.is_locked || post.is_removed {
+                return utils::warning_redirect(
+                    &format!("/post/{}", data.post_id),
+                    "this thread is locked, so it isn't accepting new comments",
+                );
+            }
 
             let parent_id = match data.parent_id.to_owned() {
                 Some(parent_id) => match stores.comment_store.get_by_public_id(&parent_id).await {
@@ -40,15 +77,48 @@ pub async fn process_comment(
                 None => None,
             };
 
+            // Moderators' own comments are never held
+            let held_score = match permissions::has_permission(
+                &auth_user_entity,
+                &admins,
+                Permission::RemoveContent,
+            ) {
+                true => None,
+                false => spam_filter.check_comment(&stores, &data.content).await,
+            };
+
             match stores
                 .comment_store
-                .insert(&auth_user_entity.id, &post.id, &parent_id, &data.content)
+                .insert(
+                    &auth_user_entity.id,
+                    &post.id,
+                    &parent_id,
+                    &data.content,
+                    held_score.is_some(),
+                )
                 .await
             {
-                Ok(_) => utils::success_redirect(
-                    &format!("/post/{}", data.post_id),
-                    "new comment successfully submitted, it should appear momentarily...",
-                ),
+                Ok(comment) => match held_score {
+                    Some(score) => {
+                        reports::hold(&stores, post.id, Some(comment.id), score).await;
+                        utils::warning_redirect(
+                            &format!("/post/{}", data.post_id),
+                            "your comment is being held for a moderator to review",
+                        )
+                    }
+                    None => {
+                        comment_bus
+                            .publish(CommentEvent {
+                                post_id: post.id,
+                                comment_id: comment.id,
+                            })
+                            .await;
+                        utils::success_redirect(
+                            &format!("/post/{}", data.post_id),
+                            "new comment successfully submitted, it should appear momentarily...",
+                        )
+                    }
+                },
                 Err(_) => utils::warning_redirect(
                     &format!("/post/{}", data.post_id),
                     "something went wrong submitting your comment, please try again",
@@ -66,6 +136,138 @@ pub async fn process_comment(
             UserContextError::NotAuthenticated => {
                 utils::error_redirect("/login", "you must be logged in to submit comments")
             }
+            UserContextError::Unverified => utils::warning_redirect(
+                &format!("/post/{}", data.post_id),
+                "you must verify your email address before submitting comments",
+            ),
+            UserContextError::Banned => {
+                utils::error_redirect("/login", "this account has been banned")
+            }
+            UserContextError::MustResetPassword => utils::error_redirect(
+                "/login",
+                "you have to choose a new password, please log in again",
+            ),
         },
     }
 }
+
+pub async fn process_remove(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    comment_bus: web::Data<CommentBus>,
+) -> impl Responder {
+    set_removed(session, &path, &data, &stores, &admins, &comment_bus, true).await
+}
+
+pub async fn process_restore(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    comment_bus: web::Data<CommentBus>,
+) -> impl Responder {
+    set_removed(session, &path, &data, &stores, &admins, &comment_bus, false).await
+}
+
+async fn set_removed(
+    session: TypedSession,
+    path_comment: &str,
+    data: &ModerationRequest,
+    stores: &EntityStores,
+    admins: &Admins,
+    comment_bus: &CommentBus,
+    is_removed: bool,
+) -> HttpResponse {
+    let comment = match stores.comment_store.get_by_public_id(path_comment).await {
+        Ok(c) => c,
+        Err(e) => return utils::redirect_entity_error(e, "comment"),
+    };
+    let post = match stores.post_store.get_by_id(comment.post_id).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+    let location = format!("/post/{}", post.public_id);
+
+    let moderator = match permissions::require_permission(
+        session,
+        stores,
+        admins,
+        Permission::RemoveContent,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let is_held = match stores.report_store.is_held(post.id, Some(comment.id)).await {
+        Ok(h) => h,
+        Err(e) => return utils::redirect_entity_error(e, "reports"),
+    };
+    let is_approval = reports::is_approval(is_removed, is_held);
+
+    let action = if is_removed { "removed" } else { "restored" };
+    let updated = match is_approval {
+        true => approve(stores, comment_bus, &moderator, post.id, comment.id).await,
+        false => stores
+            .comment_store
+            .set_removed(comment.id, is_removed)
+            .await
+            .map(|_| ()),
+    };
+    match updated {
+        Ok(_) => {
+            warn!(
+                "🛡️ {} {} comment {}",
+                moderator.name, action, comment.public_id
+            );
+            let log_action = match (is_removed, is_approval) {
+                (true, _) => ModerationAction::RemoveComment,
+                (false, true) => ModerationAction::ApproveHeld,
+                (false, false) => ModerationAction::RestoreComment,
+            };
+            moderation_log::record(
+                stores,
+                &moderator,
+                log_action,
+                ModerationTarget::comment(&comment),
+                &data.reason,
+                None,
+            )
+            .await;
+            spam::train_comment(stores, &comment, is_removed).await;
+            utils::success_redirect(&location, &format!("comment {}", action))
+        }
+        Err(e) => {
+            error!("Error setting comment {} removed: {:?}", comment.id, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
+
+// Publishes a held comment and closes its reports, so it's only ever published once
+async fn approve(
+    stores: &EntityStores,
+    comment_bus: &CommentBus,
+    moderator: &User,
+    post_id: u64,
+    comment_id: u64,
+) -> Result<(), EntityError> {
+    reports::publish_held_comment(stores, comment_bus, post_id, comment_id).await?;
+    stores
+        .report_store
+        .resolve(
+            post_id,
+            Some(comment_id),
+            moderator.id,
+            ReportResolution::Dismissed,
+        )
+        .await?;
+
+    Ok(())
+}
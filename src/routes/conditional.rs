@@ -0,0 +1,103 @@
+use std::time::SystemTime;
+
+use actix_web::{
+    http::header::{
+        self, CacheControl, CacheDirective, EntityTag, Header, HttpDate, IfModifiedSince,
+        IfNoneMatch,
+    },
+    HttpRequest, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+// Long enough to spare the database when readers poll, short enough that new posts show up soon
+const MAX_AGE_SECS: u32 = 300;
+
+/// Responds with `body`, or a bodyless 304 if the client's copy is still current according to its
+/// `If-None-Match` or `If-Modified-Since` headers.
+pub fn conditional_response(
+    req: &HttpRequest,
+    content_type: &str,
+    body: String,
+    last_modified: DateTime<Utc>,
+) -> HttpResponse {
+    let etag = EntityTag::new_strong(hex::encode(&Sha256::digest(body.as_bytes())[..16]));
+
+    let is_fresh = is_fresh(req, &etag, last_modified);
+    let mut response = match is_fresh {
+        true => HttpResponse::NotModified(),
+        false => HttpResponse::Ok(),
+    };
+    response
+        .insert_header(header::ETag(etag))
+        .insert_header(header::LastModified(HttpDate::from(SystemTime::from(
+            last_modified,
+        ))))
+        .insert_header(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(MAX_AGE_SECS),
+        ]));
+
+    match is_fresh {
+        true => response.finish(),
+        false => response.content_type(content_type).body(body),
+    }
+}
+
+fn is_fresh(req: &HttpRequest, etag: &EntityTag, last_modified: DateTime<Utc>) -> bool {
+    // If-None-Match takes precedence when both are sent (RFC 9110 section 13.2.2)
+    if req.headers().contains_key(header::IF_NONE_MATCH) {
+        return match IfNoneMatch::parse(req) {
+            Ok(IfNoneMatch::Any) => true,
+            Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+            Err(_) => false,
+        };
+    }
+
+    match IfModifiedSince::parse(req) {
+        // HTTP dates only have second precision
+        Ok(IfModifiedSince(since)) => {
+            let since = DateTime::<Utc>::from(SystemTime::from(since));
+            last_modified.timestamp() <= since.timestamp()
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn test_conditional_response() {
+        let modified = Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap();
+        let req = TestRequest::default().to_http_request();
+        let response = conditional_response(&req, "text/plain", "hi".to_owned(), modified);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, etag))
+            .to_http_request();
+        let response = conditional_response(&req, "text/plain", "hi".to_owned(), modified);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let since = HttpDate::from(SystemTime::from(modified + Duration::seconds(30)));
+        let req = TestRequest::default()
+            .insert_header((header::IF_MODIFIED_SINCE, since))
+            .to_http_request();
+        let response = conditional_response(&req, "text/plain", "hi".to_owned(), modified);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        // A changed body fails the etag check even though the date would pass
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"stale\""))
+            .insert_header((header::IF_MODIFIED_SINCE, since))
+            .to_http_request();
+        let response = conditional_response(&req, "text/plain", "hi".to_owned(), modified);
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
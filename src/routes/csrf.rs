@@ -0,0 +1,266 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method},
+    web, Error, FromRequest,
+};
+use log::{error, warn};
+
+use crate::routes::{api, bearer, unsubscribe, utils};
+
+use super::user_context::session_state::TypedSession;
+
+const FORM_FIELD: &str = "csrf_token";
+const HEADER_NAME: &str = "x-csrf-token";
+const ERROR_PAGE: &str = "/error/csrf";
+
+/// Middleware that rejects state-changing requests unless they carry the session's CSRF token,
+/// either as the `csrf_token` form field or the `X-CSRF-Token` header.
+/// Rejected requests are redirected to an error page explaining that the form has expired.
+/// Requests with a valid bearer token to the routes that accept one instead of the session are
+/// exempt, as is the API, which never uses the session, and unsubscribing, which is authorized by
+/// the link's token.
+pub struct CsrfProtection;
+
+impl<S, B> Transform<S, ServiceRequest> for CsrfProtection
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CsrfProtectionMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CsrfProtectionMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct CsrfProtectionMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfProtectionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            if is_safe_method(req.method())
+                || api::is_api_request(req.request())
+                || unsubscribe::is_unsubscribe_request(req.request())
+                || bearer::has_valid_bearer_token(req.request()).await
+            {
+                return service.call(req).await.map(|res| res.map_into_left_body());
+            }
+
+            let expected = match TypedSession::extract(req.request()).await {
+                Ok(session) => session.get_csrf_token().unwrap_or(None),
+                Err(_) => None,
+            };
+            let submitted = match get_header_token(&req) {
+                Some(token) => Some(token),
+                None => get_form_token(&mut req).await,
+            };
+
+            match (expected, submitted) {
+                (Some(expected), Some(submitted)) if tokens_match(&expected, &submitted) => {
+                    service.call(req).await.map(|res| res.map_into_left_body())
+                }
+                _ => {
+                    warn!(
+                        "🛡️ Rejected {} {} with missing or invalid csrf token",
+                        req.method(),
+                        req.path()
+                    );
+                    let response = utils::redirect(ERROR_PAGE);
+                    Ok(req.into_response(response).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+fn is_safe_method(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn get_header_token(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(HEADER_NAME)
+        .and_then(|h| h.to_str().ok())
+        .map(|h| h.to_owned())
+}
+
+// Reads the token from a urlencoded body, then puts the body back so the handler can still
+// extract its form.
+async fn get_form_token(req: &mut ServiceRequest) -> Option<String> {
+    let is_form = match req.headers().get(header::CONTENT_TYPE) {
+        Some(content_type) => content_type
+            .to_str()
+            .map(|c| c.starts_with("application/x-www-form-urlencoded"))
+            .unwrap_or(false),
+        None => false,
+    };
+    if !is_form {
+        return None;
+    }
+
+    let body = match req.extract::<web::Bytes>().await {
+        Ok(b) => b,
+        Err(e) => {
+            error!("Error reading form body for csrf check: {:?}", e);
+            return None;
+        }
+    };
+
+    let token = url::form_urlencoded::parse(&body)
+        .find(|(key, _)| key == FORM_FIELD)
+        .map(|(_, value)| value.into_owned());
+
+    let (_, mut payload) = actix_http::h1::Payload::create(true);
+    payload.unread_data(body);
+    req.set_payload(payload.into());
+
+    token
+}
+
+// Compares every byte so the time taken doesn't reveal how much of the token was right
+fn tokens_match(expected: &str, submitted: &str) -> bool {
+    if expected.len() != submitted.len() {
+        return false;
+    }
+
+    expected
+        .bytes()
+        .zip(submitted.bytes())
+        .fold(0, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_session::SessionExt;
+    use actix_web::{
+        dev::Service,
+        http::StatusCode,
+        test::{self, TestRequest},
+        App, HttpResponse,
+    };
+    use serde::Deserialize;
+
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    #[derive(Deserialize)]
+    struct Form {
+        body: String,
+    }
+
+    async fn echo(form: web::Form<Form>) -> HttpResponse {
+        HttpResponse::Ok().body(form.into_inner().body)
+    }
+
+    async fn post(req: TestRequest) -> (StatusCode, Option<String>, String) {
+        let app = test::init_service(
+            App::new()
+                .route("/echo", web::post().to(echo))
+                .wrap(CsrfProtection)
+                .wrap_fn(|req, srv| {
+                    req.get_session().insert(FORM_FIELD, TOKEN).unwrap();
+                    srv.call(req)
+                }),
+        )
+        .await;
+
+        let res =
+            test::call_service(&app, req.method(Method::POST).uri("/echo").to_request()).await;
+        let status = res.status();
+        let location = res
+            .headers()
+            .get(header::LOCATION)
+            .map(|l| l.to_str().unwrap().to_owned());
+        let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+
+        (status, location, body)
+    }
+
+    fn form(body: &str) -> TestRequest {
+        TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(body.to_owned())
+    }
+
+    #[actix_web::test]
+    async fn test_rejects_missing_and_wrong_tokens() {
+        for req in [
+            form("body=hi"),
+            form("body=hi&csrf_token=fedcba9876543210"),
+            form("body=hi").insert_header((HEADER_NAME, "fedcba9876543210")),
+            form("body=hi").insert_header((header::AUTHORIZATION, "Bearer abc123")),
+        ] {
+            let (status, location, _) = post(req).await;
+            assert_eq!(status, StatusCode::SEE_OTHER);
+            assert_eq!(location.as_deref(), Some(ERROR_PAGE));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_accepts_form_token_and_keeps_body() {
+        let (status, _, body) = post(form(&format!("body=a+b%26c&csrf_token={}", TOKEN))).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "a b&c");
+    }
+
+    #[actix_web::test]
+    async fn test_accepts_header_token() {
+        let (status, _, body) = post(form("body=hi").insert_header((HEADER_NAME, TOKEN))).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "hi");
+    }
+
+    #[actix_web::test]
+    async fn test_renew_rotates_token() {
+        let req = TestRequest::default().to_http_request();
+        let session = TypedSession::extract(&req).await.unwrap();
+        let token = session.get_or_insert_csrf_token().unwrap();
+        assert_eq!(session.get_or_insert_csrf_token().unwrap(), token);
+
+        session.renew();
+
+        assert_eq!(session.get_csrf_token().unwrap(), None);
+        assert_ne!(session.get_or_insert_csrf_token().unwrap(), token);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match(TOKEN, TOKEN));
+        assert!(!tokens_match(TOKEN, "0123456789abcdeF"));
+        assert!(!tokens_match(TOKEN, "0123"));
+    }
+}
@@ -0,0 +1,36 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use tera::{Context, Tera};
+
+use crate::{
+    entities::EntityStores,
+    routes::user_context::{session_state::TypedSession, user_context},
+};
+
+const PAGE_NAME: &str = "error - expired form";
+const HERO_BG_CLASS: &str = "hero-bg-500";
+
+pub async fn csrf(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let user_context = user_context::build(
+        session,
+        flash_messages,
+        &stores,
+        PAGE_NAME,
+        Some(HERO_BG_CLASS),
+    )
+    .await;
+
+    // TODO: handle errors
+    build_response(&tera, user_context.context)
+}
+
+fn build_response(tera: &Tera, context: Context) -> HttpResponse {
+    // TODO: handle error
+    let rendered = tera.render("403.html", &context).unwrap();
+    HttpResponse::Forbidden().body(rendered)
+}
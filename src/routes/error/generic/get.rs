@@ -7,6 +7,7 @@ use tera::{Context, Tera};
 use crate::{
     entities::EntityStores,
     routes::{
+        api,
         user_context::{session_state::TypedSession, user_context},
         utils,
     },
@@ -35,6 +36,10 @@ pub async fn generic(
 }
 
 pub fn render_generic<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+    if api::is_api_request(res.request()) {
+        return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
+    }
+
     let (request, response) = res.into_parts();
 
     // TODO: use session to get current user for navbar
@@ -1,2 +1,3 @@
+pub mod csrf;
 pub mod generic;
 pub mod not_found;
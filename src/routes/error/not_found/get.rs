@@ -7,9 +7,12 @@ use tera::Tera;
 
 use crate::{
     entities::EntityStores,
-    routes::user_context::{
-        session_state::TypedSession,
-        user_context::{self, UserContext},
+    routes::{
+        api,
+        user_context::{
+            session_state::TypedSession,
+            user_context::{self, UserContext},
+        },
     },
 };
 
@@ -37,6 +40,10 @@ pub async fn not_found(
 }
 
 pub fn render_not_found<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+    if api::is_api_request(res.request()) {
+        return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
+    }
+
     let request = res.into_parts().0;
     let tera = request.app_data::<web::Data<Tera>>().unwrap();
 
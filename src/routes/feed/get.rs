@@ -0,0 +1,353 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+
+use crate::{
+    entities::{
+        comment::{Comment, CommentStore},
+        content::ContentStore,
+        post::{Post, PostStore},
+        user::{User, UserStore},
+        EntityError, EntityStores,
+    },
+    routes::{
+        conditional,
+        models::{self, Viewer},
+        utils,
+    },
+    server::Environment,
+};
+
+const FEED_SIZE: u8 = 25;
+const SITE_TITLE: &str = "effward.dev";
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    #[default]
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    fn template(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "feeds/rss.xml",
+            FeedFormat::Atom => "feeds/atom.xml",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    #[serde(default)]
+    format: FeedFormat,
+}
+
+#[derive(Serialize)]
+struct Feed {
+    title: String,
+    description: String,
+    link: String,
+    self_link: String,
+    updated: DateTime<Utc>,
+    entries: Vec<FeedEntry>,
+}
+
+#[derive(Serialize)]
+struct FeedEntry {
+    id: String,
+    title: String,
+    link: String,
+    author: String,
+    published: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    content: Option<String>,
+}
+
+/// Recent posts across the site.
+pub async fn posts(
+    req: HttpRequest,
+    query: web::Query<FeedQuery>,
+    tera: web::Data<Tera>,
+    stores: web::Data<EntityStores>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let posts = match stores
+        .post_store
+        .get_recent(None, FEED_SIZE, None, false)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+    let entries = match post_entries(&stores, &env, &posts).await {
+        Ok(e) => e,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+
+    let feed = Feed {
+        title: SITE_TITLE.to_owned(),
+        description: "recent posts".to_owned(),
+        link: format!("{}/posts", env.base_url()),
+        self_link: self_link(&env, &req, query.format),
+        updated: last_updated(posts.iter().map(|p| p.updated), epoch()),
+        entries,
+    };
+
+    render_feed(&req, &tera, query.format, feed)
+}
+
+/// Recent posts by one user, looked up by id or name like their page. Empty for a shadowbanned
+/// user, feeds are read by anyone.
+pub async fn user(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FeedQuery>,
+    tera: web::Data<Tera>,
+    stores: web::Data<EntityStores>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let path_user = path.into_inner();
+    let user = match stores.user_store.get_by_public_id(&path_user).await {
+        Ok(u) => u,
+        Err(EntityError::InvalidInput("public_id", _)) => {
+            match stores.user_store.get_by_name(&path_user).await {
+                Ok(u) => u,
+                Err(e) => return utils::redirect_entity_error(e, "user"),
+            }
+        }
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+
+    let posts = match stores
+        .post_store
+        .get_recent_by_author_id(user.id, FEED_SIZE)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+    let entries = match post_entries(&stores, &env, &posts).await {
+        Ok(e) => e,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+
+    let feed = Feed {
+        title: format!("{} - posts by {}", SITE_TITLE, user.name),
+        description: format!("recent posts by {}", user.name),
+        link: format!("{}/user/{}", env.base_url(), user.name),
+        self_link: self_link(&env, &req, query.format),
+        updated: last_updated(posts.iter().map(|p| p.updated), user.created),
+        entries,
+    };
+
+    render_feed(&req, &tera, query.format, feed)
+}
+
+/// Recent comments on a post at any depth, newest first.
+pub async fn comments(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FeedQuery>,
+    tera: web::Data<Tera>,
+    stores: web::Data<EntityStores>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let post = match stores.post_store.get_by_public_id(&path).await {
+        Ok(p) if p.is_removed => {
+            return utils::redirect_entity_error(EntityError::NotFound, "post")
+        }
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+    match stores.user_store.get_by_id(post.author_id).await {
+        Ok(author) if Viewer::anonymous().can_see(&author) => {}
+        Ok(_) => return utils::redirect_entity_error(EntityError::NotFound, "post"),
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    }
+    let comments = match stores
+        .comment_store
+        .get_recent_by_post_id(post.id, FEED_SIZE)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return utils::redirect_entity_error(e, "comment"),
+    };
+    let entries = match comment_entries(&stores, &env, &post, &comments).await {
+        Ok(e) => e,
+        Err(e) => return utils::redirect_entity_error(e, "comment"),
+    };
+
+    let title = plain_text(&post.title);
+    let feed = Feed {
+        title: format!("{} - comments on {}", SITE_TITLE, title),
+        description: format!("recent comments on {}", title),
+        link: post_link(&env, &post),
+        self_link: self_link(&env, &req, query.format),
+        updated: last_updated(comments.iter().map(|c| c.updated), post.created),
+        entries,
+    };
+
+    render_feed(&req, &tera, query.format, feed)
+}
+
+fn render_feed(req: &HttpRequest, tera: &Tera, format: FeedFormat, feed: Feed) -> HttpResponse {
+    let mut context = Context::new();
+    context.insert("feed", &feed);
+
+    // TODO: handle error
+    let rendered = tera.render(format.template(), &context).unwrap();
+
+    conditional::conditional_response(req, format.content_type(), rendered, feed.updated)
+}
+
+async fn post_entries(
+    stores: &EntityStores,
+    env: &Environment,
+    posts: &[Post],
+) -> Result<Vec<FeedEntry>, EntityError> {
+    let mut entries = vec![];
+    for post in posts {
+        let summary = models::translate_post_summary(post, stores, 0).await?;
+
+        // Link posts lead with their link, since readers show the content rather than the page
+        let link_html = summary.link.as_ref().map(|link| {
+            format!(
+                "<p><a href=\"{}\">{}</a></p>",
+                html_escape::encode_double_quoted_attribute(link),
+                html_escape::encode_text(link)
+            )
+        });
+        let content = match (link_html, summary.content) {
+            (Some(link), Some(content)) => Some(link + &content),
+            (link, content) => link.or(content),
+        };
+
+        entries.push(FeedEntry {
+            id: post_link(env, post),
+            title: plain_text(&post.title),
+            link: post_link(env, post),
+            author: summary.author.name,
+            published: post.created,
+            updated: post.updated,
+            content,
+        });
+    }
+
+    Ok(entries)
+}
+
+async fn comment_entries(
+    stores: &EntityStores,
+    env: &Environment,
+    post: &Post,
+    comments: &[Comment],
+) -> Result<Vec<FeedEntry>, EntityError> {
+    let authors: Vec<User> = stores
+        .user_store
+        .get_by_ids(&comments.iter().map(|c| c.author_id).collect::<Vec<_>>())
+        .await?;
+
+    let mut entries = vec![];
+    for comment in comments.iter().filter(|c| !c.is_removed) {
+        let author = match authors.iter().find(|a| a.id == comment.author_id) {
+            Some(a) => a.name.clone(),
+            None => return Err(EntityError::NotFound),
+        };
+        let content = stores.content_store.get_by_id(comment.content_id).await?;
+
+        entries.push(FeedEntry {
+            // The post page doesn't anchor comments, so the id is only unique, not a permalink
+            id: format!("{}#comment-{}", post_link(env, post), comment.public_id),
+            title: format!("{} on {}", author, plain_text(&post.title)),
+            link: post_link(env, post),
+            author,
+            published: comment.created,
+            updated: comment.updated,
+            content: Some(content.body_html),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn post_link(env: &Environment, post: &Post) -> String {
+    format!("{}/post/{}", env.base_url(), post.public_id)
+}
+
+fn self_link(env: &Environment, req: &HttpRequest, format: FeedFormat) -> String {
+    match format {
+        FeedFormat::Rss => format!("{}{}", env.base_url(), req.path()),
+        FeedFormat::Atom => format!("{}{}?format=atom", env.base_url(), req.path()),
+    }
+}
+
+fn last_updated(
+    updated: impl Iterator<Item = DateTime<Utc>>,
+    default: DateTime<Utc>,
+) -> DateTime<Utc> {
+    updated.max().unwrap_or(default)
+}
+
+fn epoch() -> DateTime<Utc> {
+    Utc.timestamp_opt(0, 0).unwrap()
+}
+
+// Titles are stored html escaped, the templates escape them again for xml
+fn plain_text(title: &str) -> String {
+    html_escape::decode_html_entities(title).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_escaping() {
+        let tera = Tera::new("templates/**/*").unwrap();
+        let published = Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap();
+        let feed = || Feed {
+            title: SITE_TITLE.to_owned(),
+            description: "recent posts".to_owned(),
+            link: "https://effward.dev/posts".to_owned(),
+            self_link: "https://effward.dev/feed.xml".to_owned(),
+            updated: published,
+            entries: vec![FeedEntry {
+                id: "https://effward.dev/post/abc".to_owned(),
+                title: plain_text("fish &amp; chips"),
+                link: "https://effward.dev/post/abc".to_owned(),
+                author: "sasquatch".to_owned(),
+                published,
+                updated: published,
+                content: Some("<p>a &amp; b</p>".to_owned()),
+            }],
+        };
+
+        for format in [FeedFormat::Rss, FeedFormat::Atom] {
+            let mut context = Context::new();
+            context.insert("feed", &feed());
+            let rendered = tera.render(format.template(), &context).unwrap();
+
+            assert!(rendered.contains("fish &amp; chips"), "{:?}", format);
+            assert!(
+                rendered.contains("&lt;p&gt;a &amp;amp; b&lt;&#x2F;p&gt;"),
+                "{:?}",
+                format
+            );
+        }
+
+        let mut context = Context::new();
+        context.insert("feed", &feed());
+        let rss = tera.render(FeedFormat::Rss.template(), &context).unwrap();
+        assert!(rss.contains("<pubDate>Thu, 01 Jun 2023 12:00:00 +0000</pubDate>"));
+    }
+}
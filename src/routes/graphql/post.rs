@@ -0,0 +1,25 @@
+use actix_web::{error::JsonPayloadError, web, HttpRequest, HttpResponse};
+use async_graphql::{Response, ServerError};
+
+use crate::graphql::GraphQlSchema;
+
+/// Executes a query, always as JSON with any errors in the response's `errors` list.
+pub async fn graphql(
+    schema: web::Data<GraphQlSchema>,
+    request: web::Json<async_graphql::Request>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(schema.execute(request.into_inner()).await)
+}
+
+/// Used as the route's `JsonConfig` error handler, so a malformed request still gets a GraphQL
+/// shaped error.
+pub fn json_error(error: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let message = error.to_string();
+    let response = Response::from_errors(vec![ServerError::new(message.clone(), None)]);
+
+    actix_web::error::InternalError::from_response(
+        message,
+        HttpResponse::BadRequest().json(response),
+    )
+    .into()
+}
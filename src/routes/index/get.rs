@@ -8,6 +8,7 @@ use crate::routes::user_context::{session_state::TypedSession, user_context};
 use crate::{
     entities::post::{Post, PostStore},
     routes::models::{self, PostSummary},
+    server::Admins,
 };
 
 const POSTS_PER_PAGE: u8 = 2;
@@ -18,13 +19,27 @@ pub async fn index(
     flash_messages: IncomingFlashMessages,
     tera: web::Data<Tera>,
     stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
 ) -> impl Responder {
     debug!("getting user context");
     let mut user_context =
-        user_context::build(session, flash_messages, &stores, "home", None).await;
+        user_context::build(session.clone(), flash_messages, &stores, "home", None).await;
 
     debug!("getting recent posts");
-    let result = stores.post_store.get_recent(None, POSTS_PER_PAGE).await;
+    let viewer = user_context::get_viewer(session, &stores, &admins).await;
+    let result = match stores
+        .post_store
+        .get_recent(
+            None,
+            POSTS_PER_PAGE,
+            viewer.shadowbanned_id(),
+            viewer.sees_shadowbanned(),
+        )
+        .await
+    {
+        Ok(posts) => viewer.visible_posts(&stores, posts).await,
+        Err(e) => Err(e),
+    };
 
     let post_entities = match result {
         Ok(p) => p,
@@ -3,8 +3,12 @@ use actix_web_flash_messages::IncomingFlashMessages;
 use tera::Tera;
 
 use crate::{
-    entities::EntityStores,
-    routes::user_context::{session_state::TypedSession, user_context},
+    entities::{user::MIN_PASSWORD_LENGTH, EntityStores},
+    oauth::OAuthProviders,
+    routes::{
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
 };
 
 const HERO_BG_CLASS: &str = "hero-bg-login";
@@ -13,9 +17,10 @@ pub async fn login(
     session: TypedSession,
     flash_messages: IncomingFlashMessages,
     stores: web::Data<EntityStores>,
+    providers: web::Data<OAuthProviders>,
     tera: web::Data<Tera>,
 ) -> impl Responder {
-    let user_context = user_context::build(
+    let mut user_context = user_context::build(
         session,
         flash_messages,
         &stores,
@@ -23,9 +28,71 @@ pub async fn login(
         Some(HERO_BG_CLASS),
     )
     .await;
+    user_context
+        .context
+        .insert("oauth_providers", &providers.summaries());
 
     // TODO: handle error
     let rendered = tera.render("login.html", &user_context.context).unwrap();
 
     HttpResponse::Ok().body(rendered)
 }
+
+pub async fn two_factor(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    match session.get_pending_login() {
+        Ok(Some(_)) => (),
+        _ => return utils::redirect("/login"),
+    }
+
+    let user_context = user_context::build(
+        session,
+        flash_messages,
+        &stores,
+        "login - two-factor authentication",
+        Some(HERO_BG_CLASS),
+    )
+    .await;
+
+    // TODO: handle error
+    let rendered = tera
+        .render("two_factor.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
+
+pub async fn password_reset(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    match session.get_pending_password_reset() {
+        Ok(Some(_)) => (),
+        _ => return utils::redirect("/login"),
+    }
+
+    let mut user_context = user_context::build(
+        session,
+        flash_messages,
+        &stores,
+        "login - choose a new password",
+        Some(HERO_BG_CLASS),
+    )
+    .await;
+    user_context
+        .context
+        .insert("min_password_length", &MIN_PASSWORD_LENGTH);
+
+    // TODO: handle error
+    let rendered = tera
+        .render("password_reset.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
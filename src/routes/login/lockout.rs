@@ -0,0 +1,299 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{error, warn};
+use tera::{Context, Tera};
+
+use crate::{
+    entities::{
+        email::EmailStore,
+        login_attempt::{LoginAttemptStore, LoginFailures},
+        user::User,
+        EntityStores,
+    },
+    mailer::{EmailMessage, Mailer},
+};
+
+const EMAIL_TEMPLATE: &str = "email/lockout.txt";
+const EMAIL_SUBJECT: &str = "your effward.dev account has been temporarily locked";
+
+/// Failed attempts beyond `free_attempts` must wait an exponentially increasing number of
+/// seconds (capped at `max_backoff_secs`) before trying again, and after `lockout_threshold`
+/// failures within `window_secs` all attempts are refused for `lockout_secs`.
+pub struct LockoutPolicy {
+    pub free_attempts: i64,
+    pub lockout_threshold: i64,
+    pub max_backoff_secs: i64,
+    pub lockout_secs: i64,
+    pub window_secs: i64,
+}
+
+pub const ACCOUNT_POLICY: LockoutPolicy = LockoutPolicy {
+    free_attempts: 3,
+    lockout_threshold: 10,
+    max_backoff_secs: 5 * 60,
+    lockout_secs: 15 * 60,
+    window_secs: 60 * 60,
+};
+
+/// Keyed on the client's address, and skipped when a proxy that isn't trusted hides it.
+pub const IP_POLICY: LockoutPolicy = LockoutPolicy {
+    free_attempts: 10,
+    lockout_threshold: 50,
+    max_backoff_secs: 60,
+    lockout_secs: 60 * 60,
+    window_secs: 60 * 60,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LockoutState {
+    Open,
+    Backoff(Duration),
+    Locked(Duration),
+}
+
+impl LockoutPolicy {
+    pub fn window_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::seconds(self.window_secs)
+    }
+
+    pub fn evaluate(&self, failures: &LoginFailures, now: DateTime<Utc>) -> LockoutState {
+        let last_failure = match failures.last_failure {
+            Some(l) => l,
+            None => return LockoutState::Open,
+        };
+        let elapsed = now - last_failure;
+
+        if failures.count >= self.lockout_threshold {
+            let remaining = Duration::seconds(self.lockout_secs) - elapsed;
+            if remaining > Duration::zero() {
+                return LockoutState::Locked(remaining);
+            }
+            return LockoutState::Open;
+        }
+
+        if failures.count < self.free_attempts {
+            return LockoutState::Open;
+        }
+
+        let exponent = (failures.count - self.free_attempts).min(32) as u32;
+        let backoff_secs = 2_i64.saturating_pow(exponent).min(self.max_backoff_secs);
+        let remaining = Duration::seconds(backoff_secs) - elapsed;
+        if remaining > Duration::zero() {
+            LockoutState::Backoff(remaining)
+        } else {
+            LockoutState::Open
+        }
+    }
+}
+
+impl LockoutState {
+    /// Returns whichever state is more restrictive.
+    pub fn max(self, other: LockoutState) -> LockoutState {
+        match (self, other) {
+            (LockoutState::Locked(a), LockoutState::Locked(b)) => LockoutState::Locked(a.max(b)),
+            (LockoutState::Locked(_), _) => self,
+            (_, LockoutState::Locked(_)) => other,
+            (LockoutState::Backoff(a), LockoutState::Backoff(b)) => LockoutState::Backoff(a.max(b)),
+            (LockoutState::Backoff(_), _) => self,
+            (_, LockoutState::Backoff(_)) => other,
+            (LockoutState::Open, LockoutState::Open) => LockoutState::Open,
+        }
+    }
+}
+
+/// Checks whether a login attempt for the account and/or IP is currently allowed.
+/// Lookup errors are logged and the attempt is allowed, so a DB hiccup can't lock everyone out.
+pub async fn check(stores: &EntityStores, user_id: Option<u64>, ip: Option<&str>) -> LockoutState {
+    let now = Utc::now();
+    let mut state = LockoutState::Open;
+
+    if let Some(user_id) = user_id {
+        match stores
+            .login_attempt_store
+            .get_failures_by_user_id(user_id, ACCOUNT_POLICY.window_start(now))
+            .await
+        {
+            Ok(failures) => state = state.max(ACCOUNT_POLICY.evaluate(&failures, now)),
+            Err(e) => error!("Error getting login failures for user {}: {:?}", user_id, e),
+        }
+    }
+
+    if let Some(ip) = ip {
+        match stores
+            .login_attempt_store
+            .get_failures_by_ip(ip, IP_POLICY.window_start(now))
+            .await
+        {
+            Ok(failures) => state = state.max(IP_POLICY.evaluate(&failures, now)),
+            Err(e) => error!("Error getting login failures for ip {}: {:?}", ip, e),
+        }
+    }
+
+    state
+}
+
+pub async fn record_success(stores: &EntityStores, user_id: Option<u64>, ip: Option<&str>) {
+    if let Err(e) = stores.login_attempt_store.insert(user_id, ip, true).await {
+        error!("Error recording successful login attempt: {:?}", e);
+    }
+}
+
+/// Records a failed attempt, notifying the account owner if it pushed the account into lockout.
+pub async fn record_failure(
+    stores: &EntityStores,
+    mailer: &dyn Mailer,
+    tera: &Tera,
+    user: Option<&User>,
+    ip: Option<&str>,
+) {
+    let user_id = user.map(|u| u.id);
+    if let Err(e) = stores.login_attempt_store.insert(user_id, ip, false).await {
+        error!("Error recording failed login attempt: {:?}", e);
+        return;
+    }
+
+    let user = match user {
+        Some(u) => u,
+        None => return,
+    };
+
+    let now = Utc::now();
+    let failures = match stores
+        .login_attempt_store
+        .get_failures_by_user_id(user.id, ACCOUNT_POLICY.window_start(now))
+        .await
+    {
+        Ok(f) => f,
+        Err(e) => {
+            error!("Error getting login failures for user {}: {:?}", user.id, e);
+            return;
+        }
+    };
+
+    if failures.count == ACCOUNT_POLICY.lockout_threshold {
+        warn!(
+            "🔒 Locking account {} after {} failures",
+            user.id, failures.count
+        );
+        send_lockout_email(stores, mailer, tera, user, &failures, ip).await;
+    }
+}
+
+async fn send_lockout_email(
+    stores: &EntityStores,
+    mailer: &dyn Mailer,
+    tera: &Tera,
+    user: &User,
+    failures: &LoginFailures,
+    ip: Option<&str>,
+) {
+    let email = match stores.email_store.get_by_id(user.email_id).await {
+        Ok(e) => e,
+        Err(e) => {
+            error!("Error getting email for lockout notification: {:?}", e);
+            return;
+        }
+    };
+
+    let mut context = Context::new();
+    context.insert("name", &user.name);
+    context.insert("attempts", &failures.count);
+    context.insert("ip", &ip.unwrap_or("unknown"));
+    context.insert("lockout_minutes", &(ACCOUNT_POLICY.lockout_secs / 60));
+
+    let message = match EmailMessage::render(
+        tera,
+        EMAIL_TEMPLATE,
+        &context,
+        &email.address,
+        EMAIL_SUBJECT,
+    ) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("Error rendering lockout email: {:?}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = mailer.send(&message).await {
+        error!("Error sending lockout email: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failures(count: i64, secs_ago: i64, now: DateTime<Utc>) -> LoginFailures {
+        LoginFailures {
+            count,
+            last_failure: Some(now - Duration::seconds(secs_ago)),
+        }
+    }
+
+    #[test]
+    fn test_open_without_failures() {
+        let now = Utc::now();
+        let no_failures = LoginFailures {
+            count: 0,
+            last_failure: None,
+        };
+
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&no_failures, now),
+            LockoutState::Open
+        );
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(2, 0, now), now),
+            LockoutState::Open
+        );
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let now = Utc::now();
+
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(3, 0, now), now),
+            LockoutState::Backoff(Duration::seconds(1))
+        );
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(6, 0, now), now),
+            LockoutState::Backoff(Duration::seconds(8))
+        );
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(6, 3, now), now),
+            LockoutState::Backoff(Duration::seconds(5))
+        );
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(6, 8, now), now),
+            LockoutState::Open
+        );
+    }
+
+    #[test]
+    fn test_lockout_after_threshold() {
+        let now = Utc::now();
+
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(10, 60, now), now),
+            LockoutState::Locked(Duration::seconds(14 * 60))
+        );
+        assert_eq!(
+            ACCOUNT_POLICY.evaluate(&failures(10, 15 * 60, now), now),
+            LockoutState::Open
+        );
+    }
+
+    #[test]
+    fn test_max_prefers_most_restrictive() {
+        let backoff = LockoutState::Backoff(Duration::seconds(30));
+        let locked = LockoutState::Locked(Duration::seconds(10));
+
+        assert_eq!(LockoutState::Open.max(backoff), backoff);
+        assert_eq!(backoff.max(locked), locked);
+        assert_eq!(
+            backoff.max(LockoutState::Backoff(Duration::seconds(60))),
+            LockoutState::Backoff(Duration::seconds(60))
+        );
+    }
+}
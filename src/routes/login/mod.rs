@@ -1,2 +1,4 @@
+mod lockout;
+
 pub mod get;
 pub mod post;
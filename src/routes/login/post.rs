@@ -1,26 +1,201 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use actix_web_flash_messages::FlashMessage;
+use chrono::Duration;
 use log::{error, info};
-use secrecy::Secret;
+use secrecy::{ExposeSecret, Secret};
 use serde::Deserialize;
+use tera::Tera;
 
 use crate::{
-    entities::{user::UserStore, EntityError, EntityStores},
-    routes::{models::UserModel, user_context::session_state::TypedSession, utils},
+    entities::{
+        two_factor::TwoFactorStore,
+        user::{User, UserStore, MAX_PASSWORD_LENGTH, MIN_PASSWORD_LENGTH},
+        EntityError, EntityStores,
+    },
+    mailer::Mailer,
+    routes::{
+        models::UserModel,
+        rate_limited::{Login, RateLimited},
+        two_factor,
+        user_context::session_state::{PendingLogin, TypedSession},
+        utils,
+    },
 };
 
+use super::lockout::{self, LockoutState};
+
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
     username: String,
     password: Secret<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorRequest {
+    code: Secret<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetRequest {
+    password: Secret<String>,
+}
+
 pub async fn process_login(
+    _rate_limited: RateLimited<Login>,
+    req: HttpRequest,
     session: TypedSession,
     data: web::Form<LoginRequest>,
     stores: web::Data<EntityStores>,
+    mailer: web::Data<dyn Mailer>,
+    tera: web::Data<Tera>,
 ) -> impl Responder {
-    do_login_and_redirect(session, &stores, &data.username, &data.password).await
+    let ip = utils::get_lockout_ip(&req);
+    let user = stores.user_store.get_by_name(&data.username).await.ok();
+    let user_id = user.as_ref().map(|u| u.id);
+
+    match lockout::check(&stores, user_id, ip.as_deref()).await {
+        LockoutState::Open => (),
+        LockoutState::Backoff(retry_after) => {
+            return redirect_error_code(LoginErrorCode::TooManyAttempts(retry_after))
+        }
+        LockoutState::Locked(retry_after) => {
+            return redirect_error_code(LoginErrorCode::AccountLocked(retry_after))
+        }
+    }
+
+    let result = stores
+        .user_store
+        .get_by_name_password(&data.username, &data.password)
+        .await;
+
+    match &result {
+        // With 2FA enabled, success is only recorded once the second factor has been checked
+        Ok(user) => {
+            if let Ok(false) = two_factor::is_enabled(&stores, user.id).await {
+                lockout::record_success(&stores, user_id, ip.as_deref()).await
+            }
+        }
+        Err(EntityError::InvalidInput(_, _)) | Err(EntityError::NotFound) => {
+            lockout::record_failure(
+                &stores,
+                mailer.as_ref(),
+                &tera,
+                user.as_ref(),
+                ip.as_deref(),
+            )
+            .await
+        }
+        Err(_) => (),
+    }
+
+    login_and_redirect(session, &stores, result).await
+}
+
+pub async fn process_two_factor(
+    _rate_limited: RateLimited<Login>,
+    req: HttpRequest,
+    session: TypedSession,
+    data: web::Form<TwoFactorRequest>,
+    stores: web::Data<EntityStores>,
+    mailer: web::Data<dyn Mailer>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let pending = match session.get_pending_login() {
+        Ok(Some(p)) => p,
+        _ => return redirect_error_code(LoginErrorCode::TwoFactorExpired),
+    };
+    let user = match stores.user_store.get_by_public_id(&pending.user_id).await {
+        Ok(u) => u,
+        Err(e) => return login_error_redirect(e),
+    };
+
+    let ip = utils::get_lockout_ip(&req);
+    match lockout::check(&stores, Some(user.id), ip.as_deref()).await {
+        LockoutState::Open => (),
+        LockoutState::Backoff(retry_after) => {
+            return redirect_two_factor_error_code(LoginErrorCode::TooManyAttempts(retry_after))
+        }
+        LockoutState::Locked(retry_after) => {
+            return redirect_error_code(LoginErrorCode::AccountLocked(retry_after))
+        }
+    }
+
+    let two_factor = match stores.two_factor_store.get_by_user_id(user.id).await {
+        Ok(t) if t.is_enabled => t,
+        // 2FA was reset since the password was entered, so start over
+        Ok(_) | Err(EntityError::NotFound) => {
+            session.remove_pending_login();
+            return redirect_error_code(LoginErrorCode::TwoFactorExpired);
+        }
+        Err(e) => return login_error_redirect(e),
+    };
+
+    match two_factor::verify_code(&stores, &two_factor, data.code.expose_secret()).await {
+        Ok(true) => {
+            lockout::record_success(&stores, Some(user.id), ip.as_deref()).await;
+            session.remove_pending_login();
+            finish_login(session, user)
+        }
+        Ok(false) => {
+            lockout::record_failure(&stores, mailer.as_ref(), &tera, Some(&user), ip.as_deref())
+                .await;
+            redirect_two_factor_error_code(LoginErrorCode::InvalidTwoFactorCode)
+        }
+        Err(e) => login_error_redirect(e),
+    }
+}
+
+/// Sets the new password of a user an admin forced to reset it, then finishes logging them in.
+pub async fn process_password_reset(
+    session: TypedSession,
+    data: web::Form<PasswordResetRequest>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let pending = match session.get_pending_password_reset() {
+        Ok(Some(p)) => p,
+        _ => return redirect_error_code(LoginErrorCode::PasswordResetExpired),
+    };
+    let user = match stores.user_store.get_by_public_id(&pending.user_id).await {
+        Ok(u) => u,
+        Err(e) => return login_error_redirect(e),
+    };
+    if user.is_banned {
+        session.remove_pending_password_reset();
+        return redirect_error_code(LoginErrorCode::Banned);
+    }
+
+    // The old password may be why the reset was forced, so it can't be chosen again
+    if stores
+        .user_store
+        .get_by_name_password(&user.name, &data.password)
+        .await
+        .is_ok()
+    {
+        return utils::error_redirect(
+            "/login/password",
+            "choose a password you haven't used before",
+        );
+    }
+
+    match stores
+        .user_store
+        .set_password(user.id, &data.password)
+        .await
+    {
+        Ok(user) => {
+            info!("User {} chose a new password", user.name);
+            session.remove_pending_password_reset();
+            complete_login(session, pending.user_id)
+        }
+        Err(EntityError::InvalidInput("password", _)) => utils::error_redirect(
+            "/login/password",
+            &format!(
+                "invalid password, min length {} characters, max length {} characters",
+                MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH
+            ),
+        ),
+        Err(e) => login_error_redirect(e),
+    }
 }
 
 pub async fn do_login_and_redirect(
@@ -34,32 +209,98 @@ pub async fn do_login_and_redirect(
         .get_by_name_password(username, password)
         .await;
 
+    login_and_redirect(session, stores, result).await
+}
+
+/// Logs in a user who has already been authenticated another way, e.g. by an external identity
+/// provider. Their second factor is still required if they have 2FA enabled.
+pub async fn login_user_and_redirect(
+    session: TypedSession,
+    stores: &EntityStores,
+    user: User,
+) -> HttpResponse {
+    login_and_redirect(session, stores, Ok(user)).await
+}
+
+async fn login_and_redirect(
+    session: TypedSession,
+    stores: &EntityStores,
+    result: Result<User, EntityError>,
+) -> HttpResponse {
     match result {
+        Ok(user) if user.is_banned => redirect_error_code(LoginErrorCode::Banned),
         Ok(user) => {
-            let user_model = UserModel::from(user);
+            let is_two_factor_enabled = match two_factor::is_enabled(stores, user.id).await {
+                Ok(e) => e,
+                Err(entity_error) => return login_error_redirect(entity_error),
+            };
 
             session.renew();
-            match session.insert_user_id(user_model.id) {
-                Ok(_) => {
-                    info!("Successfully set user session");
-                    FlashMessage::success("successfully logged in").send();
-                    utils::redirect("/")
-                }
-                Err(e) => {
-                    error!("Error inserting into session: {:?}", e);
-                    login_error_redirect(EntityError::Internal(e.to_string()))
-                }
+            if is_two_factor_enabled {
+                return begin_two_factor(session, UserModel::from(user).id);
             }
+
+            finish_login(session, user)
         }
         Err(entity_error) => login_error_redirect(entity_error),
     }
 }
 
+fn begin_two_factor(session: TypedSession, user_id: String) -> HttpResponse {
+    match session.insert_pending_login(&PendingLogin::new(user_id)) {
+        Ok(_) => utils::redirect("/login/2fa"),
+        Err(e) => {
+            error!("Error inserting into session: {:?}", e);
+            login_error_redirect(EntityError::Internal(e.to_string()))
+        }
+    }
+}
+
+/// Logs the user in, unless an admin has made them choose a new password first.
+fn finish_login(session: TypedSession, user: User) -> HttpResponse {
+    let must_reset_password = user.must_reset_password;
+    let user_id = UserModel::from(user).id;
+    if !must_reset_password {
+        return complete_login(session, user_id);
+    }
+
+    match session.insert_pending_password_reset(&PendingLogin::new(user_id)) {
+        Ok(_) => utils::warning_redirect(
+            "/login/password",
+            "an admin has asked you to choose a new password",
+        ),
+        Err(e) => {
+            error!("Error inserting into session: {:?}", e);
+            login_error_redirect(EntityError::Internal(e.to_string()))
+        }
+    }
+}
+
+fn complete_login(session: TypedSession, user_id: String) -> HttpResponse {
+    match session.insert_user_id(user_id) {
+        Ok(_) => {
+            info!("Successfully set user session");
+            FlashMessage::success("successfully logged in").send();
+            utils::redirect("/")
+        }
+        Err(e) => {
+            error!("Error inserting into session: {:?}", e);
+            login_error_redirect(EntityError::Internal(e.to_string()))
+        }
+    }
+}
+
 #[derive(Debug)]
 enum LoginErrorCode {
     InvalidUsername,
     InvalidPassword,
     MalformedPassword,
+    TooManyAttempts(Duration),
+    AccountLocked(Duration),
+    TwoFactorExpired,
+    InvalidTwoFactorCode,
+    PasswordResetExpired,
+    Banned,
     Unknown,
 }
 
@@ -73,16 +314,38 @@ fn login_error_redirect(entity_error: EntityError) -> HttpResponse {
         EntityError::CachingError(_) => return utils::redirect_entity_error(entity_error, "user"),
     };
 
+    redirect_error_code(error_code)
+}
+
+fn redirect_error_code(error_code: LoginErrorCode) -> HttpResponse {
+    let error_message = get_error_message(error_code);
+
+    utils::error_redirect("/login", &error_message)
+}
+
+fn redirect_two_factor_error_code(error_code: LoginErrorCode) -> HttpResponse {
     let error_message = get_error_message(error_code);
 
-    utils::error_redirect("/login", error_message)
+    utils::error_redirect("/login/2fa", &error_message)
 }
 
-fn get_error_message(error_code: LoginErrorCode) -> &'static str {
+fn get_error_message(error_code: LoginErrorCode) -> String {
     match error_code {
-        LoginErrorCode::InvalidUsername => "incorrect username and/or password",
-        LoginErrorCode::InvalidPassword => "incorrect password",
-        LoginErrorCode::MalformedPassword => "password corrupted, please reset password below",
-        LoginErrorCode::Unknown => "an error has ocurred, please try again in a few minutes and/or contact the site administrator",
+        LoginErrorCode::InvalidUsername => "incorrect username and/or password".to_owned(),
+        LoginErrorCode::InvalidPassword => "incorrect password".to_owned(),
+        LoginErrorCode::MalformedPassword => "password corrupted, please reset password below".to_owned(),
+        LoginErrorCode::TooManyAttempts(retry_after) => format!(
+            "too many failed login attempts, try again in {}",
+            utils::readable_wait(retry_after.num_seconds() as u64)
+        ),
+        LoginErrorCode::AccountLocked(retry_after) => format!(
+            "too many failed login attempts, this account is temporarily locked, try again in {}",
+            utils::readable_wait(retry_after.num_seconds() as u64)
+        ),
+        LoginErrorCode::TwoFactorExpired => "your login has expired, please log in again".to_owned(),
+        LoginErrorCode::InvalidTwoFactorCode => "incorrect authentication or recovery code".to_owned(),
+        LoginErrorCode::PasswordResetExpired => "choosing a new password took too long, please log in again".to_owned(),
+        LoginErrorCode::Banned => "this account has been banned".to_owned(),
+        LoginErrorCode::Unknown => "an error has ocurred, please try again in a few minutes and/or contact the site administrator".to_owned(),
     }
 }
@@ -1,15 +1,35 @@
-mod models;
+pub(crate) mod models;
 mod user_context;
 mod utils;
 
+pub mod admin;
+pub mod api;
+pub mod bearer;
 pub mod comment;
+pub mod conditional;
+pub mod csrf;
 pub mod error;
+pub mod feed;
+pub mod graphql;
 pub mod health;
 pub mod index;
 pub mod login;
 pub mod logout;
+pub mod moderation_log;
+pub mod notifications;
+pub mod oauth;
+pub mod permissions;
 pub mod post;
 pub mod posts;
+pub mod rate_limited;
+pub mod reports;
+pub mod robots;
+pub mod settings;
 pub mod signup;
+pub mod sitemap;
 pub mod submit;
+pub mod two_factor;
+pub mod unsubscribe;
 pub mod user;
+pub mod verify;
+pub mod webhooks;
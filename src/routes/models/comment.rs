@@ -1,6 +1,7 @@
 use async_recursion::async_recursion;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::entities::{
     comment::{Comment, CommentStore},
@@ -9,24 +10,30 @@ use crate::entities::{
     EntityError, EntityStores,
 };
 
-use super::{utils, UserModel};
+use super::{utils, UserModel, Viewer};
 
 pub const MAX_CHILD_COMMENTS: u8 = 8;
 const MAX_DEPTH: usize = 8;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CommentModel {
     pub id: String,
     pub author: UserModel,
     pub created: DateTime<Utc>,
     pub created_pretty: String,
+    /// Empty once a moderator has removed the comment.
     pub content: String,
+    pub is_removed: bool,
+    #[schema(no_recursion)]
     pub children: Vec<CommentModel>,
 }
 
+/// Replies by authors the viewer can't see are left out, checking `comment` itself is up to the
+/// caller.
 #[async_recursion]
 pub async fn translate_comment(
     stores: &EntityStores,
+    viewer: &Viewer,
     comment: &Comment,
     depth: usize,
 ) -> Result<CommentModel, EntityError> {
@@ -40,19 +47,29 @@ pub async fn translate_comment(
     let mut children: Vec<CommentModel> = vec![];
 
     if depth < MAX_DEPTH {
-        for child_entity in children_entities {
-            let child_comment = translate_comment(stores, &child_entity, depth + 1).await?;
+        for child_entity in viewer.visible_comments(stores, children_entities).await? {
+            let child_comment = translate_comment(stores, viewer, &child_entity, depth + 1).await?;
             children.push(child_comment);
         }
     }
 
-    let content = stores.content_store.get_by_id(comment.content_id).await?;
+    let content = match comment.is_removed {
+        true => String::new(),
+        false => {
+            stores
+                .content_store
+                .get_by_id(comment.content_id)
+                .await?
+                .body_html
+        }
+    };
     Ok(CommentModel {
         id: comment.public_id.clone(),
         author,
         created: comment.created,
         created_pretty: utils::get_readable_duration(comment.created),
-        content: content.body_html,
+        content,
+        is_removed: comment.is_removed,
         children,
     })
 }
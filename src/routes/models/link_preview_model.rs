@@ -0,0 +1,23 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::entities::link_preview::LinkPreview;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LinkPreviewModel {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+impl From<LinkPreview> for LinkPreviewModel {
+    fn from(link_preview: LinkPreview) -> Self {
+        Self {
+            title: link_preview.title,
+            description: link_preview.description,
+            image_url: link_preview.image_url,
+            site_name: link_preview.site_name,
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{PostSummary, UserModel};
+
+const SITE_NAME: &str = "effward.dev";
+const MAX_EXCERPT_LEN: usize = 200;
+
+/// OpenGraph, Twitter card and JSON-LD metadata for a page, so links shared in chat apps and
+/// found by search engines show more than a bare url.
+#[derive(Debug, Serialize)]
+pub struct PageMetadata {
+    pub site_name: &'static str,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    pub og_type: &'static str,
+    pub published: Option<DateTime<Utc>>,
+    pub author: Option<String>,
+    // Already escaped for a script element, so the template can mark it safe
+    pub json_ld: String,
+}
+
+impl PageMetadata {
+    pub fn for_post(summary: &PostSummary, markdown: Option<&str>, base_url: &str) -> Self {
+        let title = plain_text(&summary.title);
+        let url = format!("{}/post/{}", base_url, summary.id);
+        let author_url = format!("{}/user/{}", base_url, summary.author.name);
+        let description = match (markdown.map(excerpt), &summary.link) {
+            (Some(excerpt), _) if !excerpt.is_empty() => excerpt,
+            (_, Some(link)) => link.clone(),
+            _ => format!("posted by {}", summary.author.name),
+        };
+
+        let mut json_ld = json!({
+            "@context": "https://schema.org",
+            "@type": "DiscussionForumPosting",
+            "headline": title,
+            "text": description,
+            "url": url,
+            "datePublished": summary.created,
+            "author": {
+                "@type": "Person",
+                "name": summary.author.name,
+                "url": author_url,
+            },
+            "interactionStatistic": {
+                "@type": "InteractionCounter",
+                "interactionType": "https://schema.org/CommentAction",
+                "userInteractionCount": summary.comment_count,
+            },
+        });
+        if let Some(link) = &summary.link {
+            json_ld["sharedContent"] = json!({ "@type": "WebPage", "url": link });
+        }
+
+        Self {
+            site_name: SITE_NAME,
+            title,
+            description,
+            url,
+            og_type: "article",
+            published: Some(summary.created),
+            author: Some(summary.author.name.clone()),
+            json_ld: script_json(&json_ld),
+        }
+    }
+
+    pub fn for_user(user: &UserModel, base_url: &str) -> Self {
+        let url = format!("{}/user/{}", base_url, user.name);
+        let json_ld = json!({
+            "@context": "https://schema.org",
+            "@type": "ProfilePage",
+            "url": url,
+            "dateCreated": user.created,
+            "mainEntity": {
+                "@type": "Person",
+                "name": user.name,
+                "identifier": user.id,
+            },
+        });
+
+        Self {
+            site_name: SITE_NAME,
+            title: user.name.clone(),
+            description: format!("posts and comments by {} on {}", user.name, SITE_NAME),
+            url,
+            og_type: "profile",
+            published: None,
+            author: None,
+            json_ld: script_json(&json_ld),
+        }
+    }
+}
+
+/// The text of some markdown without its formatting, cut at a word boundary.
+pub fn excerpt(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            // Blocks are separated, so a heading isn't run into the paragraph after it
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::Rule
+            | Event::End(
+                Tag::Paragraph
+                | Tag::Heading(..)
+                | Tag::BlockQuote
+                | Tag::CodeBlock(_)
+                | Tag::Item
+                | Tag::TableCell,
+            ) => text.push(' '),
+            // Raw html is left out rather than shown as markup
+            _ => {}
+        }
+    }
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    let mut excerpt = String::new();
+    for word in words.iter() {
+        if excerpt.chars().count() + word.chars().count() + 1 > MAX_EXCERPT_LEN {
+            excerpt.push('…');
+            return excerpt;
+        }
+        if !excerpt.is_empty() {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(word);
+    }
+
+    excerpt
+}
+
+// Titles are stored html escaped, the templates escape them again for attributes
+fn plain_text(title: &str) -> String {
+    html_escape::decode_html_entities(title).into_owned()
+}
+
+// Escapes `<` so user text can't close the script element, JSON parsers read it back unchanged
+fn script_json(value: &Value) -> String {
+    value.to_string().replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_excerpt() {
+        let markdown = "# Hello\n\nSome *markdown* with `code`\nand a [link](https://example.com).\n\n- one\n- two";
+        assert_eq!(
+            excerpt(markdown),
+            "Hello Some markdown with code and a link. one two"
+        );
+
+        let long = "word ".repeat(100);
+        let excerpt = excerpt(&long);
+        assert!(excerpt.chars().count() <= MAX_EXCERPT_LEN + 1);
+        assert!(excerpt.ends_with("word…"));
+    }
+
+    #[test]
+    fn test_post_json_ld() {
+        let created = Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap();
+        let summary = PostSummary {
+            id: "abc".to_owned(),
+            author: UserModel {
+                id: "def".to_owned(),
+                name: "sasquatch".to_owned(),
+                created,
+                created_pretty: "".to_owned(),
+            },
+            title: "fish &amp; chips &lt;&#x2F;script&gt;".to_owned(),
+            created,
+            created_pretty: "".to_owned(),
+            link: None,
+            preview: None,
+            content: None,
+            comment_count: 3,
+            is_locked: false,
+            is_removed: false,
+        };
+
+        let metadata =
+            PageMetadata::for_post(&summary, Some("Some <em>text</em>"), "https://effward.dev");
+        assert_eq!(metadata.title, "fish & chips </script>");
+        assert_eq!(metadata.description, "Some text");
+        assert!(!metadata.json_ld.contains('<'));
+
+        let json_ld: Value = serde_json::from_str(&metadata.json_ld).unwrap();
+        assert_eq!(json_ld["@type"], "DiscussionForumPosting");
+        assert_eq!(json_ld["headline"], "fish & chips </script>");
+        assert_eq!(json_ld["url"], "https://effward.dev/post/abc");
+        assert_eq!(json_ld["interactionStatistic"]["userInteractionCount"], 3);
+    }
+}
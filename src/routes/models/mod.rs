@@ -1,11 +1,25 @@
 mod comment;
+mod link_preview_model;
+mod metadata;
+mod moderation_entry_model;
+mod notification_model;
 mod post_model;
 mod post_summary;
 mod user_model;
 mod utils;
+mod viewer;
 
+pub use comment::translate_comment;
 pub use comment::CommentModel;
+pub use link_preview_model::LinkPreviewModel;
+pub use metadata::PageMetadata;
+pub use moderation_entry_model::translate_moderation_entry;
+pub use moderation_entry_model::ModerationEntryModel;
+pub use notification_model::translate_notification;
+pub use notification_model::NotificationModel;
 pub use post_model::translate_post;
+pub use post_model::PostModel;
 pub use post_summary::translate_post_summary;
 pub use post_summary::PostSummary;
 pub use user_model::UserModel;
+pub use viewer::Viewer;
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::entities::{
+    comment::CommentStore,
+    moderation_log::{ModerationAction, ModerationEntry},
+    post::PostStore,
+    user::UserStore,
+    EntityError, EntityStores,
+};
+
+use super::{utils, UserModel};
+
+#[derive(Serialize)]
+pub struct ModerationEntryModel {
+    pub action: ModerationAction,
+    pub actor: UserModel,
+    pub user: Option<UserModel>,
+    pub post_id: Option<String>,
+    pub post_title: Option<String>,
+    pub comment_id: Option<String>,
+    pub reason: Option<String>,
+    pub details: Option<String>,
+    pub created: DateTime<Utc>,
+    pub created_pretty: String,
+}
+
+pub async fn translate_moderation_entry(
+    stores: &EntityStores,
+    entry: &ModerationEntry,
+) -> Result<ModerationEntryModel, EntityError> {
+    let actor = UserModel::from(stores.user_store.get_by_id(entry.actor_id).await?);
+    let user = match entry.target_user_id {
+        Some(id) => Some(UserModel::from(stores.user_store.get_by_id(id).await?)),
+        None => None,
+    };
+    let post = match entry.post_id {
+        Some(id) => Some(stores.post_store.get_by_id(id).await?),
+        None => None,
+    };
+    let comment_id = match entry.comment_id {
+        Some(id) => Some(stores.comment_store.get_by_id(id).await?.public_id),
+        None => None,
+    };
+
+    Ok(ModerationEntryModel {
+        action: entry.action,
+        actor,
+        user,
+        post_id: post.as_ref().map(|p| p.public_id.clone()),
+        post_title: post.map(|p| p.title),
+        comment_id,
+        reason: entry.reason.clone(),
+        details: entry.details.clone(),
+        created: entry.created,
+        created_pretty: utils::get_readable_duration(entry.created),
+    })
+}
@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+use crate::entities::{
+    comment::CommentStore, content::ContentStore, notification::Notification, post::PostStore,
+    user::UserStore, EntityError, EntityStores,
+};
+
+use super::{metadata, utils, UserModel};
+
+#[derive(Serialize)]
+pub struct NotificationModel {
+    pub id: String,
+    pub is_read: bool,
+    pub created_pretty: String,
+    pub author: UserModel,
+    pub post_id: String,
+    pub post_title: String,
+    pub is_reply_to_comment: bool,
+    pub excerpt: String,
+}
+
+pub async fn translate_notification(
+    stores: &EntityStores,
+    notification: &Notification,
+) -> Result<NotificationModel, EntityError> {
+    let comment = stores
+        .comment_store
+        .get_by_id(notification.comment_id)
+        .await?;
+    let author = UserModel::from(stores.user_store.get_by_id(comment.author_id).await?);
+    let post = stores.post_store.get_by_id(comment.post_id).await?;
+    let content = stores.content_store.get_by_id(comment.content_id).await?;
+
+    Ok(NotificationModel {
+        id: notification.public_id.clone(),
+        is_read: notification.is_read,
+        created_pretty: utils::get_readable_duration(notification.created),
+        author,
+        post_id: post.public_id,
+        post_title: post.title,
+        is_reply_to_comment: comment.parent_id.is_some(),
+        excerpt: metadata::excerpt(&content.body),
+    })
+}
@@ -1,19 +1,28 @@
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::entities::comment::CommentStore;
 use crate::entities::{post::Post, EntityError, EntityStores};
 
-use super::{comment::translate_comment, translate_post_summary, CommentModel, PostSummary};
+use super::{
+    comment::translate_comment, translate_post_summary, CommentModel, PostSummary, Viewer,
+};
 
 pub const MAX_TOP_LEVEL_COMMENTS: u8 = 50;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PostModel {
     pub summary: PostSummary,
     pub comments: Vec<CommentModel>,
 }
 
-pub async fn translate_post(post: &Post, stores: &EntityStores) -> Result<PostModel, EntityError> {
+/// Comments by authors the viewer can't see are left out, checking the post itself is up to the
+/// caller.
+pub async fn translate_post(
+    post: &Post,
+    stores: &EntityStores,
+    viewer: &Viewer,
+) -> Result<PostModel, EntityError> {
     let summary = translate_post_summary(post, stores, 0).await?;
 
     let comment_entities = stores
@@ -22,8 +31,8 @@ pub async fn translate_post(post: &Post, stores: &EntityStores) -> Result<PostMo
         .await?;
 
     let mut comments: Vec<CommentModel> = vec![];
-    for comment_entity in comment_entities {
-        let comment = translate_comment(stores, &comment_entity, 0).await?;
+    for comment_entity in viewer.visible_comments(stores, comment_entities).await? {
+        let comment = translate_comment(stores, viewer, &comment_entity, 0).await?;
         comments.push(comment);
     }
 
@@ -1,15 +1,16 @@
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use substring::Substring;
+use utoipa::ToSchema;
 
 use crate::entities::{
-    comment::CommentStore, content::ContentStore, post::Post, user::UserStore, EntityError,
-    EntityStores,
+    comment::CommentStore, content::ContentStore, link_preview::LinkPreviewStore, post::Post,
+    user::UserStore, EntityError, EntityStores,
 };
 
-use super::{utils, UserModel};
+use super::{utils, LinkPreviewModel, UserModel};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PostSummary {
     pub id: String,
     pub author: UserModel,
@@ -17,8 +18,14 @@ pub struct PostSummary {
     pub created: DateTime<Utc>,
     pub created_pretty: String,
     pub link: Option<String>,
+    /// Fetched from the link in the background, so missing for a while after posting.
+    pub preview: Option<LinkPreviewModel>,
     pub content: Option<String>,
     pub comment_count: i64,
+    /// Locked posts don't accept new comments.
+    pub is_locked: bool,
+    /// Only moderators ever see removed posts.
+    pub is_removed: bool,
 }
 
 pub async fn translate_post_summary(
@@ -42,6 +49,15 @@ pub async fn translate_post_summary(
         None => None,
     };
 
+    let preview = match post.link {
+        Some(_) => match stores.link_preview_store.get_by_post_id(post.id).await {
+            Ok(preview) => Some(LinkPreviewModel::from(preview)),
+            Err(EntityError::NotFound) => None,
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+
     let comment_count = stores.comment_store.get_count_by_post_id(&post.id).await?;
 
     Ok(PostSummary {
@@ -51,7 +67,10 @@ pub async fn translate_post_summary(
         created: post.created,
         created_pretty: utils::get_readable_duration(post.created),
         link: post.link.to_owned(),
+        preview,
         content,
         comment_count,
+        is_locked: post.is_locked,
+        is_removed: post.is_removed,
     })
 }
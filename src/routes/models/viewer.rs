@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+
+use log::error;
+
+use crate::{
+    entities::{
+        block::BlockStore,
+        comment::Comment,
+        post::Post,
+        user::{Permission, User, UserStore},
+        EntityError, EntityStores,
+    },
+    routes::permissions,
+    server::Admins,
+};
+
+/// Who's reading, so what they can't or don't want to see is left out. Shadowbanned authors'
+/// posts and comments are only shown to themselves and moderators, and no one sees anything from
+/// users they've blocked.
+#[derive(Clone, Debug, Default)]
+pub struct Viewer {
+    user_id: Option<u64>,
+    is_shadowbanned: bool,
+    can_moderate: bool,
+    blocked_ids: HashSet<u64>,
+}
+
+impl Viewer {
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    /// Failing to get the user's blocks is only logged, they see everyone instead.
+    pub async fn load(stores: &EntityStores, user: Option<&User>, admins: &Admins) -> Self {
+        let user = match user {
+            Some(u) => u,
+            None => return Self::anonymous(),
+        };
+        let blocked_ids = match stores.block_store.get_by_user_id(user.id).await {
+            Ok(blocks) => blocks.into_iter().map(|b| b.blocked_id).collect(),
+            Err(e) => {
+                error!("Error getting user {}'s blocks: {:?}", user.id, e);
+                HashSet::new()
+            }
+        };
+
+        Self {
+            user_id: Some(user.id),
+            is_shadowbanned: user.is_shadowbanned,
+            can_moderate: permissions::has_permission(user, admins, Permission::RemoveContent),
+            blocked_ids,
+        }
+    }
+
+    /// The viewer's id when they're shadowbanned themselves, for `PostStore::get_recent`.
+    pub fn shadowbanned_id(&self) -> Option<u64> {
+        self.user_id.filter(|_| self.is_shadowbanned)
+    }
+
+    /// Whether shadowbanned authors are shown, for `PostStore::get_recent`. Matches `can_see`.
+    pub fn sees_shadowbanned(&self) -> bool {
+        self.can_moderate
+    }
+
+    pub fn can_see(&self, author: &User) -> bool {
+        if self.user_id == Some(author.id) {
+            return true;
+        }
+        if self.blocked_ids.contains(&author.id) {
+            return false;
+        }
+        !author.is_shadowbanned || self.can_moderate
+    }
+
+    pub async fn visible_posts(
+        &self,
+        stores: &EntityStores,
+        posts: Vec<Post>,
+    ) -> Result<Vec<Post>, EntityError> {
+        let authors = self
+            .visible_authors(stores, posts.iter().map(|p| p.author_id))
+            .await?;
+
+        Ok(posts
+            .into_iter()
+            .filter(|p| authors.contains(&p.author_id))
+            .collect())
+    }
+
+    /// Leaves out comments by authors the viewer can't see, and so the replies under them too.
+    pub async fn visible_comments(
+        &self,
+        stores: &EntityStores,
+        comments: Vec<Comment>,
+    ) -> Result<Vec<Comment>, EntityError> {
+        let authors = self
+            .visible_authors(stores, comments.iter().map(|c| c.author_id))
+            .await?;
+
+        Ok(comments
+            .into_iter()
+            .filter(|c| authors.contains(&c.author_id))
+            .collect())
+    }
+
+    async fn visible_authors(
+        &self,
+        stores: &EntityStores,
+        author_ids: impl Iterator<Item = u64>,
+    ) -> Result<HashSet<u64>, EntityError> {
+        let author_ids: Vec<u64> = author_ids.collect::<HashSet<_>>().into_iter().collect();
+
+        Ok(stores
+            .user_store
+            .get_by_ids(&author_ids)
+            .await?
+            .iter()
+            .filter(|author| self.can_see(author))
+            .map(|author| author.id)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::entities::user::Role;
+
+    use super::*;
+
+    fn user(id: u64, is_shadowbanned: bool) -> User {
+        let now = Utc::now();
+        User {
+            id,
+            public_id: id.to_string(),
+            name: format!("user{}", id),
+            email_id: id,
+            is_email_verified: true,
+            role: Role::User,
+            is_banned: false,
+            is_shadowbanned,
+            must_reset_password: false,
+            is_deleted: false,
+            created: now,
+            updated: now,
+        }
+    }
+
+    #[test]
+    fn test_can_see() {
+        let shadowbanned = user(2, true);
+        let blocked = user(3, false);
+        let viewer = Viewer {
+            user_id: Some(1),
+            blocked_ids: HashSet::from([3]),
+            ..Default::default()
+        };
+
+        assert!(viewer.can_see(&user(1, false)));
+        assert!(viewer.can_see(&user(4, false)));
+        assert!(!viewer.can_see(&shadowbanned));
+        assert!(!viewer.can_see(&blocked));
+        assert!(!Viewer::anonymous().can_see(&shadowbanned));
+
+        let moderator = Viewer {
+            can_moderate: true,
+            ..viewer.clone()
+        };
+        assert!(moderator.can_see(&shadowbanned));
+        assert!(!moderator.can_see(&blocked));
+        assert!(moderator.sees_shadowbanned());
+        assert!(!viewer.sees_shadowbanned());
+
+        let author = Viewer {
+            user_id: Some(2),
+            is_shadowbanned: true,
+            ..Default::default()
+        };
+        assert!(author.can_see(&shadowbanned));
+        assert_eq!(author.shadowbanned_id(), Some(2));
+        assert_eq!(viewer.shadowbanned_id(), None);
+    }
+}
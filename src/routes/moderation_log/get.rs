@@ -0,0 +1,121 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use serde::Deserialize;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        moderation_log::{ModerationAction, ModerationLogFilter},
+        user::{Permission, UserStore},
+        EntityError, EntityStores,
+    },
+    routes::{
+        permissions,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Admins,
+};
+
+use super::LOG_PATH;
+
+const MAX_ENTRIES: u32 = 200;
+
+/// Filters from the page's form, which sends empty strings for the ones left blank.
+#[derive(Debug, Deserialize)]
+pub struct LogQuery {
+    action: Option<String>,
+    actor: Option<String>,
+    user: Option<String>,
+}
+
+pub async fn log(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    query: web::Query<LogQuery>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    if let Err(response) = permissions::require_permission(
+        session.clone(),
+        &stores,
+        &admins,
+        Permission::ViewModerationLog,
+        "/",
+    )
+    .await
+    {
+        return response;
+    }
+
+    let action = match non_empty(&query.action) {
+        Some(action) => match action.parse::<ModerationAction>() {
+            Ok(a) => Some(a),
+            Err(()) => return utils::error_redirect(LOG_PATH, "unknown action"),
+        },
+        None => None,
+    };
+    let actor_id = match get_user_id(&stores, &query.actor).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let target_user_id = match get_user_id(&stores, &query.user).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let filter = ModerationLogFilter {
+        actor_id,
+        target_user_id,
+        action,
+    };
+
+    let entries = match super::get_entries(&stores, &filter, MAX_ENTRIES).await {
+        Ok(e) => e,
+        Err(e) => return utils::redirect_entity_error(e, "moderation log"),
+    };
+
+    let mut user_context =
+        user_context::build(session, flash_messages, &stores, "moderation log", None).await;
+    user_context.context.insert("entries", &entries);
+    user_context
+        .context
+        .insert("actions", &ModerationAction::ALL);
+    user_context.context.insert("action", &action);
+    user_context
+        .context
+        .insert("actor", non_empty(&query.actor).unwrap_or_default());
+    user_context
+        .context
+        .insert("user_name", non_empty(&query.user).unwrap_or_default());
+
+    // TODO: handle error
+    let rendered = tera
+        .render("moderation_log.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+async fn get_user_id(
+    stores: &EntityStores,
+    name: &Option<String>,
+) -> Result<Option<u64>, HttpResponse> {
+    let name = match non_empty(name) {
+        Some(n) => n,
+        None => return Ok(None),
+    };
+
+    match stores.user_store.get_by_name(name).await {
+        Ok(u) => Ok(Some(u.id)),
+        Err(EntityError::NotFound) => Err(utils::error_redirect(
+            LOG_PATH,
+            &format!("there's no user named {}", name),
+        )),
+        Err(e) => Err(utils::redirect_entity_error(e, "user")),
+    }
+}
@@ -0,0 +1,65 @@
+use log::error;
+use serde::Deserialize;
+
+use crate::{
+    entities::{
+        moderation_log::{
+            ModerationAction, ModerationLogFilter, ModerationLogStore, ModerationTarget,
+        },
+        user::User,
+        EntityError, EntityStores,
+    },
+    routes::models::{self, ModerationEntryModel},
+};
+
+pub mod get;
+
+const LOG_PATH: &str = "/admin/moderation-log";
+
+/// The optional reason moderators give along with an action.
+#[derive(Debug, Deserialize)]
+pub struct ModerationRequest {
+    pub reason: Option<String>,
+    /// Set by the admin dashboard's forms to come back to it after acting on a user.
+    pub return_to: Option<String>,
+}
+
+/// Adds the action to the moderation log. It has already been taken by the time it's recorded,
+/// so failing to record it is only logged.
+pub async fn record(
+    stores: &EntityStores,
+    actor: &User,
+    action: ModerationAction,
+    target: ModerationTarget,
+    reason: &Option<String>,
+    details: Option<String>,
+) {
+    if let Err(e) = stores
+        .moderation_log_store
+        .insert(actor.id, action, &target, reason, &details)
+        .await
+    {
+        error!(
+            "Error recording {} by {} on {:?} in the moderation log: {:?}",
+            action, actor.id, target, e
+        );
+    }
+}
+
+/// The most recent entries matching the filter, ready to render.
+pub async fn get_entries(
+    stores: &EntityStores,
+    filter: &ModerationLogFilter,
+    count: u32,
+) -> Result<Vec<ModerationEntryModel>, EntityError> {
+    let entries = stores
+        .moderation_log_store
+        .get_recent(filter, count)
+        .await?;
+    let mut models = vec![];
+    for entry in entries {
+        models.push(models::translate_moderation_entry(stores, &entry).await?);
+    }
+
+    Ok(models)
+}
@@ -0,0 +1,63 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use log::error;
+use tera::Tera;
+
+use crate::{
+    entities::{notification::NotificationStore, user::UserStore, EntityStores},
+    routes::{
+        models::{self, NotificationModel},
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+};
+
+const NOTIFICATIONS_PER_PAGE: u8 = 50;
+
+pub async fn notifications(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    tera: web::Data<Tera>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let mut user_context =
+        user_context::build(session, flash_messages, &stores, "notifications", None).await;
+
+    let auth_user = match &user_context.auth_user {
+        Some(u) => u,
+        None => {
+            return utils::warning_redirect("/login", "you must be logged in to view notifications")
+        }
+    };
+    let user = match stores.user_store.get_by_public_id(&auth_user.id).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+
+    let notifications = match stores
+        .notification_store
+        .get_by_user_id(user.id, NOTIFICATIONS_PER_PAGE)
+        .await
+    {
+        Ok(n) => n,
+        Err(e) => return utils::redirect_entity_error(e, "notification"),
+    };
+
+    // A reply that can't be shown, e.g. because its post is gone, is left out
+    let mut inbox: Vec<NotificationModel> = vec![];
+    for notification in notifications.iter() {
+        match models::translate_notification(&stores, notification).await {
+            Ok(n) => inbox.push(n),
+            Err(e) => error!("Error translating notification: {:?}", e),
+        }
+    }
+
+    user_context.context.insert("inbox", &inbox);
+
+    // TODO: handle error
+    let rendered = tera
+        .render("notifications.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
@@ -0,0 +1,74 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::error;
+
+use crate::{
+    entities::{
+        comment::CommentStore, notification::NotificationStore, post::PostStore, user::User,
+        EntityError, EntityStores,
+    },
+    routes::{
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+};
+
+/// Marks a notification read and goes to the reply.
+pub async fn process_read(
+    session: TypedSession,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let user = match get_auth_user(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let notification = match stores.notification_store.mark_read(user.id, &path).await {
+        Ok(n) => n,
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput(_, _)) => {
+            return utils::warning_redirect("/notifications", "that notification doesn't exist")
+        }
+        Err(e) => return utils::redirect_entity_error(e, "notification"),
+    };
+
+    let comment = match stores
+        .comment_store
+        .get_by_id(notification.comment_id)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return utils::redirect_entity_error(e, "comment"),
+    };
+    match stores.post_store.get_by_id(comment.post_id).await {
+        Ok(post) => utils::redirect(&format!("/post/{}", post.public_id)),
+        Err(e) => utils::redirect_entity_error(e, "post"),
+    }
+}
+
+pub async fn process_read_all(
+    session: TypedSession,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let user = match get_auth_user(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores.notification_store.mark_all_read(user.id).await {
+        Ok(()) => utils::success_redirect("/notifications", "all notifications marked as read"),
+        Err(e) => utils::redirect_entity_error(e, "notification"),
+    }
+}
+
+async fn get_auth_user(session: TypedSession, stores: &EntityStores) -> Result<User, HttpResponse> {
+    match user_context::get_auth_user_entity(session, stores).await {
+        Ok(u) => Ok(u),
+        Err(e) => {
+            error!("Error getting authenticated user: {:?}", e);
+            Err(utils::error_redirect(
+                "/login",
+                "you must be logged in to view notifications",
+            ))
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::FlashMessage;
+use chrono::Utc;
+use log::{error, info};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        identity::IdentityStore,
+        user::{User, UserStore, MAX_USERNAME_LENGTH, MIN_USERNAME_LENGTH},
+        EntityError, EntityStores,
+    },
+    oauth::{self, ExternalIdentity, OAuthProvider, OAuthProviders},
+    routes::{
+        login::post::login_user_and_redirect,
+        user_context::session_state::{OAuthState, TypedSession},
+        utils,
+    },
+    server::Environment,
+};
+
+const MAX_USERNAME_ATTEMPTS: usize = 5;
+const USERNAME_SUFFIX_LENGTH: usize = 4;
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+pub async fn authorize(
+    session: TypedSession,
+    path: web::Path<String>,
+    providers: web::Data<OAuthProviders>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let provider = match providers.get(&path.into_inner()) {
+        Ok(p) => p,
+        Err(_) => return utils::error_redirect("/login", "unknown login provider"),
+    };
+
+    let oauth_state = OAuthState {
+        provider: provider.name.clone(),
+        state: oauth::random_token(),
+        nonce: oauth::random_token(),
+        code_verifier: oauth::random_token(),
+        created: Utc::now(),
+    };
+    if let Err(e) = session.insert_oauth_state(&oauth_state) {
+        error!("Error inserting oauth state into session: {:?}", e);
+        return utils::error_redirect("/login", "something went wrong, please try again");
+    }
+
+    let authorization_url = provider.authorization_url(
+        &redirect_uri(&env, provider),
+        &oauth_state.state,
+        &oauth_state.nonce,
+        &oauth::code_challenge(&oauth_state.code_verifier),
+    );
+
+    utils::redirect(authorization_url.as_str())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn callback(
+    session: TypedSession,
+    path: web::Path<String>,
+    query: web::Query<CallbackQuery>,
+    stores: web::Data<EntityStores>,
+    providers: web::Data<OAuthProviders>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let provider = match providers.get(&path.into_inner()) {
+        Ok(p) => p,
+        Err(_) => return utils::error_redirect("/login", "unknown login provider"),
+    };
+
+    // The state must match the one stored when the login was started, otherwise this request
+    // could have been forged to log the user in to someone else's account
+    let oauth_state = match session.take_oauth_state() {
+        Ok(Some(s)) if s.provider == provider.name && Some(&s.state) == query.state.as_ref() => s,
+        _ => return utils::error_redirect("/login", "your login has expired, please try again"),
+    };
+
+    if let Some(e) = &query.error {
+        info!("{} login was not completed: {}", provider.name, e);
+        return utils::warning_redirect(
+            "/login",
+            &format!("{} login was cancelled", provider.display_name),
+        );
+    }
+    let code = match &query.code {
+        Some(c) => c,
+        None => return provider_error_redirect(provider),
+    };
+
+    let tokens = match provider
+        .exchange_code(
+            code,
+            &redirect_uri(&env, provider),
+            &oauth_state.code_verifier,
+        )
+        .await
+    {
+        Ok(t) => t,
+        Err(e) => {
+            error!(
+                "Error exchanging {} authorization code: {:?}",
+                provider.name, e
+            );
+            return provider_error_redirect(provider);
+        }
+    };
+    let external_identity = match provider.get_identity(&tokens, &oauth_state.nonce).await {
+        Ok(i) => i,
+        Err(e) => {
+            error!("Error getting {} identity: {:?}", provider.name, e);
+            return provider_error_redirect(provider);
+        }
+    };
+
+    let auth_user_id = session.get_user_id().unwrap_or(None);
+    let identity = match stores
+        .identity_store
+        .get_by_provider_subject(&external_identity.provider, &external_identity.subject)
+        .await
+    {
+        Ok(i) => Some(i),
+        Err(EntityError::NotFound) => None,
+        Err(e) => return utils::redirect_entity_error(e, "identity"),
+    };
+
+    match (identity, auth_user_id) {
+        (Some(identity), None) => match stores.user_store.get_by_id(identity.user_id).await {
+            Ok(user) => login_user_and_redirect(session, &stores, user).await,
+            Err(e) => utils::redirect_entity_error(e, "user"),
+        },
+        (Some(identity), Some(auth_user_id)) => {
+            match stores.user_store.get_by_id(identity.user_id).await {
+                Ok(user) if user.public_id == auth_user_id => utils::success_redirect(
+                    "/settings",
+                    &format!("your {} account is already linked", provider.display_name),
+                ),
+                Ok(_) => utils::error_redirect(
+                    "/settings",
+                    &format!(
+                        "that {} account is linked to a different user",
+                        provider.display_name
+                    ),
+                ),
+                Err(e) => utils::redirect_entity_error(e, "user"),
+            }
+        }
+        (None, Some(auth_user_id)) => {
+            link_identity(&stores, provider, &external_identity, &auth_user_id).await
+        }
+        (None, None) => {
+            let user = match create_user(&stores, provider, &external_identity).await {
+                Ok(u) => u,
+                Err(response) => return response,
+            };
+            FlashMessage::success("successfully signed up").send();
+
+            login_user_and_redirect(session, &stores, user).await
+        }
+    }
+}
+
+fn redirect_uri(env: &Environment, provider: &OAuthProvider) -> String {
+    format!("{}/login/oauth/{}/callback", env.base_url(), provider.name)
+}
+
+fn provider_error_redirect(provider: &OAuthProvider) -> HttpResponse {
+    utils::error_redirect(
+        "/login",
+        &format!(
+            "something went wrong logging in with {}, please try again",
+            provider.display_name
+        ),
+    )
+}
+
+async fn link_identity(
+    stores: &EntityStores,
+    provider: &OAuthProvider,
+    external_identity: &ExternalIdentity,
+    auth_user_id: &str,
+) -> HttpResponse {
+    let user = match stores.user_store.get_by_public_id(auth_user_id).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+
+    match stores
+        .identity_store
+        .insert(
+            user.id,
+            &external_identity.provider,
+            &external_identity.subject,
+        )
+        .await
+    {
+        Ok(_) => utils::success_redirect(
+            "/settings",
+            &format!("linked your {} account", provider.display_name),
+        ),
+        Err(e) => {
+            error!("Error linking {} identity: {:?}", provider.name, e);
+            utils::error_redirect(
+                "/settings",
+                &format!(
+                    "something went wrong linking your {} account, please try again",
+                    provider.display_name
+                ),
+            )
+        }
+    }
+}
+
+// First login with an identity that isn't linked to anyone, so sign them up
+async fn create_user(
+    stores: &EntityStores,
+    provider: &OAuthProvider,
+    external_identity: &ExternalIdentity,
+) -> Result<User, HttpResponse> {
+    let email = match &external_identity.email {
+        Some(e) => e,
+        None => {
+            return Err(utils::error_redirect(
+                "/signup",
+                &format!(
+                    "{} didn't share a verified email address, sign up with a password and then link your {} account from your settings",
+                    provider.display_name, provider.display_name
+                ),
+            ))
+        }
+    };
+
+    let base_name = username_from(external_identity.username.as_deref(), email);
+    let mut result = Err(EntityError::DuplicateKey);
+    for attempt in 0..MAX_USERNAME_ATTEMPTS {
+        let name = match attempt {
+            0 => base_name.clone(),
+            _ => with_random_suffix(&base_name),
+        };
+
+        result = stores.user_store.insert_external(&name, email).await;
+        if !matches!(result, Err(EntityError::DuplicateKey)) {
+            break;
+        }
+    }
+
+    let user = match result {
+        Ok(u) => u,
+        Err(e) => {
+            error!(
+                "Error creating user for {} identity: {:?}",
+                provider.name, e
+            );
+            return Err(provider_error_redirect(provider));
+        }
+    };
+
+    if let Err(e) = stores
+        .identity_store
+        .insert(
+            user.id,
+            &external_identity.provider,
+            &external_identity.subject,
+        )
+        .await
+    {
+        error!("Error linking {} identity: {:?}", provider.name, e);
+        return Err(provider_error_redirect(provider));
+    }
+
+    info!("Created user {} from {} login", user.name, provider.name);
+    Ok(user)
+}
+
+/// Derives a valid user name from the provider's user name, or the email address if it has none.
+fn username_from(username: Option<&str>, email: &str) -> String {
+    let raw = username.unwrap_or(email.split('@').next().unwrap_or_default());
+    let name: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(MAX_USERNAME_LENGTH)
+        .collect::<String>()
+        .to_lowercase();
+
+    if name.len() < MIN_USERNAME_LENGTH {
+        return with_random_suffix(&format!("user-{}", name));
+    }
+
+    name
+}
+
+fn with_random_suffix(name: &str) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    let max_length = MAX_USERNAME_LENGTH - USERNAME_SUFFIX_LENGTH - 1;
+    let name: String = name.chars().take(max_length).collect();
+
+    format!("{}-{}", name, &suffix[..USERNAME_SUFFIX_LENGTH])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_username_from() {
+        assert_eq!(username_from(Some("Big.Foot"), "x@example.com"), "bigfoot");
+        assert_eq!(username_from(None, "sasquatch@example.com"), "sasquatch");
+
+        let short = username_from(Some("yt"), "x@example.com");
+        assert!(short.starts_with("user-yt-"));
+        assert_eq!(short.len(), "user-yt-".len() + USERNAME_SUFFIX_LENGTH);
+
+        let long = with_random_suffix(&"a".repeat(40));
+        assert_eq!(long.len(), MAX_USERNAME_LENGTH);
+    }
+}
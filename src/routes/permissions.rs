@@ -0,0 +1,67 @@
+use actix_web::HttpResponse;
+use log::error;
+
+use crate::{
+    entities::{
+        user::{Permission, User},
+        EntityStores,
+    },
+    routes::{
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Admins,
+};
+
+pub fn has_permission(user: &User, admins: &Admins, permission: Permission) -> bool {
+    admins.role_of(user).can(permission)
+}
+
+/// Moderators can only ban users below them, so they can't ban each other or an admin.
+pub fn can_ban(moderator: &User, user: &User, admins: &Admins) -> bool {
+    has_permission(moderator, admins, Permission::BanUser)
+        && admins.role_of(moderator) > admins.role_of(user)
+}
+
+/// The logged in user, or a redirect back to `location` if their role doesn't grant the
+/// permission.
+pub async fn require_permission(
+    session: TypedSession,
+    stores: &EntityStores,
+    admins: &Admins,
+    permission: Permission,
+    location: &str,
+) -> Result<User, HttpResponse> {
+    let user = match user_context::get_auth_user_entity(session, stores).await {
+        Ok(u) => u,
+        Err(e) => {
+            error!("Error getting authenticated user: {:?}", e);
+            return Err(utils::error_redirect(
+                "/login",
+                "you must be logged in to do that",
+            ));
+        }
+    };
+
+    match has_permission(&user, admins, permission) {
+        true => Ok(user),
+        false => Err(utils::error_redirect(
+            location,
+            &format!("you don't have permission to {}", describe(permission)),
+        )),
+    }
+}
+
+fn describe(permission: Permission) -> &'static str {
+    match permission {
+        Permission::RemoveContent => "remove posts and comments",
+        Permission::LockThread => "lock threads",
+        Permission::BanUser => "ban users",
+        Permission::ManageRoles => "change users' roles",
+        Permission::ResetTwoFactor => "reset two-factor authentication",
+        Permission::ResetPassword => "force password resets",
+        Permission::ManageWebhooks => "manage webhooks",
+        Permission::ViewModerationLog => "view the moderation log",
+        Permission::ViewDashboard => "view the admin dashboard",
+    }
+}
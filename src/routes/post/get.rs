@@ -1,17 +1,39 @@
-use actix_web::{web, HttpResponse, Responder};
+use std::time::Duration;
+
+use actix_web::{
+    http::header::ContentEncoding,
+    web::{self, Bytes},
+    HttpResponse, Responder,
+};
 use actix_web_flash_messages::IncomingFlashMessages;
-use tera::Tera;
+use futures_util::stream;
+use log::error;
+use serde_json::json;
+use tera::{Context, Tera};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
 
 use crate::{
-    entities::{post::PostStore, EntityStores},
+    entities::{
+        comment::CommentStore,
+        content::ContentStore,
+        post::PostStore,
+        report::{ReportReason, MAX_DETAILS_LENGTH},
+        user::{Permission, UserStore},
+        EntityError, EntityStores,
+    },
+    live::{CommentBus, CommentEvent},
     routes::{
-        models,
+        models::{self, PageMetadata, Viewer},
+        permissions,
         user_context::{session_state::TypedSession, user_context},
         utils,
     },
+    server::{Admins, Environment},
 };
 
 const HERO_BG_CLASS: &str = "hero-bg-post";
+// Keeps idle connections from being closed by proxies, and finds readers that left
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
 
 pub async fn post(
     session: TypedSession,
@@ -19,6 +41,8 @@ pub async fn post(
     tera: web::Data<Tera>,
     path: web::Path<String>,
     stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    env: web::Data<Environment>,
 ) -> impl Responder {
     // TODO: handle errors
     let path_post = path.into_inner();
@@ -29,7 +53,39 @@ pub async fn post(
         }
     };
 
-    let post_model = models::translate_post(&post, &stores).await.unwrap();
+    let auth_user = user_context::get_auth_user_entity(session.clone(), &stores)
+        .await
+        .ok();
+    let can = |permission| match &auth_user {
+        Some(user) => permissions::has_permission(user, &admins, permission),
+        None => false,
+    };
+    let can_moderate = can(Permission::RemoveContent);
+    if post.is_removed && !can_moderate {
+        return utils::redirect_entity_error(EntityError::NotFound, "post");
+    }
+    let viewer = Viewer::load(&stores, auth_user.as_ref(), &admins).await;
+    match stores.user_store.get_by_id(post.author_id).await {
+        Ok(author) if viewer.can_see(&author) => {}
+        Ok(_) => return utils::redirect_entity_error(EntityError::NotFound, "post"),
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    }
+
+    let post_model = models::translate_post(&post, &stores, &viewer)
+        .await
+        .unwrap();
+
+    // The summary only has the rendered html, the excerpt is taken from the markdown
+    let markdown = match post.content_id {
+        Some(id) => stores
+            .content_store
+            .get_by_id(id)
+            .await
+            .ok()
+            .map(|c| c.body),
+        None => None,
+    };
+    let metadata = PageMetadata::for_post(&post_model.summary, markdown.as_deref(), env.base_url());
 
     let mut user_context = user_context::build(
         session,
@@ -41,9 +97,147 @@ pub async fn post(
     .await;
 
     user_context.context.insert("post", &post_model);
+    user_context.context.insert("metadata", &metadata);
+    user_context.context.insert("can_moderate", &can_moderate);
+    user_context
+        .context
+        .insert("can_lock", &can(Permission::LockThread));
+    insert_report_options(&mut user_context.context);
 
     // TODO: handle error
     let rendered = tera.render("post.html", &user_context.context).unwrap();
 
     HttpResponse::Ok().body(rendered)
 }
+
+struct CommentStream {
+    receiver: Receiver<CommentEvent>,
+    post_id: u64,
+    post_public_id: String,
+    csrf_token: String,
+    is_auth: bool,
+    can_moderate: bool,
+    viewer: Viewer,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+}
+
+/// Server-Sent Events with each new comment on the post, rendered like the comments already on
+/// the page so it can be slotted in under its parent.
+pub async fn comment_events(
+    session: TypedSession,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+    comment_bus: web::Data<CommentBus>,
+    admins: web::Data<Admins>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let post = match stores.post_store.get_by_public_id(&path).await {
+        Ok(p) if p.is_removed => return HttpResponse::NotFound().finish(),
+        Ok(p) => p,
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput(_, _)) => {
+            return HttpResponse::NotFound().finish()
+        }
+        Err(e) => {
+            error!("Error getting post for comment events: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let auth_user = user_context::get_auth_user_entity(session.clone(), &stores)
+        .await
+        .ok();
+    let can_moderate = match &auth_user {
+        Some(user) => permissions::has_permission(user, &admins, Permission::RemoveContent),
+        None => false,
+    };
+    let state = CommentStream {
+        receiver: comment_bus.subscribe(),
+        post_id: post.id,
+        post_public_id: post.public_id,
+        // The post page already made one, the reply forms need it
+        csrf_token: session.get_csrf_token().unwrap_or(None).unwrap_or_default(),
+        is_auth: auth_user.is_some(),
+        can_moderate,
+        viewer: Viewer::load(&stores, auth_user.as_ref(), &admins).await,
+        stores,
+        tera,
+    };
+    let events = stream::unfold(state, |mut state| async move {
+        loop {
+            let event = match actix_web::rt::time::timeout(KEEP_ALIVE, state.receiver.recv()).await
+            {
+                Ok(Ok(event)) if event.post_id == state.post_id => event,
+                Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => continue,
+                Ok(Err(RecvError::Closed)) => return None,
+                Err(_) => {
+                    return Some((
+                        Ok::<_, actix_web::Error>(Bytes::from_static(b": keep-alive\n\n")),
+                        state,
+                    ))
+                }
+            };
+
+            match render_comment_event(&state, event.comment_id).await {
+                Ok(Some(message)) => return Some((Ok(Bytes::from(message)), state)),
+                Ok(None) => continue,
+                Err(e) => error!("Error rendering comment {}: {:?}", event.comment_id, e),
+            }
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        // Compressing would buffer events until enough of them arrive
+        .insert_header(ContentEncoding::Identity)
+        .streaming(events)
+}
+
+// Nothing when the reader can't see the comment's author
+async fn render_comment_event(
+    state: &CommentStream,
+    comment_id: u64,
+) -> Result<Option<String>, EntityError> {
+    let comment = state.stores.comment_store.get_by_id(comment_id).await?;
+    let author = state.stores.user_store.get_by_id(comment.author_id).await?;
+    if !state.viewer.can_see(&author) {
+        return Ok(None);
+    }
+    let parent_id = match comment.parent_id {
+        Some(id) => Some(state.stores.comment_store.get_by_id(id).await?.public_id),
+        None => None,
+    };
+    let comment_model =
+        models::translate_comment(&state.stores, &state.viewer, &comment, 0).await?;
+
+    let mut context = Context::new();
+    context.insert("comment", &comment_model);
+    context.insert("comment_depth", &0);
+    context.insert(
+        "post",
+        &json!({ "summary": { "id": state.post_public_id, "is_locked": false } }),
+    );
+    context.insert("csrf_token", &state.csrf_token);
+    context.insert("is_auth", &state.is_auth);
+    context.insert("can_moderate", &state.can_moderate);
+    insert_report_options(&mut context);
+    let html = match state.tera.render("comment.html", &context) {
+        Ok(html) => html,
+        Err(e) => return Err(EntityError::Internal(format!("{:?}", e))),
+    };
+
+    // Serialized JSON has no newlines, so it fits on the one data line
+    let data = json!({ "id": comment_model.id, "parent_id": parent_id, "html": html });
+
+    Ok(Some(format!(
+        "event: comment\nid: {}\ndata: {}\n\n",
+        comment_model.id, data
+    )))
+}
+
+/// What the report forms on the post and its comments offer.
+fn insert_report_options(context: &mut Context) {
+    context.insert("report_reasons", &ReportReason::ALL);
+    context.insert("max_report_details_length", &MAX_DETAILS_LENGTH);
+}
@@ -0,0 +1,220 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, warn};
+
+use crate::{
+    entities::{
+        moderation_log::{ModerationAction, ModerationTarget},
+        post::PostStore,
+        report::{ReportResolution, ReportStore},
+        user::{Permission, User},
+        EntityError, EntityStores,
+    },
+    routes::{
+        moderation_log::{self, ModerationRequest},
+        permissions, reports,
+        user_context::session_state::TypedSession,
+        utils,
+    },
+    server::Admins,
+    spam,
+    unfurl::LinkPreviewer,
+};
+
+pub async fn process_remove(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    link_previewer: web::Data<LinkPreviewer>,
+) -> impl Responder {
+    set_removed(
+        session,
+        &path,
+        &data,
+        &stores,
+        &admins,
+        &link_previewer,
+        true,
+    )
+    .await
+}
+
+pub async fn process_restore(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    link_previewer: web::Data<LinkPreviewer>,
+) -> impl Responder {
+    set_removed(
+        session,
+        &path,
+        &data,
+        &stores,
+        &admins,
+        &link_previewer,
+        false,
+    )
+    .await
+}
+
+pub async fn process_lock(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    set_locked(session, &path, &data, &stores, &admins, true).await
+}
+
+pub async fn process_unlock(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    set_locked(session, &path, &data, &stores, &admins, false).await
+}
+
+async fn set_removed(
+    session: TypedSession,
+    path_post: &str,
+    data: &ModerationRequest,
+    stores: &EntityStores,
+    admins: &Admins,
+    link_previewer: &LinkPreviewer,
+    is_removed: bool,
+) -> HttpResponse {
+    let location = format!("/post/{}", path_post);
+    let moderator = match permissions::require_permission(
+        session,
+        stores,
+        admins,
+        Permission::RemoveContent,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let post = match stores.post_store.get_by_public_id(path_post).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+
+    let is_held = match stores.report_store.is_held(post.id, None).await {
+        Ok(h) => h,
+        Err(e) => return utils::redirect_entity_error(e, "reports"),
+    };
+    let is_approval = reports::is_approval(is_removed, is_held);
+
+    let action = if is_removed { "removed" } else { "restored" };
+    let updated = match is_approval {
+        true => approve(stores, link_previewer, &moderator, post.id).await,
+        false => stores
+            .post_store
+            .set_removed(post.id, is_removed)
+            .await
+            .map(|_| ()),
+    };
+    match updated {
+        Ok(_) => {
+            warn!("🛡️ {} {} post {}", moderator.name, action, post.public_id);
+            let log_action = match (is_removed, is_approval) {
+                (true, _) => ModerationAction::RemovePost,
+                (false, true) => ModerationAction::ApproveHeld,
+                (false, false) => ModerationAction::RestorePost,
+            };
+            moderation_log::record(
+                stores,
+                &moderator,
+                log_action,
+                ModerationTarget::post(&post),
+                &data.reason,
+                None,
+            )
+            .await;
+            spam::train_post(stores, &post, is_removed).await;
+            utils::success_redirect(&location, &format!("post {}", action))
+        }
+        Err(e) => {
+            error!("Error setting post {} removed: {:?}", post.id, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
+
+// Publishes a held post and closes its reports, so it's only ever published once
+async fn approve(
+    stores: &EntityStores,
+    link_previewer: &LinkPreviewer,
+    moderator: &User,
+    post_id: u64,
+) -> Result<(), EntityError> {
+    reports::publish_held_post(stores, link_previewer, post_id).await?;
+    stores
+        .report_store
+        .resolve(post_id, None, moderator.id, ReportResolution::Dismissed)
+        .await?;
+
+    Ok(())
+}
+
+async fn set_locked(
+    session: TypedSession,
+    path_post: &str,
+    data: &ModerationRequest,
+    stores: &EntityStores,
+    admins: &Admins,
+    is_locked: bool,
+) -> HttpResponse {
+    let location = format!("/post/{}", path_post);
+    let moderator = match permissions::require_permission(
+        session,
+        stores,
+        admins,
+        Permission::LockThread,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let post = match stores.post_store.get_by_public_id(path_post).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+
+    let action = if is_locked { "locked" } else { "unlocked" };
+    match stores.post_store.set_locked(post.id, is_locked).await {
+        Ok(_) => {
+            warn!("🛡️ {} {} post {}", moderator.name, action, post.public_id);
+            let log_action = match is_locked {
+                true => ModerationAction::LockThread,
+                false => ModerationAction::UnlockThread,
+            };
+            moderation_log::record(
+                stores,
+                &moderator,
+                log_action,
+                ModerationTarget::post(&post),
+                &data.reason,
+                None,
+            )
+            .await;
+            utils::success_redirect(&location, &format!("thread {}", action))
+        }
+        Err(e) => {
+            error!("Error setting post {} locked: {:?}", post.id, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
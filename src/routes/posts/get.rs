@@ -12,6 +12,7 @@ use crate::{
         models::{self, PostSummary},
         user_context::{session_state::TypedSession, user_context},
     },
+    server::Admins,
 };
 
 const POSTS_PER_PAGE: u8 = 15;
@@ -23,9 +24,10 @@ pub async fn posts(
     flash_messages: IncomingFlashMessages,
     tera: web::Data<Tera>,
     stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
 ) -> impl Responder {
     let mut user_context = user_context::build(
-        session,
+        session.clone(),
         flash_messages,
         &stores,
         "posts",
@@ -33,7 +35,20 @@ pub async fn posts(
     )
     .await;
 
-    let result = stores.post_store.get_recent(None, POSTS_PER_PAGE).await;
+    let viewer = user_context::get_viewer(session, &stores, &admins).await;
+    let result = match stores
+        .post_store
+        .get_recent(
+            None,
+            POSTS_PER_PAGE,
+            viewer.shadowbanned_id(),
+            viewer.sees_shadowbanned(),
+        )
+        .await
+    {
+        Ok(posts) => viewer.visible_posts(&stores, posts).await,
+        Err(e) => Err(e),
+    };
 
     let post_entities = match result {
         Ok(p) => p,
@@ -0,0 +1,142 @@
+use std::{future::Future, marker::PhantomData, pin::Pin, time::Duration};
+
+use actix_web::{
+    dev::{Payload, ServiceResponse},
+    error::InternalError,
+    http::header::{self, ContentType},
+    middleware::ErrorHandlerResponse,
+    web, FromRequest, HttpRequest, HttpResponse, Result,
+};
+use log::{error, warn};
+use url::Url;
+
+use crate::{
+    rate_limit::{RateLimitConfig, RateLimiter},
+    routes::utils,
+};
+
+use super::user_context::session_state::TypedSession;
+
+/// A route whose requests are limited by the rate limit rules configured under `NAME`.
+/// Browsers that hit the limit are redirected back to the page they came from (or `REDIRECT`)
+/// with a flash message.
+pub trait LimitedRoute {
+    const NAME: &'static str;
+    const REDIRECT: &'static str;
+}
+
+pub struct Login;
+pub struct Signup;
+pub struct Submit;
+pub struct Comment;
+
+impl LimitedRoute for Login {
+    const NAME: &'static str = RateLimitConfig::LOGIN;
+    const REDIRECT: &'static str = "/login";
+}
+
+impl LimitedRoute for Signup {
+    const NAME: &'static str = RateLimitConfig::SIGNUP;
+    const REDIRECT: &'static str = "/signup";
+}
+
+impl LimitedRoute for Submit {
+    const NAME: &'static str = RateLimitConfig::SUBMIT;
+    const REDIRECT: &'static str = "/submit";
+}
+
+impl LimitedRoute for Comment {
+    const NAME: &'static str = RateLimitConfig::COMMENT;
+    const REDIRECT: &'static str = "/posts";
+}
+
+/// Extractor that takes a token from the per-IP and per-user buckets for route `R`,
+/// rejecting the request before the handler runs if either bucket is empty.
+pub struct RateLimited<R: LimitedRoute>(PhantomData<R>);
+
+impl<R: LimitedRoute + 'static> FromRequest for RateLimited<R> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+
+        Box::pin(async move {
+            let rate_limiter = match req.app_data::<web::Data<RateLimiter>>() {
+                Some(r) => r.clone(),
+                None => {
+                    error!(
+                        "RateLimiter missing from app data, {} is not limited",
+                        R::NAME
+                    );
+                    return Ok(RateLimited(PhantomData));
+                }
+            };
+
+            let user_id = match TypedSession::extract(&req).await {
+                Ok(session) => session.get_user_id().unwrap_or(None),
+                Err(_) => None,
+            };
+            let ip = utils::get_client_ip(&req);
+
+            match rate_limiter
+                .check(R::NAME, ip.as_deref(), user_id.as_deref())
+                .await
+            {
+                None => Ok(RateLimited(PhantomData)),
+                Some(retry_after) => {
+                    warn!(
+                        "🚦 Rate limited {} request from ip: {:?}, user: {:?}",
+                        R::NAME,
+                        ip,
+                        user_id
+                    );
+                    let response = limited_response(&req, R::REDIRECT, retry_after);
+                    Err(InternalError::from_response("rate limited", response).into())
+                }
+            }
+        })
+    }
+}
+
+/// Lets 429 responses through untouched instead of redirecting them to the generic error page.
+pub fn render_too_many_requests<B>(res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
+    Ok(ErrorHandlerResponse::Response(res.map_into_left_body()))
+}
+
+fn limited_response(req: &HttpRequest, redirect: &str, retry_after: Duration) -> HttpResponse {
+    let retry_after_secs = retry_after.as_secs().max(1);
+
+    if accepts_html(req) {
+        return utils::warning_redirect(
+            &referer_path(req).unwrap_or(redirect.to_owned()),
+            &format!(
+                "slow down! you're doing that too often, try again in {}",
+                utils::readable_wait(retry_after_secs)
+            ),
+        );
+    }
+
+    HttpResponse::TooManyRequests()
+        .insert_header((header::RETRY_AFTER, retry_after_secs.to_string()))
+        .content_type(ContentType::plaintext())
+        .body("too many requests")
+}
+
+fn accepts_html(req: &HttpRequest) -> bool {
+    match req.headers().get(header::ACCEPT) {
+        Some(accept) => match accept.to_str() {
+            Ok(accept) => accept.contains("text/html"),
+            Err(_) => false,
+        },
+        None => false,
+    }
+}
+
+// Only the path is kept so that a forged Referer can't redirect off-site
+fn referer_path(req: &HttpRequest) -> Option<String> {
+    let referer = req.headers().get(header::REFERER)?.to_str().ok()?;
+    let url = Url::parse(referer).ok()?;
+
+    Some(url.path().to_owned())
+}
@@ -0,0 +1,141 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        comment::CommentStore,
+        content::ContentStore,
+        moderation_log::MAX_REASON_LENGTH,
+        post::PostStore,
+        report::{ReportReason, ReportStore, ReportedItem},
+        user::{Permission, User, UserStore},
+        EntityError, EntityStores,
+    },
+    routes::{
+        models::{self, PostSummary, UserModel},
+        permissions,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Admins,
+};
+
+// Enough to keep moderators busy, the rest show up as these are resolved
+const MAX_OPEN_REPORTS: u32 = 500;
+const MAX_CONTENT_LENGTH: usize = 400;
+
+#[derive(Serialize)]
+struct QueueItem {
+    post: PostSummary,
+    comment: Option<QueueComment>,
+    author: UserModel,
+    is_author_banned: bool,
+    can_ban: bool,
+    // Held by the spam filter, so it hasn't been published yet
+    is_held: bool,
+    report_count: usize,
+    reasons: Vec<(ReportReason, usize)>,
+    details: Vec<String>,
+    first_reported: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+struct QueueComment {
+    id: String,
+    content: String,
+    is_removed: bool,
+}
+
+/// Open reports grouped by the post or comment they're about, most reported first.
+pub async fn queue(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let moderator = match permissions::require_permission(
+        session.clone(),
+        &stores,
+        &admins,
+        Permission::RemoveContent,
+        "/",
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let reports = match stores.report_store.get_open(MAX_OPEN_REPORTS).await {
+        Ok(r) => r,
+        Err(e) => return utils::redirect_entity_error(e, "reports"),
+    };
+    let mut items = vec![];
+    for item in ReportedItem::group(reports) {
+        match translate_item(&stores, &admins, &moderator, &item).await {
+            Ok(i) => items.push(i),
+            Err(e) => return utils::redirect_entity_error(e, "reported item"),
+        }
+    }
+
+    let mut user_context =
+        user_context::build(session, flash_messages, &stores, "moderation queue", None).await;
+    user_context.context.insert("items", &items);
+    user_context
+        .context
+        .insert("max_reason_length", &MAX_REASON_LENGTH);
+
+    // TODO: handle error
+    let rendered = tera.render("queue.html", &user_context.context).unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
+
+async fn translate_item(
+    stores: &EntityStores,
+    admins: &Admins,
+    moderator: &User,
+    item: &ReportedItem,
+) -> Result<QueueItem, EntityError> {
+    let post = stores.post_store.get_by_id(item.post_id).await?;
+    let post_summary = models::translate_post_summary(&post, stores, MAX_CONTENT_LENGTH).await?;
+
+    let (comment, author_id) = match item.comment_id {
+        Some(comment_id) => {
+            let comment = stores.comment_store.get_by_id(comment_id).await?;
+            // Moderators still see what a removed comment said
+            let content = stores.content_store.get_by_id(comment.content_id).await?;
+            (
+                Some(QueueComment {
+                    id: comment.public_id,
+                    content: content.body_html,
+                    is_removed: comment.is_removed,
+                }),
+                comment.author_id,
+            )
+        }
+        None => (None, post.author_id),
+    };
+    let author = stores.user_store.get_by_id(author_id).await?;
+
+    Ok(QueueItem {
+        post: post_summary,
+        comment,
+        is_author_banned: author.is_banned,
+        can_ban: permissions::can_ban(moderator, &author, admins),
+        author: UserModel::from(author),
+        is_held: item.is_held(),
+        report_count: item.reports.len(),
+        reasons: item.reason_counts(),
+        details: item
+            .reports
+            .iter()
+            .filter_map(|r| r.details.clone())
+            .collect(),
+        first_reported: item.first_reported(),
+    })
+}
@@ -0,0 +1,89 @@
+use log::{error, warn};
+
+use crate::{
+    entities::{
+        comment::CommentStore,
+        post::PostStore,
+        report::{ReportReason, ReportStore},
+        EntityError, EntityStores,
+    },
+    live::{CommentBus, CommentEvent},
+    unfurl::LinkPreviewer,
+};
+
+pub mod get;
+pub mod post;
+
+const QUEUE_PATH: &str = "/mod/queue";
+
+/// Puts a post or comment the spam filter held in the moderation queue. It's already saved
+/// unpublished, so failing to queue it is only logged.
+pub async fn hold(stores: &EntityStores, post_id: u64, comment_id: Option<u64>, score: f64) {
+    warn!(
+        "🥫 Holding post {} comment {:?} for review, scored {:.3}",
+        post_id, comment_id, score
+    );
+    let details = Some(format!("held by the spam filter, scored {:.2}", score));
+    if let Err(e) = stores
+        .report_store
+        .insert(None, post_id, comment_id, ReportReason::Spam, &details)
+        .await
+    {
+        error!(
+            "Error queueing held post {} comment {:?}: {:?}",
+            post_id, comment_id, e
+        );
+    }
+}
+
+/// Publishes a post the spam filter held and previews its link. Its reports still have to be
+/// resolved, or it's published again.
+pub async fn publish_held_post(
+    stores: &EntityStores,
+    link_previewer: &LinkPreviewer,
+    post_id: u64,
+) -> Result<(), EntityError> {
+    let post = stores.post_store.publish(post_id).await?;
+    link_previewer.spawn(stores, &post);
+
+    Ok(())
+}
+
+/// Publishes a comment the spam filter held, telling everyone who would have heard about it had
+/// it never been held. Its reports still have to be resolved, or it's published again.
+pub async fn publish_held_comment(
+    stores: &EntityStores,
+    comment_bus: &CommentBus,
+    post_id: u64,
+    comment_id: u64,
+) -> Result<(), EntityError> {
+    stores.comment_store.publish(comment_id).await?;
+    comment_bus
+        .publish(CommentEvent {
+            post_id,
+            comment_id,
+        })
+        .await;
+
+    Ok(())
+}
+
+/// Restoring something the spam filter held is approving it, restoring anything else only shows
+/// it again.
+pub fn is_approval(is_removed: bool, is_held: bool) -> bool {
+    !is_removed && is_held
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_approval() {
+        assert!(is_approval(false, true));
+
+        assert!(!is_approval(false, false));
+        assert!(!is_approval(true, true));
+        assert!(!is_approval(true, false));
+    }
+}
@@ -0,0 +1,305 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, warn};
+use serde::Deserialize;
+
+use crate::{
+    entities::{
+        comment::CommentStore,
+        moderation_log::{ModerationAction, ModerationTarget},
+        post::PostStore,
+        report::{ReportReason, ReportResolution, ReportStore},
+        user::{Permission, User, UserStore},
+        EntityError, EntityStores,
+    },
+    live::CommentBus,
+    routes::{
+        moderation_log, permissions,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Admins,
+    spam,
+    unfurl::LinkPreviewer,
+};
+
+use super::QUEUE_PATH;
+
+#[derive(Debug, Deserialize)]
+pub struct ReportRequest {
+    reason: String,
+    details: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveRequest {
+    post_id: String,
+    // Left out when the post itself was reported
+    comment_id: Option<String>,
+    action: String,
+    reason: Option<String>,
+}
+
+pub async fn process_report_post(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ReportRequest>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let reporter = match get_reporter(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+    let post = match stores.post_store.get_by_public_id(&path).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+
+    let location = format!("/post/{}", post.public_id);
+    submit_report(&stores, &reporter, post.id, None, &data, &location).await
+}
+
+pub async fn process_report_comment(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ReportRequest>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let reporter = match get_reporter(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+    let comment = match stores.comment_store.get_by_public_id(&path).await {
+        Ok(c) => c,
+        Err(e) => return utils::redirect_entity_error(e, "comment"),
+    };
+    let post = match stores.post_store.get_by_id(comment.post_id).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+
+    let location = format!("/post/{}", post.public_id);
+    submit_report(
+        &stores,
+        &reporter,
+        post.id,
+        Some(comment.id),
+        &data,
+        &location,
+    )
+    .await
+}
+
+/// Resolves every open report on a post or comment: dismissing them, removing what they're
+/// about, or removing it and banning its author.
+pub async fn process_resolve(
+    session: TypedSession,
+    data: web::Form<ResolveRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    comment_bus: web::Data<CommentBus>,
+    link_previewer: web::Data<LinkPreviewer>,
+) -> impl Responder {
+    let moderator = match permissions::require_permission(
+        session,
+        &stores,
+        &admins,
+        Permission::RemoveContent,
+        QUEUE_PATH,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let resolution = match data.action.as_str() {
+        "dismiss" => ReportResolution::Dismissed,
+        "remove" => ReportResolution::Removed,
+        "ban" => ReportResolution::Banned,
+        _ => return utils::error_redirect(QUEUE_PATH, "unknown action"),
+    };
+    let post = match stores.post_store.get_by_public_id(&data.post_id).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+    let comment = match &data.comment_id {
+        Some(comment_id) => match stores.comment_store.get_by_public_id(comment_id).await {
+            Ok(c) => Some(c),
+            Err(e) => return utils::redirect_entity_error(e, "comment"),
+        },
+        None => None,
+    };
+
+    let comment_id = comment.as_ref().map(|c| c.id);
+    let target = match &comment {
+        Some(c) => ModerationTarget::comment(c),
+        None => ModerationTarget::post(&post),
+    };
+    let mut log_actions = vec![];
+
+    if resolution == ReportResolution::Banned {
+        let author_id = match &comment {
+            Some(c) => c.author_id,
+            None => post.author_id,
+        };
+        let author = match stores.user_store.get_by_id(author_id).await {
+            Ok(u) => u,
+            Err(e) => return utils::redirect_entity_error(e, "user"),
+        };
+        if !permissions::can_ban(&moderator, &author, &admins) {
+            return utils::error_redirect(
+                QUEUE_PATH,
+                "you can only ban users with a lower role than yours",
+            );
+        }
+        if let Err(e) = stores.user_store.set_banned(author.id, true).await {
+            error!("Error banning user {}: {:?}", author.id, e);
+            return utils::error_redirect(QUEUE_PATH, "something went wrong, please try again");
+        }
+        warn!("🛡️ {} banned user {}", moderator.name, author.name);
+        log_actions.push((ModerationAction::BanUser, ModerationTarget::user(author.id)));
+    }
+
+    if resolution != ReportResolution::Dismissed {
+        let removed = match &comment {
+            Some(c) => stores
+                .comment_store
+                .set_removed(c.id, true)
+                .await
+                .map(|_| ()),
+            None => stores
+                .post_store
+                .set_removed(post.id, true)
+                .await
+                .map(|_| ()),
+        };
+        if let Err(e) = removed {
+            error!("Error removing reported item: {:?}", e);
+            return utils::error_redirect(QUEUE_PATH, "something went wrong, please try again");
+        }
+        let log_action = match comment {
+            Some(_) => ModerationAction::RemoveComment,
+            None => ModerationAction::RemovePost,
+        };
+        log_actions.push((log_action, target));
+    } else {
+        let is_held = match stores.report_store.is_held(post.id, comment_id).await {
+            Ok(h) => h,
+            Err(e) => return utils::redirect_entity_error(e, "reports"),
+        };
+        // Dismissing the spam filter's report on something it held is approving it
+        if is_held {
+            let published = match comment_id {
+                Some(comment_id) => {
+                    super::publish_held_comment(&stores, &comment_bus, post.id, comment_id).await
+                }
+                None => super::publish_held_post(&stores, &link_previewer, post.id).await,
+            };
+            if let Err(e) = published {
+                error!("Error publishing held item: {:?}", e);
+                return utils::error_redirect(QUEUE_PATH, "something went wrong, please try again");
+            }
+            log_actions.push((ModerationAction::ApproveHeld, target));
+        } else {
+            log_actions.push((ModerationAction::DismissReports, target));
+        }
+    }
+
+    match stores
+        .report_store
+        .resolve(post.id, comment_id, moderator.id, resolution)
+        .await
+    {
+        Ok(count) => {
+            warn!(
+                "🛡️ {} resolved {} reports on post {} comment {:?} as {}",
+                moderator.name, count, post.id, comment_id, resolution
+            );
+            for (log_action, log_target) in log_actions {
+                moderation_log::record(
+                    &stores,
+                    &moderator,
+                    log_action,
+                    log_target,
+                    &data.reason,
+                    Some(format!("{} {}", count, plural(count))),
+                )
+                .await;
+            }
+            let is_spam = resolution != ReportResolution::Dismissed;
+            match &comment {
+                Some(c) => spam::train_comment(&stores, c, is_spam).await,
+                None => spam::train_post(&stores, &post, is_spam).await,
+            }
+            utils::success_redirect(
+                QUEUE_PATH,
+                &format!("{} {} resolved as {}", count, plural(count), resolution),
+            )
+        }
+        Err(e) => {
+            error!("Error resolving reports: {:?}", e);
+            utils::error_redirect(QUEUE_PATH, "something went wrong, please try again")
+        }
+    }
+}
+
+async fn get_reporter(session: TypedSession, stores: &EntityStores) -> Result<User, HttpResponse> {
+    match user_context::get_auth_user_entity(session, stores).await {
+        Ok(u) => Ok(u),
+        Err(e) => {
+            error!("Error getting authenticated user: {:?}", e);
+            Err(utils::error_redirect(
+                "/login",
+                "you must be logged in to report posts and comments",
+            ))
+        }
+    }
+}
+
+async fn submit_report(
+    stores: &EntityStores,
+    reporter: &User,
+    post_id: u64,
+    comment_id: Option<u64>,
+    data: &ReportRequest,
+    location: &str,
+) -> HttpResponse {
+    let reason: ReportReason = match data.reason.parse() {
+        Ok(r) => r,
+        Err(()) => return utils::error_redirect(location, "unknown reason"),
+    };
+
+    match stores
+        .report_store
+        .insert(
+            Some(reporter.id),
+            post_id,
+            comment_id,
+            reason,
+            &data.details,
+        )
+        .await
+    {
+        Ok(_) => utils::success_redirect(
+            location,
+            "thanks for the report, a moderator will take a look",
+        ),
+        Err(EntityError::DuplicateKey) => {
+            utils::warning_redirect(location, "you've already reported this")
+        }
+        Err(EntityError::InvalidInput(field, message)) => {
+            utils::error_redirect(location, &format!("{} {}", field, message))
+        }
+        Err(e) => {
+            error!("Error inserting report: {:?}", e);
+            utils::error_redirect(location, "something went wrong, please try again")
+        }
+    }
+}
+
+fn plural(count: u64) -> &'static str {
+    match count {
+        1 => "report",
+        _ => "reports",
+    }
+}
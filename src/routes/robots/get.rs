@@ -0,0 +1,12 @@
+use actix_web::{web, HttpRequest, Responder};
+
+use crate::{routes::conditional, server::Robots};
+
+pub async fn robots(req: HttpRequest, robots: web::Data<Robots>) -> impl Responder {
+    conditional::conditional_response(
+        &req,
+        "text/plain; charset=utf-8",
+        robots.body.clone(),
+        robots.updated,
+    )
+}
@@ -0,0 +1,110 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        api_token::{ApiScope, ApiTokenStore, MAX_TOKEN_NAME_LENGTH},
+        block::BlockStore,
+        email_preference::{EmailFrequency, EmailPreferenceStore},
+        identity::IdentityStore,
+        two_factor::TwoFactorStore,
+        user::UserStore,
+        EntityError, EntityStores,
+    },
+    oauth::OAuthProviders,
+    routes::{
+        models::UserModel,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    totp::{self, Totp},
+};
+
+pub async fn settings(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: web::Data<EntityStores>,
+    providers: web::Data<OAuthProviders>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let mut user_context =
+        user_context::build(session, flash_messages, &stores, "settings", None).await;
+
+    let auth_user = match &user_context.auth_user {
+        Some(u) => u,
+        None => return utils::warning_redirect("/login", "you must be logged in to view settings"),
+    };
+    let user = match stores.user_store.get_by_public_id(&auth_user.id).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+
+    let context = &mut user_context.context;
+    match stores.two_factor_store.get_by_user_id(user.id).await {
+        Ok(two_factor) if two_factor.is_enabled => context.insert("two_factor_status", "enabled"),
+        Ok(two_factor) => {
+            context.insert("two_factor_status", "pending");
+            let uri = totp::provisioning_uri(&two_factor.secret, &user.name);
+            context.insert("two_factor_qr", &totp::qr::svg(&uri));
+            context.insert(
+                "two_factor_secret",
+                &Totp::new(&two_factor.secret).encoded_secret(),
+            );
+        }
+        Err(EntityError::NotFound) => context.insert("two_factor_status", "disabled"),
+        Err(e) => return utils::redirect_entity_error(e, "two-factor authentication"),
+    }
+
+    let identities = match stores.identity_store.get_by_user_id(user.id).await {
+        Ok(i) => i,
+        Err(e) => return utils::redirect_entity_error(e, "linked accounts"),
+    };
+    let (linked, unlinked): (Vec<_>, Vec<_>) = providers
+        .summaries()
+        .into_iter()
+        .partition(|p| identities.iter().any(|i| i.provider == p.name));
+    context.insert("linked_providers", &linked);
+    context.insert("unlinked_providers", &unlinked);
+
+    let email_preference = match stores.email_preference_store.get_by_user_id(user.id).await {
+        Ok(p) => p,
+        Err(e) => return utils::redirect_entity_error(e, "email preferences"),
+    };
+    context.insert("email_frequency", &email_preference.frequency);
+    context.insert("email_frequencies", &EmailFrequency::ALL);
+    context.insert("email_is_verified", &user.is_email_verified);
+
+    let api_tokens = match stores.api_token_store.get_by_user_id(user.id).await {
+        Ok(t) => t,
+        Err(e) => return utils::redirect_entity_error(e, "api tokens"),
+    };
+    context.insert("api_tokens", &api_tokens);
+    context.insert("api_scopes", &ApiScope::ALL);
+    context.insert("max_token_name_length", &MAX_TOKEN_NAME_LENGTH);
+
+    let blocks = match stores.block_store.get_by_user_id(user.id).await {
+        Ok(b) => b,
+        Err(e) => return utils::redirect_entity_error(e, "blocked users"),
+    };
+    let blocked_users = match stores
+        .user_store
+        .get_by_ids(&blocks.iter().map(|b| b.blocked_id).collect::<Vec<_>>())
+        .await
+    {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "blocked users"),
+    };
+    // In the order they were blocked, most recent first
+    let blocked_users: Vec<UserModel> = blocks
+        .iter()
+        .filter_map(|b| blocked_users.iter().find(|u| u.id == b.blocked_id))
+        .map(|u| UserModel::from(u.clone()))
+        .collect();
+    context.insert("blocked_users", &blocked_users);
+
+    // TODO: handle error
+    let rendered = tera.render("settings.html", &user_context.context).unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
@@ -0,0 +1,361 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use log::error;
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        api_token::{ApiScope, ApiToken, ApiTokenStore},
+        email_preference::{EmailFrequency, EmailPreferenceStore},
+        two_factor::TwoFactorStore,
+        user::User,
+        EntityError, EntityStores,
+    },
+    routes::{
+        rate_limited::{Login, RateLimited},
+        two_factor,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    totp,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CodeRequest {
+    code: Secret<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    name: String,
+    read: Option<String>,
+    post: Option<String>,
+    comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailPreferencesRequest {
+    frequency: EmailFrequency,
+}
+
+impl TokenRequest {
+    // Unchecked checkboxes aren't submitted at all
+    fn scopes(&self) -> Vec<ApiScope> {
+        ApiScope::ALL
+            .into_iter()
+            .filter(|scope| match scope {
+                ApiScope::Read => self.read.is_some(),
+                ApiScope::Post => self.post.is_some(),
+                ApiScope::Comment => self.comment.is_some(),
+            })
+            .collect()
+    }
+}
+
+pub async fn process_enroll(
+    session: TypedSession,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let user = match get_auth_user(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores
+        .two_factor_store
+        .begin_enrollment(user.id, &totp::generate_secret())
+        .await
+    {
+        Ok(_) => utils::redirect("/settings"),
+        Err(EntityError::DuplicateKey) => {
+            utils::warning_redirect("/settings", "two-factor authentication is already enabled")
+        }
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+pub async fn process_confirm(
+    _rate_limited: RateLimited<Login>,
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    data: web::Form<CodeRequest>,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let user = match get_auth_user(session.clone(), &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let two_factor = match stores.two_factor_store.get_by_user_id(user.id).await {
+        Ok(t) if !t.is_enabled => t,
+        Ok(_) => {
+            return utils::warning_redirect(
+                "/settings",
+                "two-factor authentication is already enabled",
+            )
+        }
+        Err(EntityError::NotFound) => {
+            return utils::warning_redirect(
+                "/settings",
+                "start setting up two-factor authentication first",
+            )
+        }
+        Err(e) => return settings_error_redirect(e),
+    };
+
+    match two_factor::verify_totp(&stores, &two_factor, data.code.expose_secret()).await {
+        Ok(true) => (),
+        Ok(false) => {
+            return utils::error_redirect(
+                "/settings",
+                "incorrect authentication code, check that your device's clock is correct and try again",
+            )
+        }
+        Err(e) => return settings_error_redirect(e),
+    }
+
+    match stores.two_factor_store.enable(user.id).await {
+        Ok(codes) => {
+            render_recovery_codes(
+                session,
+                flash_messages,
+                &stores,
+                &tera,
+                codes,
+                "two-factor authentication is now enabled",
+            )
+            .await
+        }
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+pub async fn process_recovery_codes(
+    _rate_limited: RateLimited<Login>,
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    data: web::Form<CodeRequest>,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let user = match get_auth_user(session.clone(), &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+    if let Err(response) = check_code(&stores, &user, data.code.expose_secret()).await {
+        return response;
+    }
+
+    match stores
+        .two_factor_store
+        .regenerate_recovery_codes(user.id)
+        .await
+    {
+        Ok(codes) => {
+            render_recovery_codes(
+                session,
+                flash_messages,
+                &stores,
+                &tera,
+                codes,
+                "your old recovery codes no longer work",
+            )
+            .await
+        }
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+pub async fn process_disable(
+    _rate_limited: RateLimited<Login>,
+    session: TypedSession,
+    data: web::Form<CodeRequest>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let user = match get_auth_user(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+    if let Err(response) = check_code(&stores, &user, data.code.expose_secret()).await {
+        return response;
+    }
+
+    match stores.two_factor_store.delete(user.id).await {
+        Ok(()) => utils::success_redirect("/settings", "two-factor authentication disabled"),
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+pub async fn process_create_token(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    data: web::Form<TokenRequest>,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let user = match get_auth_user(session.clone(), &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores
+        .api_token_store
+        .insert(user.id, &data.name, &data.scopes())
+        .await
+    {
+        Ok((api_token, token)) => {
+            render_api_token(session, flash_messages, &stores, &tera, api_token, token).await
+        }
+        Err(EntityError::InvalidInput(field, message)) => utils::error_redirect(
+            "/settings",
+            &format!("invalid token {}: {}", field, message),
+        ),
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+pub async fn process_revoke_token(
+    session: TypedSession,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let user = match get_auth_user(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores.api_token_store.revoke(user.id, &path).await {
+        Ok(()) => utils::success_redirect("/settings", "token revoked"),
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput(_, _)) => {
+            utils::warning_redirect(
+                "/settings",
+                "that token doesn't exist or was already revoked",
+            )
+        }
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+pub async fn process_email_preferences(
+    session: TypedSession,
+    data: web::Form<EmailPreferencesRequest>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let user = match get_auth_user(session, &stores).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores
+        .email_preference_store
+        .set_frequency(user.id, data.frequency)
+        .await
+    {
+        Ok(_) => utils::success_redirect("/settings", "email preferences saved"),
+        Err(e) => settings_error_redirect(e),
+    }
+}
+
+async fn get_auth_user(session: TypedSession, stores: &EntityStores) -> Result<User, HttpResponse> {
+    match user_context::get_auth_user_entity(session, stores).await {
+        Ok(u) => Ok(u),
+        Err(e) => {
+            error!("Error getting authenticated user: {:?}", e);
+            Err(utils::error_redirect(
+                "/login",
+                "you must be logged in to change settings",
+            ))
+        }
+    }
+}
+
+// Changes to an enabled second factor require a current code, so a hijacked session can't remove it
+async fn check_code(stores: &EntityStores, user: &User, code: &str) -> Result<(), HttpResponse> {
+    let two_factor = match stores.two_factor_store.get_by_user_id(user.id).await {
+        Ok(t) if t.is_enabled => t,
+        Ok(_) | Err(EntityError::NotFound) => {
+            return Err(utils::warning_redirect(
+                "/settings",
+                "two-factor authentication isn't enabled",
+            ))
+        }
+        Err(e) => return Err(settings_error_redirect(e)),
+    };
+
+    match two_factor::verify_code(stores, &two_factor, code).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(utils::error_redirect(
+            "/settings",
+            "incorrect authentication or recovery code",
+        )),
+        Err(e) => Err(settings_error_redirect(e)),
+    }
+}
+
+async fn render_recovery_codes(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: &EntityStores,
+    tera: &Tera,
+    codes: Vec<String>,
+    message: &str,
+) -> HttpResponse {
+    let mut user_context = user_context::build(
+        session,
+        flash_messages,
+        stores,
+        "settings - recovery codes",
+        None,
+    )
+    .await;
+
+    user_context.context.insert("recovery_codes", &codes);
+    user_context.context.insert("message", message);
+
+    // TODO: handle error
+    let rendered = tera
+        .render("recovery_codes.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
+
+async fn render_api_token(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: &EntityStores,
+    tera: &Tera,
+    api_token: ApiToken,
+    token: String,
+) -> HttpResponse {
+    let mut user_context = user_context::build(
+        session,
+        flash_messages,
+        stores,
+        "settings - new token",
+        None,
+    )
+    .await;
+
+    user_context.context.insert("api_token", &api_token);
+    user_context.context.insert("token", &token);
+
+    // TODO: handle error
+    let rendered = tera
+        .render("api_token.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
+
+fn settings_error_redirect(entity_error: EntityError) -> HttpResponse {
+    error!("Entity Error updating settings: {:?}", entity_error);
+
+    utils::error_redirect(
+        "/settings",
+        "something went wrong updating your settings, please try again",
+    )
+}
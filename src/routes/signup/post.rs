@@ -2,6 +2,7 @@ use actix_web::{web, HttpResponse, Responder};
 use actix_web_flash_messages::FlashMessage;
 use secrecy::Secret;
 use serde::Deserialize;
+use tera::Tera;
 
 use crate::{
     entities::{
@@ -11,9 +12,15 @@ use crate::{
         },
         EntityError, EntityStores,
     },
+    mailer::Mailer,
     routes::{
-        login::post::do_login_and_redirect, user_context::session_state::TypedSession, utils,
+        login::post::do_login_and_redirect,
+        rate_limited::{RateLimited, Signup},
+        user_context::session_state::TypedSession,
+        utils,
+        verify::post::send_verification_email,
     },
+    server::Environment,
 };
 
 #[derive(Debug, Deserialize)]
@@ -24,9 +31,13 @@ pub struct SignupRequest {
 }
 
 pub async fn process_signup(
+    _rate_limited: RateLimited<Signup>,
     session: TypedSession,
     data: web::Form<SignupRequest>,
     stores: web::Data<EntityStores>,
+    mailer: web::Data<dyn Mailer>,
+    tera: web::Data<Tera>,
+    env: web::Data<Environment>,
 ) -> impl Responder {
     let result = stores
         .user_store
@@ -34,8 +45,17 @@ pub async fn process_signup(
         .await;
 
     match result {
-        Ok(_) => {
+        Ok(user) => {
             FlashMessage::success("successfully signed up").send();
+            if send_verification_email(&stores, mailer.as_ref(), &tera, &env, &user).await {
+                FlashMessage::info("check your inbox for a link to verify your email address")
+                    .send();
+            } else {
+                FlashMessage::warning(
+                    "we couldn't send your verification email, you can request a new one from your user page",
+                )
+                .send();
+            }
             do_login_and_redirect(session, &stores, &data.username, &data.password).await
         }
         Err(entity_error) => signup_error_redirect(entity_error),
@@ -0,0 +1,196 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+
+use crate::{
+    entities::{post::PostStore, user::UserStore, EntityError, EntityStores},
+    routes::{conditional, utils},
+    server::Environment,
+};
+
+// Well under the protocol's limit of 50,000, so a page of posts is a reasonable thing to load and
+// cache at once
+const SITEMAP_SIZE: u32 = 10_000;
+const CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SitemapKind {
+    Posts,
+    Users,
+}
+
+impl SitemapKind {
+    fn path(&self) -> &'static str {
+        match self {
+            SitemapKind::Posts => "posts",
+            SitemapKind::Users => "users",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SitemapUrl {
+    loc: String,
+    lastmod: Option<DateTime<Utc>>,
+}
+
+/// Every post and user page, or an index of sitemap pages once there are too many for one file.
+pub async fn sitemap(
+    req: HttpRequest,
+    tera: web::Data<Tera>,
+    stores: web::Data<EntityStores>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let post_count = match stores.post_store.get_count().await {
+        Ok(c) => c as u64,
+        Err(e) => return utils::redirect_entity_error(e, "post"),
+    };
+    let user_count = match stores.user_store.get_count().await {
+        Ok(c) => c as u64,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+
+    // The home page takes up one url too
+    if post_count + user_count < SITEMAP_SIZE as u64 {
+        let mut urls = vec![SitemapUrl {
+            loc: format!("{}/", env.base_url()),
+            lastmod: None,
+        }];
+        match page_urls(&stores, &env, SitemapKind::Posts, 0).await {
+            Ok(u) => urls.extend(u),
+            Err(e) => return utils::redirect_entity_error(e, "post"),
+        }
+        match page_urls(&stores, &env, SitemapKind::Users, 0).await {
+            Ok(u) => urls.extend(u),
+            Err(e) => return utils::redirect_entity_error(e, "user"),
+        }
+
+        return render_urlset(&req, &tera, urls);
+    }
+
+    let mut sitemaps = vec![];
+    for (kind, count) in [
+        (SitemapKind::Posts, post_count),
+        (SitemapKind::Users, user_count),
+    ] {
+        for page in 0..count.div_ceil(SITEMAP_SIZE as u64) {
+            sitemaps.push(SitemapUrl {
+                loc: format!("{}/sitemap/{}/{}.xml", env.base_url(), kind.path(), page),
+                lastmod: None,
+            });
+        }
+    }
+
+    let mut context = Context::new();
+    context.insert("sitemaps", &sitemaps);
+
+    // TODO: handle error
+    let rendered = tera.render("sitemaps/index.xml", &context).unwrap();
+
+    // Nothing was loaded to date the index by, the etag still saves sending it again
+    conditional::conditional_response(&req, CONTENT_TYPE, rendered, Utc::now())
+}
+
+/// One page of post or user urls listed by the sitemap index.
+pub async fn sitemap_page(
+    req: HttpRequest,
+    path: web::Path<(SitemapKind, u64)>,
+    tera: web::Data<Tera>,
+    stores: web::Data<EntityStores>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let (kind, page) = path.into_inner();
+    let urls = match page_urls(&stores, &env, kind, page).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, kind.path()),
+    };
+    if urls.is_empty() {
+        return utils::redirect_entity_error(EntityError::NotFound, kind.path());
+    }
+
+    render_urlset(&req, &tera, urls)
+}
+
+fn render_urlset(req: &HttpRequest, tera: &Tera, urls: Vec<SitemapUrl>) -> HttpResponse {
+    let last_modified = urls
+        .iter()
+        .filter_map(|u| u.lastmod)
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let mut context = Context::new();
+    context.insert("urls", &urls);
+
+    // TODO: handle error
+    let rendered = tera.render("sitemaps/urlset.xml", &context).unwrap();
+
+    conditional::conditional_response(req, CONTENT_TYPE, rendered, last_modified)
+}
+
+async fn page_urls(
+    stores: &EntityStores,
+    env: &Environment,
+    kind: SitemapKind,
+    page: u64,
+) -> Result<Vec<SitemapUrl>, EntityError> {
+    Ok(match kind {
+        SitemapKind::Posts => stores
+            .post_store
+            .get_page(page, SITEMAP_SIZE)
+            .await?
+            .into_iter()
+            .map(|p| SitemapUrl {
+                loc: format!("{}/post/{}", env.base_url(), p.public_id),
+                lastmod: Some(p.updated),
+            })
+            .collect(),
+        SitemapKind::Users => stores
+            .user_store
+            .get_page(page, SITEMAP_SIZE)
+            .await?
+            .into_iter()
+            .map(|u| SitemapUrl {
+                loc: format!("{}/user/{}", env.base_url(), u.name),
+                lastmod: Some(u.updated),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_render_sitemaps() {
+        let tera = Tera::new("templates/**/*").unwrap();
+        let urls = vec![
+            SitemapUrl {
+                loc: "https://effward.dev/".to_owned(),
+                lastmod: None,
+            },
+            SitemapUrl {
+                loc: "https://effward.dev/user/fish&chips".to_owned(),
+                lastmod: Some(Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap()),
+            },
+        ];
+
+        let mut context = Context::new();
+        context.insert("urls", &urls);
+        let rendered = tera.render("sitemaps/urlset.xml", &context).unwrap();
+        assert!(rendered
+            .contains("<loc>https:&#x2F;&#x2F;effward.dev&#x2F;user&#x2F;fish&amp;chips</loc>"));
+        assert!(rendered.contains("<lastmod>2023-06-01T12:00:00+00:00</lastmod>"));
+        assert_eq!(rendered.matches("<lastmod>").count(), 1);
+
+        let mut context = Context::new();
+        context.insert("sitemaps", &urls[..1]);
+        let rendered = tera.render("sitemaps/index.xml", &context).unwrap();
+        assert!(rendered.contains("<sitemapindex"));
+        assert!(rendered.contains("<loc>https:&#x2F;&#x2F;effward.dev&#x2F;</loc>"));
+    }
+}
@@ -6,11 +6,18 @@ use log::error;
 use serde::Deserialize;
 
 use crate::{
-    entities::{post::PostStore, EntityStores},
+    entities::{post::PostStore, user::Permission, EntityStores},
     routes::{
-        user_context::{session_state::TypedSession, user_context},
+        bearer::{MaybeBearerUser, PostScope},
+        permissions,
+        rate_limited::{RateLimited, Submit},
+        reports,
+        user_context::{session_state::TypedSession, user_context, UserContextError},
         utils,
     },
+    server::{Admins, VerificationPolicy},
+    spam::SpamFilter,
+    unfurl::LinkPreviewer,
 };
 
 #[derive(Debug, Deserialize)]
@@ -20,31 +27,92 @@ pub struct SubmitRequest {
     content: Option<String>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn process_submission(
+    _rate_limited: RateLimited<Submit>,
+    bearer_user: MaybeBearerUser<PostScope>,
     session: TypedSession,
     data: Form<SubmitRequest>,
     stores: Data<EntityStores>,
+    verification_policy: Data<VerificationPolicy>,
+    link_previewer: Data<LinkPreviewer>,
+    admins: Data<Admins>,
+    spam_filter: Data<SpamFilter>,
 ) -> impl Responder {
-    match user_context::get_auth_user_entity(session, &stores).await {
-        Ok(auth_user_entity) => match stores
-            .post_store
-            .insert(&auth_user_entity.id, &data.title, &data.link, &data.content)
-            .await
-        {
-            Ok(post) => utils::success_redirect(
-                &format!("/post/{}", post.public_id),
-                "new post successfully submitted, it should appear momentarily...",
-            ),
-            Err(entity_error) => {
-                error!("Entity Error creating post: {:?}", entity_error);
+    let auth_user_entity = match bearer_user.0 {
+        Some(bearer_user) => user_context::check_verified(bearer_user.user, &verification_policy),
+        None => {
+            user_context::get_verified_auth_user_entity(session, &stores, &verification_policy)
+                .await
+        }
+    };
+
+    match auth_user_entity {
+        Ok(auth_user_entity) => {
+            // Moderators' own posts are never held
+            let held_score = match permissions::has_permission(
+                &auth_user_entity,
+                &admins,
+                Permission::RemoveContent,
+            ) {
+                true => None,
+                false => {
+                    spam_filter
+                        .check_post(&stores, &data.title, &data.link, &data.content)
+                        .await
+                }
+            };
 
-                // TODO: preserve form contents on redirect so that submission isn't lost
-                utils::warning_redirect(
-                    "/submit",
-                    "something went wrong submitting your post, please try again",
+            match stores
+                .post_store
+                .insert(
+                    &auth_user_entity.id,
+                    &data.title,
+                    &data.link,
+                    &data.content,
+                    held_score.is_some(),
                 )
+                .await
+            {
+                // A held post is previewed once a moderator approves it
+                Ok(post) => match held_score {
+                    Some(score) => {
+                        reports::hold(&stores, post.id, None, score).await;
+                        utils::warning_redirect(
+                            "/",
+                            "your post is being held for a moderator to review",
+                        )
+                    }
+                    None => {
+                        link_previewer.spawn(&stores, &post);
+                        utils::success_redirect(
+                            &format!("/post/{}", post.public_id),
+                            "new post successfully submitted, it should appear momentarily...",
+                        )
+                    }
+                },
+                Err(entity_error) => {
+                    error!("Entity Error creating post: {:?}", entity_error);
+
+                    // TODO: preserve form contents on redirect so that submission isn't lost
+                    utils::warning_redirect(
+                        "/submit",
+                        "something went wrong submitting your post, please try again",
+                    )
+                }
             }
-        },
+        }
+        Err(UserContextError::Unverified) => utils::warning_redirect(
+            "/submit",
+            "you must verify your email address before submitting posts",
+        ),
+        Err(UserContextError::Banned) => {
+            utils::error_redirect("/login", "this account has been banned")
+        }
+        Err(UserContextError::MustResetPassword) => utils::error_redirect(
+            "/login",
+            "you have to choose a new password, please log in again",
+        ),
         Err(e) => {
             error!("Error getting authenticated user: {:?}", e);
             utils::error_redirect("/login", "you must be logged in to submit posts")
@@ -0,0 +1,60 @@
+use chrono::Utc;
+
+use crate::{
+    entities::{
+        two_factor::{TwoFactor, TwoFactorStore},
+        EntityError, EntityStores,
+    },
+    totp::Totp,
+};
+
+pub async fn is_enabled(stores: &EntityStores, user_id: u64) -> Result<bool, EntityError> {
+    match stores.two_factor_store.get_by_user_id(user_id).await {
+        Ok(two_factor) => Ok(two_factor.is_enabled),
+        Err(EntityError::NotFound) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks a code from the user's authenticator app, marking it as used so it can't be replayed.
+pub async fn verify_totp(
+    stores: &EntityStores,
+    two_factor: &TwoFactor,
+    code: &str,
+) -> Result<bool, EntityError> {
+    let step = match Totp::new(&two_factor.secret).verify(code, Utc::now().timestamp() as u64) {
+        Some(s) => s,
+        None => return Ok(false),
+    };
+
+    match stores
+        .two_factor_store
+        .use_step(two_factor.user_id, step)
+        .await
+    {
+        Ok(()) => Ok(true),
+        Err(EntityError::InvalidInput(_, _)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Accepts either a code from the user's authenticator app or one of their unused recovery codes.
+pub async fn verify_code(
+    stores: &EntityStores,
+    two_factor: &TwoFactor,
+    code: &str,
+) -> Result<bool, EntityError> {
+    if verify_totp(stores, two_factor, code).await? {
+        return Ok(true);
+    }
+
+    match stores
+        .two_factor_store
+        .use_recovery_code(two_factor.user_id, code)
+        .await
+    {
+        Ok(()) => Ok(true),
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput(_, _)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,47 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use tera::Tera;
+
+use crate::{
+    entities::{email_preference::EmailPreferenceStore, EntityError, EntityStores},
+    routes::{
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+};
+
+/// Asks before unsubscribing, since link scanners open every link in an email.
+pub async fn unsubscribe(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let token = path.into_inner();
+    let preference = match stores
+        .email_preference_store
+        .get_by_unsubscribe_token(&token)
+        .await
+    {
+        Ok(p) => p,
+        Err(EntityError::NotFound) => {
+            return utils::error_redirect("/", "that unsubscribe link is invalid")
+        }
+        Err(e) => return utils::redirect_entity_error(e, "email preferences"),
+    };
+
+    let mut user_context =
+        user_context::build(session, flash_messages, &stores, "unsubscribe", None).await;
+    user_context
+        .context
+        .insert("frequency", &preference.frequency);
+    user_context.context.insert("token", &token);
+
+    // TODO: handle error
+    let rendered = tera
+        .render("unsubscribe.html", &user_context.context)
+        .unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
@@ -0,0 +1,12 @@
+pub mod get;
+pub mod post;
+
+use actix_web::HttpRequest;
+
+const UNSUBSCRIBE_PREFIX: &str = "/unsubscribe/";
+
+/// Mail clients unsubscribe by POSTing to the link from the email without a session, the token
+/// in the link is all the proof needed.
+pub fn is_unsubscribe_request(req: &HttpRequest) -> bool {
+    req.path().starts_with(UNSUBSCRIBE_PREFIX)
+}
@@ -0,0 +1,41 @@
+use actix_web::{web, Responder};
+
+use crate::{
+    entities::{
+        email_preference::{EmailFrequency, EmailPreferenceStore},
+        EntityError, EntityStores,
+    },
+    routes::utils,
+};
+
+/// Turns off all emails about replies, from the unsubscribe page or a mail client's one-click
+/// unsubscribe button.
+pub async fn process_unsubscribe(
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    let token = path.into_inner();
+    let preference = match stores
+        .email_preference_store
+        .get_by_unsubscribe_token(&token)
+        .await
+    {
+        Ok(p) => p,
+        Err(EntityError::NotFound) => {
+            return utils::error_redirect("/", "that unsubscribe link is invalid")
+        }
+        Err(e) => return utils::redirect_entity_error(e, "email preferences"),
+    };
+
+    match stores
+        .email_preference_store
+        .set_frequency(preference.user_id, EmailFrequency::Never)
+        .await
+    {
+        Ok(_) => utils::success_redirect(
+            &format!("/unsubscribe/{}", token),
+            "you won't get any more emails about replies",
+        ),
+        Err(e) => utils::redirect_entity_error(e, "email preferences"),
+    }
+}
@@ -1,22 +1,34 @@
 use actix_web::{web, HttpResponse, Responder};
 use actix_web_flash_messages::IncomingFlashMessages;
+use log::error;
 use tera::Tera;
 
 use crate::{
-    entities::{user::UserStore, EntityError, EntityStores},
+    entities::{
+        block::BlockStore,
+        moderation_log::ModerationLogFilter,
+        user::{Permission, Role, UserStore},
+        EntityError, EntityStores,
+    },
     routes::{
-        models::UserModel,
+        models::{PageMetadata, UserModel},
+        moderation_log, permissions, two_factor,
         user_context::{session_state::TypedSession, user_context},
         utils,
     },
+    server::{Admins, Environment},
 };
 
+const MODERATION_LOG_ENTRIES: u32 = 20;
+
 pub async fn user(
     session: TypedSession,
     flash_messages: IncomingFlashMessages,
     tera: web::Data<Tera>,
     path: web::Path<String>,
     stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    env: web::Data<Environment>,
 ) -> impl Responder {
     // TODO: handle errors
     let path_user = path.into_inner();
@@ -37,6 +49,19 @@ pub async fn user(
         },
     };
 
+    let user_id = user.id;
+    let is_email_verified = user.is_email_verified;
+    let user_role = admins.role_of(&user);
+    let is_banned = user.is_banned;
+    let is_shadowbanned = user.is_shadowbanned;
+    let must_reset_password = user.must_reset_password;
+    let auth_user_entity = user_context::get_auth_user_entity(session.clone(), &stores)
+        .await
+        .ok();
+    let can_ban = match &auth_user_entity {
+        Some(auth_user) => permissions::can_ban(auth_user, &user, &admins),
+        None => false,
+    };
     let user_model = UserModel::from(user);
 
     let mut user_context = user_context::build(
@@ -48,7 +73,71 @@ pub async fn user(
     )
     .await;
 
+    let is_own_page = match &user_context.auth_user {
+        Some(auth_user) => auth_user.id == user_model.id,
+        None => false,
+    };
+    if is_own_page {
+        user_context
+            .context
+            .insert("email_verified", &is_email_verified);
+    }
+
+    user_context.context.insert("user_role", &user_role);
+    user_context.context.insert("is_banned", &is_banned);
+
+    let can = |permission| match &auth_user_entity {
+        Some(auth_user) => {
+            !is_own_page && permissions::has_permission(auth_user, &admins, permission)
+        }
+        None => false,
+    };
+    user_context.context.insert("can_ban", &can_ban);
+    // Only the moderators who can lift it are told, least of all the user themselves
+    if can_ban {
+        user_context
+            .context
+            .insert("is_shadowbanned", &is_shadowbanned);
+    }
+    if let Some(auth_user) = auth_user_entity.as_ref().filter(|_| !is_own_page) {
+        match stores.block_store.is_blocked(auth_user.id, user_id).await {
+            Ok(is_blocked) => user_context.context.insert("is_blocked", &is_blocked),
+            Err(e) => error!("Error getting whether user is blocked: {:?}", e),
+        }
+    }
+    if can(Permission::ManageRoles) {
+        user_context.context.insert("roles", &Role::ALL);
+    }
+    if can(Permission::ResetPassword) {
+        user_context
+            .context
+            .insert("must_reset_password", &must_reset_password);
+    }
+    if can(Permission::ResetTwoFactor) {
+        match two_factor::is_enabled(&stores, user_id).await {
+            Ok(is_enabled) => user_context
+                .context
+                .insert("can_reset_two_factor", &is_enabled),
+            Err(e) => error!("Error getting user's two-factor authentication: {:?}", e),
+        }
+    }
+
+    if can(Permission::ViewModerationLog) {
+        let filter = ModerationLogFilter {
+            target_user_id: Some(user_id),
+            ..Default::default()
+        };
+        match moderation_log::get_entries(&stores, &filter, MODERATION_LOG_ENTRIES).await {
+            Ok(entries) => user_context.context.insert("moderation_entries", &entries),
+            Err(e) => error!("Error getting user's moderation log: {:?}", e),
+        }
+    }
+
     user_context.context.insert("user", &user_model);
+    user_context.context.insert(
+        "metadata",
+        &PageMetadata::for_user(&user_model, env.base_url()),
+    );
 
     // TODO: handle error
     let rendered = tera.render("user.html", &user_context.context).unwrap();
@@ -0,0 +1,444 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{error, warn};
+use serde::Deserialize;
+
+use crate::{
+    entities::{
+        block::BlockStore,
+        moderation_log::{ModerationAction, ModerationTarget},
+        two_factor::TwoFactorStore,
+        user::{Permission, Role, UserStore},
+        EntityError, EntityStores,
+    },
+    routes::{
+        admin::ADMIN_PATH,
+        moderation_log::{self, ModerationRequest},
+        permissions,
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Admins,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RoleRequest {
+    role: String,
+    reason: Option<String>,
+    return_to: Option<String>,
+}
+
+// Banned users are locked out, shadowbanned users carry on without anyone else seeing them
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BanKind {
+    Ban,
+    Shadowban,
+}
+
+pub async fn process_reset_two_factor(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    let path_user = path.into_inner();
+    let location = user_location(&path_user, &data.return_to);
+
+    let admin = match permissions::require_permission(
+        session,
+        &stores,
+        &admins,
+        Permission::ResetTwoFactor,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let user = match stores.user_store.get_by_public_id(&path_user).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+
+    match stores.two_factor_store.delete(user.id).await {
+        Ok(()) => {
+            warn!(
+                "🔑 Admin {} reset two-factor authentication for user {}",
+                admin.name, user.name
+            );
+            moderation_log::record(
+                &stores,
+                &admin,
+                ModerationAction::ResetTwoFactor,
+                ModerationTarget::user(user.id),
+                &data.reason,
+                None,
+            )
+            .await;
+            utils::success_redirect(
+                &location,
+                &format!("two-factor authentication reset for {}", user.name),
+            )
+        }
+        Err(e) => {
+            error!("Error resetting two-factor authentication: {:?}", e);
+            utils::error_redirect(
+                &location,
+                "something went wrong resetting two-factor authentication, please try again",
+            )
+        }
+    }
+}
+
+pub async fn process_force_password_reset(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    let path_user = path.into_inner();
+    let location = user_location(&path_user, &data.return_to);
+
+    let admin = match permissions::require_permission(
+        session,
+        &stores,
+        &admins,
+        Permission::ResetPassword,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let user = match stores.user_store.get_by_public_id(&path_user).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+    // Otherwise they'd be logged out of the dashboard they're using
+    if user.id == admin.id {
+        return utils::error_redirect(&location, "you can't force your own password reset");
+    }
+
+    match stores
+        .user_store
+        .set_must_reset_password(user.id, true)
+        .await
+    {
+        Ok(_) => {
+            warn!(
+                "🔑 Admin {} forced a password reset for user {}",
+                admin.name, user.name
+            );
+            moderation_log::record(
+                &stores,
+                &admin,
+                ModerationAction::ForcePasswordReset,
+                ModerationTarget::user(user.id),
+                &data.reason,
+                None,
+            )
+            .await;
+            utils::success_redirect(
+                &location,
+                &format!(
+                    "{} has been logged out and has to choose a new password",
+                    user.name
+                ),
+            )
+        }
+        Err(e) => {
+            error!("Error forcing user {}'s password reset: {:?}", user.id, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
+
+pub async fn process_ban(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    set_banned(session, &path, &data, &stores, &admins, BanKind::Ban, true).await
+}
+
+pub async fn process_unban(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    set_banned(session, &path, &data, &stores, &admins, BanKind::Ban, false).await
+}
+
+pub async fn process_shadowban(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    set_banned(
+        session,
+        &path,
+        &data,
+        &stores,
+        &admins,
+        BanKind::Shadowban,
+        true,
+    )
+    .await
+}
+
+pub async fn process_unshadowban(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<ModerationRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    set_banned(
+        session,
+        &path,
+        &data,
+        &stores,
+        &admins,
+        BanKind::Shadowban,
+        false,
+    )
+    .await
+}
+
+pub async fn process_block(
+    session: TypedSession,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    set_blocked(session, &path, &stores, true).await
+}
+
+pub async fn process_unblock(
+    session: TypedSession,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+) -> impl Responder {
+    set_blocked(session, &path, &stores, false).await
+}
+
+pub async fn process_set_role(
+    session: TypedSession,
+    path: web::Path<String>,
+    data: web::Form<RoleRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    let path_user = path.into_inner();
+    let location = user_location(&path_user, &data.return_to);
+
+    let admin = match permissions::require_permission(
+        session,
+        &stores,
+        &admins,
+        Permission::ManageRoles,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let role: Role = match data.role.parse() {
+        Ok(r) => r,
+        Err(()) => return utils::error_redirect(&location, "unknown role"),
+    };
+    let user = match stores.user_store.get_by_public_id(&path_user).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+    // Otherwise the last admin could demote themselves and leave no one to undo it
+    if user.id == admin.id {
+        return utils::error_redirect(&location, "you can't change your own role");
+    }
+
+    match stores.user_store.set_role(user.id, role).await {
+        Ok(_) => {
+            warn!("👑 Admin {} made {} a {}", admin.name, user.name, role);
+            moderation_log::record(
+                &stores,
+                &admin,
+                ModerationAction::ChangeRole,
+                ModerationTarget::user(user.id),
+                &data.reason,
+                Some(format!("{} to {}", user.role, role)),
+            )
+            .await;
+            utils::success_redirect(&location, &format!("{} is now a {}", user.name, role))
+        }
+        Err(e) => {
+            error!("Error setting user {}'s role: {:?}", user.id, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
+
+async fn set_banned(
+    session: TypedSession,
+    path_user: &str,
+    data: &ModerationRequest,
+    stores: &EntityStores,
+    admins: &Admins,
+    kind: BanKind,
+    is_banned: bool,
+) -> HttpResponse {
+    let location = user_location(path_user, &data.return_to);
+
+    let moderator = match permissions::require_permission(
+        session,
+        stores,
+        admins,
+        Permission::BanUser,
+        &location,
+    )
+    .await
+    {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    let user = match stores.user_store.get_by_public_id(path_user).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+    if !permissions::can_ban(&moderator, &user, admins) {
+        return utils::error_redirect(
+            &location,
+            "you can only ban users with a lower role than yours",
+        );
+    }
+
+    let (action, log_action, result) = match (kind, is_banned) {
+        (BanKind::Ban, true) => (
+            "banned",
+            ModerationAction::BanUser,
+            stores.user_store.set_banned(user.id, true).await,
+        ),
+        (BanKind::Ban, false) => (
+            "unbanned",
+            ModerationAction::UnbanUser,
+            stores.user_store.set_banned(user.id, false).await,
+        ),
+        (BanKind::Shadowban, true) => (
+            "shadowbanned",
+            ModerationAction::ShadowbanUser,
+            stores.user_store.set_shadowbanned(user.id, true).await,
+        ),
+        (BanKind::Shadowban, false) => (
+            "unshadowbanned",
+            ModerationAction::UnshadowbanUser,
+            stores.user_store.set_shadowbanned(user.id, false).await,
+        ),
+    };
+    match result {
+        Ok(_) => {
+            warn!("🛡️ {} {} user {}", moderator.name, action, user.name);
+            moderation_log::record(
+                stores,
+                &moderator,
+                log_action,
+                ModerationTarget::user(user.id),
+                &data.reason,
+                None,
+            )
+            .await;
+            utils::success_redirect(&location, &format!("{} {}", user.name, action))
+        }
+        Err(e) => {
+            error!("Error setting user {} {}: {:?}", user.id, action, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
+
+async fn set_blocked(
+    session: TypedSession,
+    path_user: &str,
+    stores: &EntityStores,
+    is_blocked: bool,
+) -> HttpResponse {
+    let location = format!("/user/{}", path_user);
+
+    let auth_user = match user_context::get_auth_user_entity(session, stores).await {
+        Ok(u) => u,
+        Err(e) => {
+            error!("Error getting authenticated user: {:?}", e);
+            return utils::error_redirect("/login", "you must be logged in to do that");
+        }
+    };
+    let user = match stores.user_store.get_by_public_id(path_user).await {
+        Ok(u) => u,
+        Err(e) => return utils::redirect_entity_error(e, "user"),
+    };
+    if user.id == auth_user.id {
+        return utils::error_redirect(&location, "you can't block yourself");
+    }
+
+    let (action, result) = match is_blocked {
+        true => (
+            "blocked",
+            stores
+                .block_store
+                .insert(auth_user.id, user.id)
+                .await
+                .map(|_| ()),
+        ),
+        false => (
+            "unblocked",
+            stores.block_store.delete(auth_user.id, user.id).await,
+        ),
+    };
+    match result {
+        // Already done, likely a double submit
+        Ok(()) | Err(EntityError::DuplicateKey) | Err(EntityError::NotFound) => {
+            utils::success_redirect(&location, &format!("{} {}", action, user.name))
+        }
+        Err(e) => {
+            error!("Error setting user {} {}: {:?}", user.id, action, e);
+            utils::error_redirect(&location, "something went wrong, please try again")
+        }
+    }
+}
+
+/// Where to go after acting on a user, their page unless the form came from the admin dashboard.
+/// Anything else is ignored, so a form can't send anyone off the site.
+fn user_location(path_user: &str, return_to: &Option<String>) -> String {
+    match return_to.as_deref() {
+        Some(r) if r == ADMIN_PATH || r.starts_with(&format!("{}?", ADMIN_PATH)) => r.to_owned(),
+        _ => format!("/user/{}", path_user),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_location() {
+        let location =
+            |return_to: Option<&str>| user_location("abc", &return_to.map(str::to_owned));
+
+        assert_eq!(location(None), "/user/abc");
+        assert_eq!(location(Some("/admin")), "/admin");
+        assert_eq!(location(Some("/admin?q=bob")), "/admin?q=bob");
+        assert_eq!(location(Some("/administrator")), "/user/abc");
+        assert_eq!(location(Some("https://example.com/admin")), "/user/abc");
+        assert_eq!(location(Some("//example.com")), "/user/abc");
+    }
+}
@@ -13,6 +13,12 @@ pub enum UserContextError {
     EntityError(EntityError),
     #[error("no authenticated user session")]
     NotAuthenticated,
+    #[error("authenticated user's email address is not verified")]
+    Unverified,
+    #[error("authenticated user is banned")]
+    Banned,
+    #[error("authenticated user has to reset their password")]
+    MustResetPassword,
 }
 
 impl std::convert::From<SessionGetError> for UserContextError {
@@ -1,15 +1,66 @@
 use actix_session::{Session, SessionExt, SessionGetError, SessionInsertError};
 use actix_web::dev::Payload;
 use actix_web::{FromRequest, HttpRequest};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::future::{ready, Ready};
+use uuid::Uuid;
 
+const PENDING_LOGIN_EXPIRY_MINUTES: i64 = 5;
+const OAUTH_STATE_EXPIRY_MINUTES: i64 = 10;
+
+/// A user who has entered their password but not yet their second factor, or who has logged in
+/// but still has to choose a new password.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PendingLogin {
+    pub user_id: String,
+    pub created: DateTime<Utc>,
+}
+
+/// An external login that's been started, kept until the provider redirects back.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OAuthState {
+    pub provider: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub created: DateTime<Utc>,
+}
+
+impl OAuthState {
+    pub fn is_expired(&self) -> bool {
+        Utc::now() - self.created > Duration::minutes(OAUTH_STATE_EXPIRY_MINUTES)
+    }
+}
+
+impl PendingLogin {
+    pub fn new(user_id: String) -> Self {
+        Self {
+            user_id,
+            created: Utc::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() - self.created > Duration::minutes(PENDING_LOGIN_EXPIRY_MINUTES)
+    }
+}
+
+#[derive(Clone)]
 pub struct TypedSession(Session);
 
 impl TypedSession {
     const USER_ID_KEY: &'static str = "user_id";
+    const CSRF_TOKEN_KEY: &'static str = "csrf_token";
+    const PENDING_LOGIN_KEY: &'static str = "pending_login";
+    const PENDING_PASSWORD_RESET_KEY: &'static str = "pending_password_reset";
+    const OAUTH_STATE_KEY: &'static str = "oauth_state";
 
+    /// Gives the session a new key when the user logs in, along with a new CSRF token so one seen
+    /// before logging in can't be used after.
     pub fn renew(&self) {
         self.0.renew();
+        self.0.remove(Self::CSRF_TOKEN_KEY);
     }
 
     pub fn insert_user_id(&self, user_id: String) -> Result<(), SessionInsertError> {
@@ -20,6 +71,75 @@ impl TypedSession {
         self.0.get(Self::USER_ID_KEY)
     }
 
+    pub fn insert_pending_login(&self, pending: &PendingLogin) -> Result<(), SessionInsertError> {
+        self.0.insert(Self::PENDING_LOGIN_KEY, pending)
+    }
+
+    /// Returns the pending login if there is one that hasn't expired.
+    pub fn get_pending_login(&self) -> Result<Option<PendingLogin>, SessionGetError> {
+        match self.0.get::<PendingLogin>(Self::PENDING_LOGIN_KEY)? {
+            Some(pending) if !pending.is_expired() => Ok(Some(pending)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn remove_pending_login(&self) {
+        self.0.remove(Self::PENDING_LOGIN_KEY);
+    }
+
+    pub fn insert_pending_password_reset(
+        &self,
+        pending: &PendingLogin,
+    ) -> Result<(), SessionInsertError> {
+        self.0.insert(Self::PENDING_PASSWORD_RESET_KEY, pending)
+    }
+
+    /// Returns the login waiting on a new password if there is one that hasn't expired.
+    pub fn get_pending_password_reset(&self) -> Result<Option<PendingLogin>, SessionGetError> {
+        match self
+            .0
+            .get::<PendingLogin>(Self::PENDING_PASSWORD_RESET_KEY)?
+        {
+            Some(pending) if !pending.is_expired() => Ok(Some(pending)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn remove_pending_password_reset(&self) {
+        self.0.remove(Self::PENDING_PASSWORD_RESET_KEY);
+    }
+
+    pub fn insert_oauth_state(&self, oauth_state: &OAuthState) -> Result<(), SessionInsertError> {
+        self.0.insert(Self::OAUTH_STATE_KEY, oauth_state)
+    }
+
+    /// Removes and returns the external login in progress, if it hasn't expired.
+    /// Each state can only be used once.
+    pub fn take_oauth_state(&self) -> Result<Option<OAuthState>, SessionGetError> {
+        let oauth_state = self.0.get::<OAuthState>(Self::OAUTH_STATE_KEY)?;
+        self.0.remove(Self::OAUTH_STATE_KEY);
+
+        match oauth_state {
+            Some(s) if !s.is_expired() => Ok(Some(s)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn get_csrf_token(&self) -> Result<Option<String>, SessionGetError> {
+        self.0.get(Self::CSRF_TOKEN_KEY)
+    }
+
+    /// Returns the session's CSRF token, generating and storing a new one if it doesn't have one yet.
+    pub fn get_or_insert_csrf_token(&self) -> Result<String, SessionInsertError> {
+        if let Ok(Some(token)) = self.get_csrf_token() {
+            return Ok(token);
+        }
+
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        self.0.insert(Self::CSRF_TOKEN_KEY, &token)?;
+        Ok(token)
+    }
+
     pub fn log_out(self) {
         self.0.purge()
     }
@@ -5,10 +5,12 @@ use tera::Context;
 
 use crate::{
     entities::{
+        notification::NotificationStore,
         user::{User, UserStore},
         EntityStores,
     },
-    routes::models::UserModel,
+    routes::models::{UserModel, Viewer},
+    server::{Admins, VerificationPolicy},
 };
 
 use super::{session_state::TypedSession, UserContextError};
@@ -62,6 +64,7 @@ pub async fn build(
 
     insert_title(&mut context, page_name);
     let flash_messages = insert_notifications(&mut context, flash_messages);
+    insert_csrf_token(&mut context, &session);
     let auth_user = insert_auth_user(&mut context, session, stores).await;
     insert_hero_bg_class(&mut context, image_path);
 
@@ -78,8 +81,45 @@ pub async fn get_auth_user_entity(
 ) -> Result<User, UserContextError> {
     match session.get_user_id()? {
         None => Err(UserContextError::NotAuthenticated),
-        Some(user_id) => Ok(stores.user_store.get_by_public_id(&user_id).await?),
+        Some(user_id) => {
+            let user = stores.user_store.get_by_public_id(&user_id).await?;
+            if user.is_banned {
+                return Err(UserContextError::Banned);
+            }
+            // Ends the sessions they had when an admin forced a password reset
+            if user.must_reset_password {
+                return Err(UserContextError::MustResetPassword);
+            }
+
+            Ok(user)
+        }
+    }
+}
+
+/// What the logged in user gets to see, or anyone's view when no one is logged in.
+pub async fn get_viewer(session: TypedSession, stores: &EntityStores, admins: &Admins) -> Viewer {
+    let user = get_auth_user_entity(session, stores).await.ok();
+
+    Viewer::load(stores, user.as_ref(), admins).await
+}
+
+pub async fn get_verified_auth_user_entity(
+    session: TypedSession,
+    stores: &EntityStores,
+    policy: &VerificationPolicy,
+) -> Result<User, UserContextError> {
+    let user = get_auth_user_entity(session, stores).await?;
+
+    check_verified(user, policy)
+}
+
+/// Passes the user through unless the policy requires a verified email address and theirs isn't.
+pub fn check_verified(user: User, policy: &VerificationPolicy) -> Result<User, UserContextError> {
+    if *policy == VerificationPolicy::Required && !user.is_email_verified {
+        return Err(UserContextError::Unverified);
     }
+
+    Ok(user)
 }
 
 fn insert_title(context: &mut Context, page_name: &str) {
@@ -119,6 +159,16 @@ fn insert_notifications(
     debugs
 }
 
+fn insert_csrf_token(context: &mut Context, session: &TypedSession) {
+    match session.get_or_insert_csrf_token() {
+        Ok(token) => context.insert("csrf_token", &token),
+        Err(e) => {
+            error!("Error inserting csrf token into session: {:?}", e);
+            context.insert("csrf_token", "");
+        }
+    }
+}
+
 async fn insert_auth_user(
     context: &mut Context,
     session: TypedSession,
@@ -126,12 +176,21 @@ async fn insert_auth_user(
 ) -> Option<UserModel> {
     match get_auth_user_entity(session, stores).await {
         Ok(auth_user_entity) => {
+            match stores
+                .notification_store
+                .get_unread_count(auth_user_entity.id)
+                .await
+            {
+                Ok(count) => context.insert("unread_notifications", &count),
+                Err(e) => error!("Error getting unread notifications: {:?}", e),
+            }
+
             let auth_user = UserModel::from(auth_user_entity);
             context.insert("auth_user", &auth_user);
             context.insert("is_auth", &true);
             Some(auth_user)
         }
-        Err(UserContextError::NotAuthenticated) => {
+        Err(UserContextError::NotAuthenticated) | Err(UserContextError::Banned) => {
             context.insert("is_auth", &false);
             None
         }
@@ -1,9 +1,37 @@
 use actix_web::http::header::LOCATION;
-use actix_web::HttpResponse;
+use actix_web::{web, HttpRequest, HttpResponse};
 use actix_web_flash_messages::FlashMessage;
 use log::error;
 
-use crate::entities::EntityError;
+use crate::{entities::EntityError, server::TrustedProxies};
+
+/// The client's address, taken from `X-Forwarded-For` only when a trusted proxy sent the request.
+pub fn get_client_ip(req: &HttpRequest) -> Option<String> {
+    let ip = match req.app_data::<web::Data<TrustedProxies>>() {
+        Some(proxies) => proxies.client_ip(req),
+        None => req.peer_addr().map(|addr| addr.ip()),
+    };
+
+    ip.map(|ip| ip.to_string())
+}
+
+/// The client's address for login lockouts, or nothing when the request came through a proxy
+/// that isn't trusted. Its address is shared by everyone behind it, so failures from a few
+/// clients would lock out the whole site.
+pub fn get_lockout_ip(req: &HttpRequest) -> Option<String> {
+    match req.app_data::<web::Data<TrustedProxies>>() {
+        Some(proxies) if proxies.is_untrusted_proxy(req) => None,
+        _ => get_client_ip(req),
+    }
+}
+
+pub fn readable_wait(secs: u64) -> String {
+    if secs < 60 {
+        format!("{} seconds", secs.max(1))
+    } else {
+        format!("{} minutes", secs.div_ceil(60))
+    }
+}
 
 pub fn redirect(location: &str) -> HttpResponse {
     HttpResponse::SeeOther()
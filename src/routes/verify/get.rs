@@ -0,0 +1,19 @@
+use actix_web::{web, Responder};
+
+use crate::{
+    entities::{user::UserStore, EntityError, EntityStores},
+    routes::utils,
+};
+
+pub async fn verify(path: web::Path<String>, stores: web::Data<EntityStores>) -> impl Responder {
+    let token = path.into_inner();
+
+    match stores.user_store.verify_email(&token).await {
+        Ok(_) => utils::success_redirect("/", "email address successfully verified"),
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput(_, _)) => utils::error_redirect(
+            "/",
+            "verification link is invalid or has expired, request a new one from your user page",
+        ),
+        Err(entity_error) => utils::redirect_entity_error(entity_error, "email verification"),
+    }
+}
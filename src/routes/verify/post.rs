@@ -0,0 +1,107 @@
+use actix_web::{web, Responder};
+use log::error;
+use tera::{Context, Tera};
+
+use crate::{
+    entities::{
+        email::EmailStore,
+        user::{User, UserStore, VERIFICATION_TOKEN_EXPIRY_HOURS},
+        EntityStores,
+    },
+    mailer::{EmailMessage, Mailer},
+    routes::{
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Environment,
+};
+
+const EMAIL_TEMPLATE: &str = "email/verify.txt";
+const EMAIL_SUBJECT: &str = "verify your effward.dev email address";
+
+pub async fn process_resend(
+    session: TypedSession,
+    stores: web::Data<EntityStores>,
+    mailer: web::Data<dyn Mailer>,
+    tera: web::Data<Tera>,
+    env: web::Data<Environment>,
+) -> impl Responder {
+    let user = match user_context::get_auth_user_entity(session, &stores).await {
+        Ok(u) => u,
+        Err(e) => {
+            error!("Error getting authenticated user: {:?}", e);
+            return utils::error_redirect("/login", "you must be logged in to verify your email");
+        }
+    };
+
+    let user_page = format!("/user/{}", user.public_id);
+    if user.is_email_verified {
+        return utils::success_redirect(&user_page, "your email address is already verified");
+    }
+
+    if send_verification_email(&stores, mailer.as_ref(), &tera, &env, &user).await {
+        utils::success_redirect(&user_page, "verification email sent, check your inbox")
+    } else {
+        utils::warning_redirect(
+            &user_page,
+            "something went wrong sending your verification email, please try again",
+        )
+    }
+}
+
+/// Creates a new verification token for the user's email address and mails them a link to it.
+/// Errors are logged rather than returned, since callers only need to know whether it was sent.
+pub async fn send_verification_email(
+    stores: &EntityStores,
+    mailer: &dyn Mailer,
+    tera: &Tera,
+    env: &Environment,
+    user: &User,
+) -> bool {
+    let email = match stores.email_store.get_by_id(user.email_id).await {
+        Ok(e) => e,
+        Err(e) => {
+            error!("Error getting email for verification: {:?}", e);
+            return false;
+        }
+    };
+
+    let token = match stores
+        .user_store
+        .create_email_verification_token(user.id)
+        .await
+    {
+        Ok(t) => t,
+        Err(e) => {
+            error!("Error creating verification token: {:?}", e);
+            return false;
+        }
+    };
+
+    let mut context = Context::new();
+    context.insert("name", &user.name);
+    context.insert("link", &format!("{}/verify/{}", env.base_url(), token));
+    context.insert("expiry_hours", &VERIFICATION_TOKEN_EXPIRY_HOURS);
+
+    let message = match EmailMessage::render(
+        tera,
+        EMAIL_TEMPLATE,
+        &context,
+        &email.address,
+        EMAIL_SUBJECT,
+    ) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("Error rendering verification email: {:?}", e);
+            return false;
+        }
+    };
+
+    match mailer.send(&message).await {
+        Ok(_) => true,
+        Err(e) => {
+            error!("Error sending verification email: {:?}", e);
+            false
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use serde::Serialize;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        webhook::{Webhook, WebhookDelivery, WebhookEvent, WebhookStore, MAX_URL_LENGTH},
+        EntityStores,
+    },
+    routes::{
+        user_context::{session_state::TypedSession, user_context},
+        utils,
+    },
+    server::Admins,
+};
+
+const DELIVERIES_PER_WEBHOOK: u8 = 20;
+
+#[derive(Serialize)]
+struct WebhookSummary {
+    webhook: Webhook,
+    deliveries: Vec<WebhookDelivery>,
+}
+
+pub async fn webhooks(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    if let Err(response) = super::get_admin(session.clone(), &stores, &admins).await {
+        return response;
+    }
+
+    render_webhooks(session, flash_messages, &stores, &tera, None).await
+}
+
+/// The webhooks page, showing a new webhook's secret once after it's created.
+pub async fn render_webhooks(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    stores: &EntityStores,
+    tera: &Tera,
+    new_webhook: Option<&Webhook>,
+) -> HttpResponse {
+    let mut user_context =
+        user_context::build(session, flash_messages, stores, "webhooks", None).await;
+
+    let webhooks = match stores.webhook_store.get_all().await {
+        Ok(w) => w,
+        Err(e) => return utils::redirect_entity_error(e, "webhooks"),
+    };
+    let mut summaries = vec![];
+    for webhook in webhooks {
+        let deliveries = match stores
+            .webhook_store
+            .get_deliveries_by_webhook_id(webhook.id, DELIVERIES_PER_WEBHOOK)
+            .await
+        {
+            Ok(d) => d,
+            Err(e) => return utils::redirect_entity_error(e, "webhook deliveries"),
+        };
+        summaries.push(WebhookSummary {
+            webhook,
+            deliveries,
+        });
+    }
+
+    let context = &mut user_context.context;
+    context.insert("webhooks", &summaries);
+    context.insert("webhook_events", &WebhookEvent::ALL);
+    context.insert("max_url_length", &MAX_URL_LENGTH);
+    if let Some(webhook) = new_webhook {
+        context.insert("new_webhook", webhook);
+    }
+
+    // TODO: handle error
+    let rendered = tera.render("webhooks.html", &user_context.context).unwrap();
+
+    HttpResponse::Ok().body(rendered)
+}
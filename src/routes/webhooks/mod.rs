@@ -0,0 +1,21 @@
+pub mod get;
+pub mod post;
+
+use actix_web::HttpResponse;
+
+use crate::{
+    entities::{
+        user::{Permission, User},
+        EntityStores,
+    },
+    routes::{permissions, user_context::session_state::TypedSession},
+    server::Admins,
+};
+
+async fn get_admin(
+    session: TypedSession,
+    stores: &EntityStores,
+    admins: &Admins,
+) -> Result<User, HttpResponse> {
+    permissions::require_permission(session, stores, admins, Permission::ManageWebhooks, "/").await
+}
@@ -0,0 +1,89 @@
+use actix_web::{web, Responder};
+use actix_web_flash_messages::IncomingFlashMessages;
+use log::warn;
+use serde::Deserialize;
+use tera::Tera;
+
+use crate::{
+    entities::{
+        webhook::{WebhookEvent, WebhookStore},
+        EntityError, EntityStores,
+    },
+    routes::{user_context::session_state::TypedSession, utils},
+    server::Admins,
+};
+
+use super::get::render_webhooks;
+
+const WEBHOOKS_PATH: &str = "/admin/webhooks";
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookRequest {
+    url: String,
+    #[serde(rename = "post.created")]
+    post_created: Option<String>,
+    #[serde(rename = "comment.created")]
+    comment_created: Option<String>,
+}
+
+impl WebhookRequest {
+    // Unchecked checkboxes aren't submitted at all
+    fn events(&self) -> Vec<WebhookEvent> {
+        WebhookEvent::ALL
+            .into_iter()
+            .filter(|event| match event {
+                WebhookEvent::PostCreated => self.post_created.is_some(),
+                WebhookEvent::CommentCreated => self.comment_created.is_some(),
+            })
+            .collect()
+    }
+}
+
+pub async fn process_create(
+    session: TypedSession,
+    flash_messages: IncomingFlashMessages,
+    data: web::Form<WebhookRequest>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+    tera: web::Data<Tera>,
+) -> impl Responder {
+    let admin = match super::get_admin(session.clone(), &stores, &admins).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores.webhook_store.insert(&data.url, &data.events()).await {
+        Ok(webhook) => {
+            warn!("🪝 Admin {} added a webhook to {}", admin.name, webhook.url);
+            render_webhooks(session, flash_messages, &stores, &tera, Some(&webhook)).await
+        }
+        Err(EntityError::InvalidInput(field, message)) => utils::error_redirect(
+            WEBHOOKS_PATH,
+            &format!("invalid webhook {}: {}", field, message),
+        ),
+        Err(e) => utils::redirect_entity_error(e, "webhook"),
+    }
+}
+
+pub async fn process_delete(
+    session: TypedSession,
+    path: web::Path<String>,
+    stores: web::Data<EntityStores>,
+    admins: web::Data<Admins>,
+) -> impl Responder {
+    let admin = match super::get_admin(session, &stores, &admins).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+
+    match stores.webhook_store.delete(&path).await {
+        Ok(()) => {
+            warn!("🪝 Admin {} deleted webhook {}", admin.name, path);
+            utils::success_redirect(WEBHOOKS_PATH, "webhook deleted")
+        }
+        Err(EntityError::NotFound) | Err(EntityError::InvalidInput(_, _)) => {
+            utils::warning_redirect(WEBHOOKS_PATH, "that webhook doesn't exist")
+        }
+        Err(e) => utils::redirect_entity_error(e, "webhook"),
+    }
+}
@@ -0,0 +1,46 @@
+use std::{collections::HashSet, env};
+
+use log::warn;
+
+use crate::entities::user::{Role, User};
+
+/// Users who are always admins, whatever role is stored for them. Used to bootstrap a new site,
+/// since only admins can hand out roles.
+#[derive(Clone, Debug, Default)]
+pub struct Admins {
+    names: HashSet<String>,
+}
+
+impl Admins {
+    pub fn new(names: &str) -> Self {
+        Self {
+            names: names
+                .split(',')
+                .map(|n| n.trim().to_lowercase())
+                .filter(|n| !n.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_admin(&self, name: &str) -> bool {
+        self.names.contains(&name.to_lowercase())
+    }
+
+    /// The role the user acts with.
+    pub fn role_of(&self, user: &User) -> Role {
+        match self.is_admin(&user.name) {
+            true => Role::Admin,
+            false => user.role,
+        }
+    }
+}
+
+pub fn init_admins() -> Admins {
+    let admins = match env::var("ADMIN_USERS") {
+        Ok(names) => Admins::new(&names),
+        Err(_) => Admins::default(),
+    };
+
+    warn!("👑 Admins: {:?}", admins.names);
+    admins
+}
@@ -11,12 +11,20 @@ use dotenv::dotenv;
 use log::warn;
 
 use crate::entities::EntityStores;
+use crate::graphql::build_schema;
 use crate::routes::{
-    comment, error, health, index, login, logout, post, posts, signup, submit, user,
+    admin, api, bearer, comment, csrf, error, feed, graphql, health, index, login, logout,
+    moderation_log, notifications, oauth, post, posts, rate_limited, reports, robots, settings,
+    signup, sitemap, submit, unsubscribe, user, verify, webhooks,
 };
 use crate::server::{
-    db::init_db, environment::Environment, flash_messages::init_flash_messages, redis::init_redis,
-    session::init_session_store, tera::init_tera,
+    admins::init_admins, comment_bus::init_comment_bus, db::init_db, digest::init_digest_job,
+    environment::Environment, flash_messages::init_flash_messages,
+    link_previewer::init_link_previewer, mailer::init_mailer, oauth::init_oauth_providers,
+    rate_limit::init_rate_limiter, redis::init_redis, robots::init_robots,
+    session::init_session_store, spam_filter::init_spam_filter, tera::init_tera,
+    trusted_proxies::init_trusted_proxies, verification_policy::init_verification_policy,
+    webhooks::init_webhook_dispatcher,
 };
 
 use super::ServerError;
@@ -40,11 +48,28 @@ impl Application {
 
         warn!("🖕 Starting effward-dev dependencies...");
         let db_pool = init_db(db_url).await?;
-        let _redis_client = init_redis(redis_uri).await?;
+        let redis_client = init_redis(redis_uri).await?;
         let tera = init_tera()?;
         let flash_messages = init_flash_messages(secret_key.clone());
         let session_store = init_session_store(redis_uri).await?;
         let entity_stores = EntityStores::new(db_pool.clone());
+        let mailer = init_mailer()?;
+        let verification_policy = init_verification_policy()?;
+        let spam_filter = init_spam_filter()?;
+        let admins = init_admins();
+        let trusted_proxies = init_trusted_proxies();
+        let oauth_providers = init_oauth_providers(&env).await?;
+        let rate_limiter = init_rate_limiter(redis_client)?;
+        let graphql_schema = build_schema(entity_stores.clone());
+        let robots = init_robots(&env);
+        let link_previewer = init_link_previewer();
+        let comment_bus = init_comment_bus(redis_uri).await?;
+        if let Some(digest_job) = init_digest_job(&env, &entity_stores, mailer.clone(), &tera) {
+            digest_job.spawn();
+        }
+        if let Some(webhook_dispatcher) = init_webhook_dispatcher(&env, &entity_stores) {
+            webhook_dispatcher.spawn();
+        }
         warn!("🖕 Finished starting effward-dev dependencies.");
 
         warn!("🚀 Starting HttpServer...");
@@ -65,6 +90,7 @@ impl Application {
             }
 
             App::new()
+                .wrap(csrf::CsrfProtection)
                 .wrap(Compress::default())
                 .wrap(cors)
                 .wrap(
@@ -73,6 +99,12 @@ impl Application {
                         .handler(
                             StatusCode::NOT_FOUND,
                             error::not_found::get::render_not_found,
+                        )
+                        .handler(StatusCode::UNAUTHORIZED, bearer::render_auth_error)
+                        .handler(StatusCode::FORBIDDEN, bearer::render_auth_error)
+                        .handler(
+                            StatusCode::TOO_MANY_REQUESTS,
+                            rate_limited::render_too_many_requests,
                         ),
                 )
                 .wrap(flash_messages.clone())
@@ -86,23 +118,207 @@ impl Application {
                 .route("/signup", web::post().to(signup::post::process_signup))
                 .route("/login", web::get().to(login::get::login))
                 .route("/login", web::post().to(login::post::process_login))
+                .route("/login/2fa", web::get().to(login::get::two_factor))
+                .route(
+                    "/login/2fa",
+                    web::post().to(login::post::process_two_factor),
+                )
+                .route("/login/password", web::get().to(login::get::password_reset))
+                .route(
+                    "/login/password",
+                    web::post().to(login::post::process_password_reset),
+                )
+                .route(
+                    "/login/oauth/{provider}",
+                    web::get().to(oauth::get::authorize),
+                )
+                .route(
+                    "/login/oauth/{provider}/callback",
+                    web::get().to(oauth::get::callback),
+                )
                 .route("/logout", web::post().to(logout::post::process_logout))
                 .route("/comment", web::post().to(comment::post::process_comment))
+                .route(
+                    "/comment/{comment}/remove",
+                    web::post().to(comment::post::process_remove),
+                )
+                .route(
+                    "/comment/{comment}/restore",
+                    web::post().to(comment::post::process_restore),
+                )
+                .route(
+                    "/comment/{comment}/report",
+                    web::post().to(reports::post::process_report_comment),
+                )
                 .route("/submit", web::get().to(submit::get::submit))
                 .route("/submit", web::post().to(submit::post::process_submission))
                 .route("/user/{user}", web::get().to(user::get::user))
+                .route("/user/{user}/feed.xml", web::get().to(feed::get::user))
+                .route(
+                    "/user/{user}/2fa/reset",
+                    web::post().to(user::post::process_reset_two_factor),
+                )
+                .route("/user/{user}/ban", web::post().to(user::post::process_ban))
+                .route(
+                    "/user/{user}/password-reset",
+                    web::post().to(user::post::process_force_password_reset),
+                )
+                .route(
+                    "/user/{user}/unban",
+                    web::post().to(user::post::process_unban),
+                )
+                .route(
+                    "/user/{user}/shadowban",
+                    web::post().to(user::post::process_shadowban),
+                )
+                .route(
+                    "/user/{user}/unshadowban",
+                    web::post().to(user::post::process_unshadowban),
+                )
+                .route(
+                    "/user/{user}/block",
+                    web::post().to(user::post::process_block),
+                )
+                .route(
+                    "/user/{user}/unblock",
+                    web::post().to(user::post::process_unblock),
+                )
+                .route(
+                    "/user/{user}/role",
+                    web::post().to(user::post::process_set_role),
+                )
+                .route(
+                    "/notifications",
+                    web::get().to(notifications::get::notifications),
+                )
+                .route(
+                    "/notifications/read",
+                    web::post().to(notifications::post::process_read_all),
+                )
+                .route(
+                    "/notifications/{notification}/read",
+                    web::post().to(notifications::post::process_read),
+                )
+                .route("/settings", web::get().to(settings::get::settings))
+                .service(
+                    scope("/settings/2fa")
+                        .route("/enroll", web::post().to(settings::post::process_enroll))
+                        .route("/confirm", web::post().to(settings::post::process_confirm))
+                        .route(
+                            "/recovery-codes",
+                            web::post().to(settings::post::process_recovery_codes),
+                        )
+                        .route("/disable", web::post().to(settings::post::process_disable)),
+                )
+                .route(
+                    "/settings/email",
+                    web::post().to(settings::post::process_email_preferences),
+                )
+                .service(
+                    scope("/settings/tokens")
+                        .route("", web::post().to(settings::post::process_create_token))
+                        .route(
+                            "/{token}/revoke",
+                            web::post().to(settings::post::process_revoke_token),
+                        ),
+                )
+                .route("/admin", web::get().to(admin::get::dashboard))
+                .service(
+                    scope("/admin/webhooks")
+                        .route("", web::get().to(webhooks::get::webhooks))
+                        .route("", web::post().to(webhooks::post::process_create))
+                        .route(
+                            "/{webhook}/delete",
+                            web::post().to(webhooks::post::process_delete),
+                        ),
+                )
+                .route(
+                    "/admin/moderation-log",
+                    web::get().to(moderation_log::get::log),
+                )
+                .service(
+                    scope("/mod/queue")
+                        .route("", web::get().to(reports::get::queue))
+                        .route("/resolve", web::post().to(reports::post::process_resolve)),
+                )
+                .service(scope("/api/v1").configure(api::v1::configure))
+                .service(
+                    web::resource("/graphql")
+                        .app_data(
+                            web::JsonConfig::default().error_handler(graphql::post::json_error),
+                        )
+                        .route(web::post().to(graphql::post::graphql)),
+                )
                 .route("/post/{post}", web::get().to(post::get::post))
+                .route(
+                    "/post/{post}/remove",
+                    web::post().to(post::post::process_remove),
+                )
+                .route(
+                    "/post/{post}/restore",
+                    web::post().to(post::post::process_restore),
+                )
+                .route(
+                    "/post/{post}/lock",
+                    web::post().to(post::post::process_lock),
+                )
+                .route(
+                    "/post/{post}/unlock",
+                    web::post().to(post::post::process_unlock),
+                )
+                .route(
+                    "/post/{post}/report",
+                    web::post().to(reports::post::process_report_post),
+                )
+                .route(
+                    "/post/{post}/comments/live",
+                    web::get().to(post::get::comment_events),
+                )
+                .route(
+                    "/post/{post}/comments.xml",
+                    web::get().to(feed::get::comments),
+                )
+                .route("/feed.xml", web::get().to(feed::get::posts))
                 .route("/posts", web::get().to(posts::get::posts))
+                .route("/verify", web::post().to(verify::post::process_resend))
+                .route("/verify/{token}", web::get().to(verify::get::verify))
+                .route(
+                    "/unsubscribe/{token}",
+                    web::get().to(unsubscribe::get::unsubscribe),
+                )
+                .route(
+                    "/unsubscribe/{token}",
+                    web::post().to(unsubscribe::post::process_unsubscribe),
+                )
                 .route("/health", web::get().to(health::get::health))
+                .route("/robots.txt", web::get().to(robots::get::robots))
+                .route("/sitemap.xml", web::get().to(sitemap::get::sitemap))
+                .route(
+                    "/sitemap/{kind}/{page}.xml",
+                    web::get().to(sitemap::get::sitemap_page),
+                )
                 .service(
                     scope("/error")
                         .route("/404", web::get().to(error::not_found::get::not_found))
+                        .route("/csrf", web::get().to(error::csrf::get::csrf))
                         .route("/generic", web::get().to(error::generic::get::generic)),
                 )
                 .service(Files::new("/static", "public").show_files_listing())
                 .app_data(web::Data::new(db_pool.clone()))
                 .app_data(web::Data::new(tera.clone()))
                 .app_data(web::Data::new(entity_stores.clone()))
+                .app_data(web::Data::new(env.clone()))
+                .app_data(web::Data::new(verification_policy))
+                .app_data(web::Data::new(spam_filter.clone()))
+                .app_data(web::Data::new(admins.clone()))
+                .app_data(web::Data::new(trusted_proxies.clone()))
+                .app_data(web::Data::new(oauth_providers.clone()))
+                .app_data(web::Data::from(mailer.clone()))
+                .app_data(web::Data::new(rate_limiter.clone()))
+                .app_data(web::Data::new(graphql_schema.clone()))
+                .app_data(web::Data::new(robots.clone()))
+                .app_data(web::Data::new(link_previewer.clone()))
+                .app_data(web::Data::new(comment_bus.clone()))
         })
         .bind(("0.0.0.0", port))?
         .run();
@@ -0,0 +1,24 @@
+use std::env;
+
+use log::warn;
+
+use crate::live::CommentBus;
+
+use super::ServerError;
+
+pub async fn init_comment_bus(redis_uri: &str) -> Result<CommentBus, ServerError> {
+    let backend = env::var("LIVE_COMMENTS_BACKEND").unwrap_or("memory".to_owned());
+    let bus = match backend.to_lowercase().as_str() {
+        "memory" => CommentBus::new(),
+        "redis" => CommentBus::with_redis(redis::Client::open(redis_uri)?).await?,
+        _ => {
+            return Err(ServerError::RedisInit(format!(
+                "💬🔥 unknown LIVE_COMMENTS_BACKEND '{}', set it to memory or redis",
+                backend
+            )))
+        }
+    };
+    warn!("💬 Live comments backend: {}", backend);
+
+    Ok(bus)
+}
@@ -3,12 +3,14 @@ use sqlx::{mysql::MySqlPoolOptions, MySqlPool};
 
 use super::ServerError;
 
+pub const MAX_DB_CONNECTIONS: u32 = 10;
+
 pub async fn init_db(db_url: &str) -> Result<MySqlPool, ServerError> {
     let db_server = get_server(db_url)?;
 
     warn!("📚 Connecting to MySQL DB: {}", db_server);
     Ok(MySqlPoolOptions::new()
-        .max_connections(10)
+        .max_connections(MAX_DB_CONNECTIONS)
         .connect(db_url)
         .await?)
 }
@@ -0,0 +1,40 @@
+use std::{env, sync::Arc, time::Duration};
+
+use log::warn;
+use tera::Tera;
+
+use crate::{digest::DigestJob, entities::EntityStores, mailer::Mailer};
+
+use super::Environment;
+
+const DIGEST_INTERVAL: Duration = Duration::from_secs(60);
+
+pub fn init_digest_job(
+    env: &Environment,
+    stores: &EntityStores,
+    mailer: Arc<dyn Mailer>,
+    tera: &Tera,
+) -> Option<DigestJob> {
+    match env::var("EMAIL_DIGESTS")
+        .map(|v| v.to_lowercase())
+        .as_deref()
+    {
+        Ok("off") => {
+            warn!("📬 Reply emails and digests are off");
+            None
+        }
+        _ => {
+            warn!(
+                "📬 Checking for reply emails and digests to send every {:?}",
+                DIGEST_INTERVAL
+            );
+            Some(DigestJob::new(
+                stores.clone(),
+                mailer,
+                tera.clone(),
+                env.base_url(),
+                DIGEST_INTERVAL,
+            ))
+        }
+    }
+}
@@ -20,3 +20,12 @@ impl FromStr for Environment {
         }
     }
 }
+
+impl Environment {
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            Environment::Development => "http://localhost:8080",
+            Environment::Production => "https://effward.dev",
+        }
+    }
+}
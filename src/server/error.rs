@@ -1,15 +1,29 @@
 use redis::RedisError;
 
+use crate::mailer::MailerError;
+
 #[derive(thiserror::Error, Debug, Clone)]
 pub enum ServerError {
     #[error("Unknown error")]
     Unknown(String),
     #[error("Unknown environment error, set EFFWARD_DEV_ENVIRONMENT to prod or dev")]
     Environment,
+    #[error(
+        "Unknown email verification policy, set EMAIL_VERIFICATION_POLICY to optional or required"
+    )]
+    VerificationPolicy,
+    #[error("Invalid spam threshold, set SPAM_THRESHOLD to a number above 0 and at most 1")]
+    SpamThreshold,
     #[error("Database initialization error")]
     DatabaseInit(String),
     #[error("Redis initialization error")]
     RedisInit(String),
+    #[error("Mailer initialization error")]
+    MailerInit(String),
+    #[error("OAuth provider initialization error")]
+    OAuthInit(String),
+    #[error("Rate limiter initialization error")]
+    RateLimitInit(String),
     #[error("Tera initialization error")]
     TeraInit(String),
     #[error("HttpServer initialization error")]
@@ -47,3 +61,9 @@ impl From<std::io::Error> for ServerError {
         ServerError::HttpInit(format!("🚀🔥 {}", err.to_string()))
     }
 }
+
+impl From<MailerError> for ServerError {
+    fn from(err: MailerError) -> Self {
+        ServerError::MailerInit(format!("📧🔥 {}", err))
+    }
+}
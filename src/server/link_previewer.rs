@@ -0,0 +1,22 @@
+use std::env;
+
+use log::warn;
+
+use crate::unfurl::{FetchPolicy, LinkPreviewer};
+
+pub fn init_link_previewer() -> LinkPreviewer {
+    match env::var("LINK_PREVIEWS")
+        .map(|v| v.to_lowercase())
+        .as_deref()
+    {
+        Ok("off") => {
+            warn!("🔗 Link previews are off");
+            LinkPreviewer::disabled()
+        }
+        _ => {
+            let policy = FetchPolicy::default();
+            warn!("🔗 Link previews: {:?}", policy);
+            LinkPreviewer::new(policy)
+        }
+    }
+}
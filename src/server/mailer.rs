@@ -0,0 +1,34 @@
+use std::{env, sync::Arc};
+
+use log::warn;
+
+use crate::mailer::{FileMailer, LogMailer, Mailer, SmtpMailer};
+
+use super::ServerError;
+
+const DEFAULT_MAIL_FROM: &str = "effward.dev <noreply@effward.dev>";
+
+pub fn init_mailer() -> Result<Arc<dyn Mailer>, ServerError> {
+    warn!("📧 Initializing mailer...");
+
+    match env::var("SMTP_URL") {
+        Ok(smtp_url) => {
+            let from = env::var("MAIL_FROM").unwrap_or(DEFAULT_MAIL_FROM.to_owned());
+            let mailer = SmtpMailer::new(&smtp_url, &from)?;
+            Ok(Arc::new(mailer))
+        }
+        Err(_) => match env::var("MAIL_DROP_DIR") {
+            Ok(dir) => {
+                warn!(
+                    "📧 SMTP_URL not set, emails will be written to {} instead of sent",
+                    dir
+                );
+                Ok(Arc::new(FileMailer::new(&dir)?))
+            }
+            Err(_) => {
+                warn!("📧 SMTP_URL not set, emails will be written to the log instead of sent");
+                Ok(Arc::new(LogMailer::new()))
+            }
+        },
+    }
+}
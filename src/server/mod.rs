@@ -1,12 +1,29 @@
+mod admins;
 mod application;
+mod comment_bus;
 mod db;
+mod digest;
 mod environment;
 mod error;
 mod flash_messages;
+mod link_previewer;
+mod mailer;
+mod oauth;
+mod rate_limit;
 mod redis;
+mod robots;
 mod session;
+mod spam_filter;
 mod tera;
+mod trusted_proxies;
+mod verification_policy;
+mod webhooks;
 
+pub use admins::Admins;
 pub use application::Application;
+pub use db::MAX_DB_CONNECTIONS;
 pub use environment::Environment;
 pub use error::ServerError;
+pub use robots::Robots;
+pub use trusted_proxies::TrustedProxies;
+pub use verification_policy::VerificationPolicy;
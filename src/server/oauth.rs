@@ -0,0 +1,21 @@
+use log::warn;
+
+use crate::oauth::{OAuthError, OAuthProviders};
+
+use super::{Environment, ServerError};
+
+pub async fn init_oauth_providers(env: &Environment) -> Result<OAuthProviders, ServerError> {
+    warn!("🪪 Initializing oauth providers...");
+    let providers = OAuthProviders::from_env(*env == Environment::Development).await?;
+
+    let names: Vec<String> = providers.summaries().into_iter().map(|p| p.name).collect();
+    warn!("🪪 OAuth providers: {:?}", names);
+
+    Ok(providers)
+}
+
+impl From<OAuthError> for ServerError {
+    fn from(err: OAuthError) -> Self {
+        ServerError::OAuthInit(format!("🪪🔥 {:?}", err))
+    }
+}
@@ -0,0 +1,37 @@
+use std::{env, sync::Arc};
+
+use log::warn;
+use redis::aio::ConnectionManager;
+
+use crate::rate_limit::{
+    MemoryRateLimitStore, RateLimitConfig, RateLimitError, RateLimitStore, RateLimiter,
+    RedisRateLimitStore,
+};
+
+use super::ServerError;
+
+pub fn init_rate_limiter(redis_client: ConnectionManager) -> Result<RateLimiter, ServerError> {
+    warn!("🚦 Initializing rate limiter...");
+    let config = RateLimitConfig::from_env()?;
+
+    let backend = env::var("RATE_LIMIT_BACKEND").unwrap_or("memory".to_owned());
+    let store: Arc<dyn RateLimitStore> = match backend.to_lowercase().as_str() {
+        "memory" => Arc::new(MemoryRateLimitStore::new()),
+        "redis" => Arc::new(RedisRateLimitStore::new(redis_client)),
+        _ => {
+            return Err(ServerError::RateLimitInit(format!(
+                "🚦🔥 unknown RATE_LIMIT_BACKEND '{}', set it to memory or redis",
+                backend
+            )))
+        }
+    };
+    warn!("🚦 Rate limit backend: {}", backend);
+
+    Ok(RateLimiter::new(store, config))
+}
+
+impl From<RateLimitError> for ServerError {
+    fn from(err: RateLimitError) -> Self {
+        ServerError::RateLimitInit(format!("🚦🔥 {:?}", err))
+    }
+}
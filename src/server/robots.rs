@@ -0,0 +1,68 @@
+use std::env;
+
+use chrono::{DateTime, Utc};
+use log::warn;
+
+use super::Environment;
+
+// Pages that are only useful when logged in, or aren't pages at all
+const DEFAULT_DISALLOW: &str = "/api/,/graphql,/login,/logout,/settings,/signup,/submit,/verify";
+
+/// The `/robots.txt` served to crawlers, rendered once at startup since it only changes with
+/// configuration.
+#[derive(Clone, Debug)]
+pub struct Robots {
+    pub body: String,
+    pub updated: DateTime<Utc>,
+}
+
+impl Robots {
+    pub fn new(disallow: &str, base_url: &str) -> Self {
+        let mut body = "User-agent: *\n".to_owned();
+        let paths: Vec<&str> = disallow
+            .split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect();
+        match paths.is_empty() {
+            // An empty Disallow allows everything
+            true => body.push_str("Disallow:\n"),
+            false => {
+                for path in paths {
+                    body.push_str(&format!("Disallow: {}\n", path));
+                }
+            }
+        }
+        body.push_str(&format!("\nSitemap: {}/sitemap.xml\n", base_url));
+
+        Self {
+            body,
+            updated: Utc::now(),
+        }
+    }
+}
+
+pub fn init_robots(env: &Environment) -> Robots {
+    let disallow = env::var("ROBOTS_DISALLOW").unwrap_or_else(|_| DEFAULT_DISALLOW.to_owned());
+    let robots = Robots::new(&disallow, env.base_url());
+
+    warn!("🤖 Robots disallow: {}", disallow);
+    robots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_body() {
+        let robots = Robots::new(" /settings, ,/api/ ", "https://effward.dev");
+        assert_eq!(
+            robots.body,
+            "User-agent: *\nDisallow: /settings\nDisallow: /api/\n\nSitemap: https://effward.dev/sitemap.xml\n"
+        );
+
+        let robots = Robots::new("", "https://effward.dev");
+        assert!(robots.body.starts_with("User-agent: *\nDisallow:\n\n"));
+    }
+}
@@ -0,0 +1,30 @@
+use std::env;
+
+use log::warn;
+
+use crate::spam::SpamFilter;
+
+use super::ServerError;
+
+const DEFAULT_THRESHOLD: f64 = 0.9;
+
+pub fn init_spam_filter() -> Result<SpamFilter, ServerError> {
+    if let Ok("off") = env::var("SPAM_FILTER").map(|v| v.to_lowercase()).as_deref() {
+        warn!("🥫 Spam filter is off");
+        return Ok(SpamFilter::disabled());
+    }
+
+    let threshold = match env::var("SPAM_THRESHOLD") {
+        Ok(t) => match t.parse::<f64>() {
+            Ok(t) if t > 0.0 && t <= 1.0 => t,
+            _ => return Err(ServerError::SpamThreshold),
+        },
+        Err(_) => DEFAULT_THRESHOLD,
+    };
+
+    warn!(
+        "🥫 Spam filter holds anything scored {} or higher",
+        threshold
+    );
+    Ok(SpamFilter::new(threshold))
+}
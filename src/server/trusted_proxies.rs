@@ -0,0 +1,171 @@
+use std::{env, net::IpAddr};
+
+use actix_web::HttpRequest;
+use ipnet::IpNet;
+use log::warn;
+
+/// Reverse proxies allowed to tell us who the client is with `X-Forwarded-For`, as addresses or
+/// CIDR ranges. Anyone else could put any address there, so for requests that don't come from one
+/// of these the header is ignored.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies {
+    nets: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    pub fn new(proxies: &str) -> Self {
+        let mut nets = vec![];
+        for proxy in proxies
+            .split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+        {
+            match proxy.parse::<IpNet>() {
+                Ok(net) => nets.push(net),
+                Err(_) => match proxy.parse::<IpAddr>() {
+                    Ok(ip) => nets.push(IpNet::from(ip)),
+                    Err(_) => warn!(
+                        "Ignoring trusted proxy that isn't an IP address or range: {}",
+                        proxy
+                    ),
+                },
+            }
+        }
+
+        Self { nets }
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.nets.iter().any(|net| net.contains(ip))
+    }
+
+    /// The address of whoever sent the request, or of the client a trusted proxy forwarded it for.
+    pub fn client_ip(&self, req: &HttpRequest) -> Option<IpAddr> {
+        let peer = req.peer_addr()?.ip();
+        if !self.is_trusted(&peer) {
+            return Some(peer);
+        }
+
+        // Each proxy appends the address it got the request from, so the client is the last one
+        // that isn't another trusted proxy. Anything before it could have been sent by the client.
+        let mut client = peer;
+        let values: Vec<_> = req.headers().get_all("x-forwarded-for").collect();
+        for value in values.into_iter().rev() {
+            let value = match value.to_str() {
+                Ok(v) => v,
+                Err(_) => return Some(client),
+            };
+            for ip in value.split(',').rev() {
+                match ip.trim().parse() {
+                    Ok(ip) if self.is_trusted(&client) => client = ip,
+                    _ => return Some(client),
+                }
+            }
+        }
+
+        Some(client)
+    }
+
+    /// Whether the request was forwarded by a proxy on the private network that isn't trusted,
+    /// so every client behind it shares the proxy's address.
+    pub fn is_untrusted_proxy(&self, req: &HttpRequest) -> bool {
+        let peer = match req.peer_addr() {
+            Some(addr) => addr.ip(),
+            None => return false,
+        };
+        let is_private = match peer {
+            IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+            IpAddr::V6(ip) => {
+                ip.is_loopback()
+                    // unique local and link local
+                    || (ip.segments()[0] & 0xfe00) == 0xfc00
+                    || (ip.segments()[0] & 0xffc0) == 0xfe80
+            }
+        };
+        let is_forwarded = req.headers().contains_key("x-forwarded-for")
+            || req.headers().contains_key("forwarded");
+
+        is_private && is_forwarded && !self.is_trusted(&peer)
+    }
+}
+
+pub fn init_trusted_proxies() -> TrustedProxies {
+    let proxies = match env::var("TRUSTED_PROXIES") {
+        Ok(ips) => TrustedProxies::new(&ips),
+        Err(_) => TrustedProxies::default(),
+    };
+
+    warn!("🛡️ Trusted proxies: {:?}", proxies.nets);
+    proxies
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    #[test]
+    fn test_client_ip() {
+        let proxies = TrustedProxies::new("10.0.0.1, 10.0.0.2,nonsense");
+        let client_ip = |peer: &str, forwarded: Option<&str>| {
+            let mut req = TestRequest::default().peer_addr(peer.parse().unwrap());
+            if let Some(forwarded) = forwarded {
+                req = req.insert_header(("X-Forwarded-For", forwarded));
+            }
+            proxies
+                .client_ip(&req.to_http_request())
+                .unwrap()
+                .to_string()
+        };
+
+        // Clients can't pick their own address
+        assert_eq!(client_ip("1.2.3.4:80", Some("5.6.7.8")), "1.2.3.4");
+        assert_eq!(client_ip("1.2.3.4:80", None), "1.2.3.4");
+        // But trusted proxies can
+        assert_eq!(client_ip("10.0.0.1:80", Some("5.6.7.8")), "5.6.7.8");
+        assert_eq!(
+            client_ip("10.0.0.1:80", Some("9.9.9.9, 5.6.7.8, 10.0.0.2")),
+            "5.6.7.8"
+        );
+        assert_eq!(client_ip("10.0.0.1:80", Some("junk, 5.6.7.8")), "5.6.7.8");
+        assert_eq!(client_ip("10.0.0.1:80", None), "10.0.0.1");
+    }
+
+    #[test]
+    fn test_is_untrusted_proxy() {
+        let is_untrusted_proxy = |proxies: &TrustedProxies, peer: &str, forwarded: bool| {
+            let mut req = TestRequest::default().peer_addr(peer.parse().unwrap());
+            if forwarded {
+                req = req.insert_header(("X-Forwarded-For", "5.6.7.8"));
+            }
+            proxies.is_untrusted_proxy(&req.to_http_request())
+        };
+
+        let none = TrustedProxies::default();
+        assert!(is_untrusted_proxy(&none, "172.19.4.2:80", true));
+        assert!(is_untrusted_proxy(&none, "[fdaa::3]:80", true));
+        assert!(!is_untrusted_proxy(&none, "172.19.4.2:80", false));
+        // A client on the internet can't opt out of the lockout by adding the header
+        assert!(!is_untrusted_proxy(&none, "1.2.3.4:80", true));
+
+        let fly = TrustedProxies::new("172.16.0.0/12");
+        assert!(!is_untrusted_proxy(&fly, "172.19.4.2:80", true));
+    }
+
+    #[test]
+    fn test_client_ip_through_range() {
+        let proxies = TrustedProxies::new("172.16.0.0/12, fdaa::/16");
+        let client_ip = |peer: &str, forwarded: &str| {
+            let req = TestRequest::default()
+                .peer_addr(peer.parse().unwrap())
+                .insert_header(("X-Forwarded-For", forwarded))
+                .to_http_request();
+            proxies.client_ip(&req).unwrap().to_string()
+        };
+
+        assert_eq!(client_ip("172.19.4.2:80", "5.6.7.8"), "5.6.7.8");
+        assert_eq!(client_ip("[fdaa:0:1::3]:80", "9.9.9.9, 5.6.7.8"), "5.6.7.8");
+        assert_eq!(client_ip("172.32.0.1:80", "5.6.7.8"), "172.32.0.1");
+    }
+}
@@ -0,0 +1,35 @@
+use std::{env, str::FromStr};
+
+use log::warn;
+
+use super::ServerError;
+
+/// Whether users must verify their email address before posting or commenting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerificationPolicy {
+    Optional,
+    Required,
+}
+
+impl FromStr for VerificationPolicy {
+    type Err = ServerError;
+
+    fn from_str(input: &str) -> Result<VerificationPolicy, Self::Err> {
+        let input_lower: &str = &input.to_lowercase();
+        match input_lower {
+            "optional" => Ok(VerificationPolicy::Optional),
+            "required" => Ok(VerificationPolicy::Required),
+            _ => Err(ServerError::VerificationPolicy),
+        }
+    }
+}
+
+pub fn init_verification_policy() -> Result<VerificationPolicy, ServerError> {
+    let policy = match env::var("EMAIL_VERIFICATION_POLICY") {
+        Ok(p) => VerificationPolicy::from_str(&p)?,
+        Err(_) => VerificationPolicy::Optional,
+    };
+
+    warn!("📧 Email verification policy: {:?}", policy);
+    Ok(policy)
+}
@@ -0,0 +1,39 @@
+use std::{env, time::Duration};
+
+use log::warn;
+
+use crate::{entities::EntityStores, http_client::HttpPolicy, webhooks::WebhookDispatcher};
+
+use super::Environment;
+
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn init_webhook_dispatcher(
+    env: &Environment,
+    stores: &EntityStores,
+) -> Option<WebhookDispatcher> {
+    if let Ok("off") = env::var("WEBHOOKS").map(|v| v.to_lowercase()).as_deref() {
+        warn!("🪝 Webhook deliveries are off");
+        return None;
+    }
+
+    let policy = HttpPolicy {
+        // The response is only read for its status
+        max_bytes: 16 * 1024,
+        allow_private: matches!(
+            env::var("WEBHOOK_ALLOW_PRIVATE")
+                .map(|v| v.to_lowercase())
+                .as_deref(),
+            Ok("on")
+        ),
+        ..HttpPolicy::default()
+    };
+    warn!("🪝 Webhook deliveries: {:?}", policy);
+
+    Some(WebhookDispatcher::new(
+        stores.clone(),
+        policy,
+        env.base_url(),
+        DISPATCH_INTERVAL,
+    ))
+}
@@ -0,0 +1,54 @@
+use crate::entities::spam::{TokenCounts, TrainingTotals};
+
+/// Naive Bayes: how likely a post or comment is to be spam, given what the filter has learned
+/// about its tokens. Tokens it was never trained on don't sway it either way, and with nothing
+/// learned about any of them it falls back to how much of what it's seen was spam.
+pub fn spam_probability(totals: TrainingTotals, counts: &[TokenCounts]) -> f64 {
+    // Add-one smoothing, so a token only ever seen in spam doesn't make ham impossible
+    let spam_documents = totals.spam as f64;
+    let ham_documents = totals.ham as f64;
+    let mut log_odds = ((spam_documents + 1.0) / (ham_documents + 1.0)).ln();
+
+    for token in counts.iter().filter(|t| t.spam + t.ham > 0) {
+        let in_spam = (token.spam as f64 + 1.0) / (spam_documents + 2.0);
+        let in_ham = (token.ham as f64 + 1.0) / (ham_documents + 2.0);
+        log_odds += in_spam.ln() - in_ham.ln();
+    }
+
+    1.0 / (1.0 + (-log_odds).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(token: &str, spam: u64, ham: u64) -> TokenCounts {
+        TokenCounts {
+            token: token.to_owned(),
+            spam,
+            ham,
+        }
+    }
+
+    #[test]
+    fn test_spam_probability() {
+        let totals = TrainingTotals { spam: 20, ham: 80 };
+
+        let spammy = [
+            counts("domain:pills.example", 18, 0),
+            counts("cheap", 15, 4),
+            counts("the", 19, 78),
+        ];
+        assert!(spam_probability(totals, &spammy) > 0.99);
+
+        let hammy = [
+            counts("rust", 0, 70),
+            counts("borrow", 1, 12),
+            counts("the", 19, 78),
+        ];
+        assert!(spam_probability(totals, &hammy) < 0.01);
+
+        let unknown = spam_probability(totals, &[]);
+        assert!((unknown - 21.0 / 102.0).abs() < 1e-9);
+    }
+}
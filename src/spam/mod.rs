@@ -0,0 +1,144 @@
+mod classifier;
+mod tokenize;
+
+use log::{error, info};
+
+use crate::entities::{
+    comment::Comment, content::ContentStore, post::Post, spam::SpamStore, EntityError, EntityStores,
+};
+
+use classifier::spam_probability;
+use tokenize::tokenize;
+
+// Until moderators have made this many decisions each way, every guess would be a bad one
+const MIN_TRAINING: u64 = 10;
+
+/// Scores new posts and comments against what moderators have removed and approved, so likely
+/// spam is held for review instead of published.
+#[derive(Clone, Debug)]
+pub struct SpamFilter {
+    // None when the filter is turned off
+    threshold: Option<f64>,
+}
+
+impl SpamFilter {
+    /// Holds anything scored at or above `threshold`, between 0 and 1.
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold: Some(threshold),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self { threshold: None }
+    }
+
+    /// The post's score when it should be held. Failures are only logged, the post is published
+    /// unchecked rather than lost.
+    pub async fn check_post(
+        &self,
+        stores: &EntityStores,
+        title: &str,
+        link: &Option<String>,
+        content: &Option<String>,
+    ) -> Option<f64> {
+        let tokens = tokenize(
+            Some(title),
+            link.as_deref(),
+            content.as_deref().unwrap_or_default(),
+        );
+        self.check(stores, &tokens).await
+    }
+
+    /// The comment's score when it should be held, like `check_post`.
+    pub async fn check_comment(&self, stores: &EntityStores, content: &str) -> Option<f64> {
+        let tokens = tokenize(None, None, content);
+        self.check(stores, &tokens).await
+    }
+
+    async fn check(&self, stores: &EntityStores, tokens: &[String]) -> Option<f64> {
+        let threshold = self.threshold?;
+        let score = match score(stores, tokens).await {
+            Ok(Some(s)) => s,
+            Ok(None) => return None,
+            Err(e) => {
+                error!("Error scoring submission for spam: {:?}", e);
+                return None;
+            }
+        };
+
+        info!("🥫 Spam score {:.3}", score);
+        match score >= threshold {
+            true => Some(score),
+            false => None,
+        }
+    }
+}
+
+// None until the filter has enough training to be trusted
+async fn score(stores: &EntityStores, tokens: &[String]) -> Result<Option<f64>, EntityError> {
+    let totals = stores.spam_store.get_totals().await?;
+    if totals.spam < MIN_TRAINING || totals.ham < MIN_TRAINING {
+        return Ok(None);
+    }
+    let counts = stores.spam_store.get_token_counts(tokens).await?;
+
+    Ok(Some(spam_probability(totals, &counts)))
+}
+
+/// Learns from a moderator's decision about a post. Trained whether or not the filter is on, so
+/// it's ready when it's turned on. Failures are only logged, the decision itself stands.
+pub async fn train_post(stores: &EntityStores, post: &Post, is_spam: bool) {
+    let body = match post.content_id {
+        Some(id) => match stores.content_store.get_by_id(id).await {
+            Ok(content) => content.body,
+            Err(e) => {
+                error!(
+                    "Error getting post {}'s content to train on: {:?}",
+                    post.id, e
+                );
+                return;
+            }
+        },
+        None => String::new(),
+    };
+    let tokens = tokenize(Some(&post.title), post.link.as_deref(), &body);
+
+    train(stores, post.id, None, &tokens, is_spam).await
+}
+
+/// Learns from a moderator's decision about a comment, like `train_post`.
+pub async fn train_comment(stores: &EntityStores, comment: &Comment, is_spam: bool) {
+    let body = match stores.content_store.get_by_id(comment.content_id).await {
+        Ok(content) => content.body,
+        Err(e) => {
+            error!(
+                "Error getting comment {}'s content to train on: {:?}",
+                comment.id, e
+            );
+            return;
+        }
+    };
+    let tokens = tokenize(None, None, &body);
+
+    train(stores, comment.post_id, Some(comment.id), &tokens, is_spam).await
+}
+
+async fn train(
+    stores: &EntityStores,
+    post_id: u64,
+    comment_id: Option<u64>,
+    tokens: &[String],
+    is_spam: bool,
+) {
+    if let Err(e) = stores
+        .spam_store
+        .train(post_id, comment_id, tokens, is_spam)
+        .await
+    {
+        error!(
+            "Error training spam filter on post {} comment {:?}: {:?}",
+            post_id, comment_id, e
+        );
+    }
+}
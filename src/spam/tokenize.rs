@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+
+use sha2::{Digest, Sha256};
+use url::Url;
+
+const MIN_WORD_LENGTH: usize = 2;
+const MAX_WORD_LENGTH: usize = 32;
+// Enough to tell what a post is about, long posts don't get to outweigh everything else
+const MAX_TOKENS: usize = 300;
+// The length of the spam_tokens column
+const MAX_TOKEN_BYTES: usize = 64;
+// Hex digits of the hash that replaces the end of a longer token
+const TOKEN_HASH_LENGTH: usize = 16;
+
+/// The distinct tokens in a post or comment, in the order they first appear. Title words and
+/// linked domains are prefixed, since "viagra" in a title or a link to a known spam domain says
+/// more than a word in passing.
+pub fn tokenize(title: Option<&str>, link: Option<&str>, body: &str) -> Vec<String> {
+    let mut tokens = Tokens::default();
+
+    if let Some(title) = title {
+        for word in words(title) {
+            tokens.push(format!("title:{}", word));
+        }
+    }
+    if let Some(link) = link {
+        for domain in domains(link) {
+            tokens.push(format!("domain:{}", domain));
+        }
+    }
+    for url in body.split_whitespace().filter_map(find_url) {
+        for domain in domains(url) {
+            tokens.push(format!("domain:{}", domain));
+        }
+    }
+    for word in words(body) {
+        tokens.push(word);
+    }
+
+    tokens.list
+}
+
+#[derive(Default)]
+struct Tokens {
+    seen: HashSet<String>,
+    list: Vec<String>,
+}
+
+impl Tokens {
+    fn push(&mut self, token: String) {
+        let token = fit(token);
+        if self.list.len() < MAX_TOKENS && self.seen.insert(token.clone()) {
+            self.list.push(token);
+        }
+    }
+}
+
+// A token too long to store keeps its start and ends in a hash of the whole thing, so two long
+// domains that start the same still count apart
+fn fit(token: String) -> String {
+    if token.len() <= MAX_TOKEN_BYTES {
+        return token;
+    }
+
+    let hash = hex::encode(Sha256::digest(token.as_bytes()));
+    let mut end = MAX_TOKEN_BYTES - TOKEN_HASH_LENGTH - 1;
+    while !token.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}#{}", &token[..end], &hash[..TOKEN_HASH_LENGTH])
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| (MIN_WORD_LENGTH..=MAX_WORD_LENGTH).contains(&word.chars().count()))
+}
+
+// Markdown links leave brackets and punctuation around the url
+fn find_url(word: &str) -> Option<&str> {
+    let start = word.find("http://").or_else(|| word.find("https://"))?;
+    Some(word[start..].trim_end_matches(|c: char| ")]>\"'.,;:!?".contains(c)))
+}
+
+/// The link's host without `www.`, and the domain it's under when it's a subdomain, so every
+/// subdomain of a spam domain counts against it.
+fn domains(link: &str) -> Vec<String> {
+    let host = match Url::parse(link)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_lowercase))
+    {
+        Some(h) => h,
+        None => return vec![],
+    };
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_owned();
+
+    let labels: Vec<&str> = host.split('.').collect();
+    let mut domains = vec![];
+    if labels.len() > 2 {
+        domains.push(labels[labels.len() - 2..].join("."));
+    }
+    domains.push(host);
+    domains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize(
+            Some("Cheap Pills, cheap!"),
+            Some("https://www.shop.pills.example/buy"),
+            "Don't miss [this](http://deals.example/x). a cheap deal",
+        );
+
+        assert_eq!(
+            tokens,
+            [
+                "title:cheap",
+                "title:pills",
+                "domain:pills.example",
+                "domain:shop.pills.example",
+                "domain:deals.example",
+                "don't",
+                "miss",
+                "this",
+                "http",
+                "deals",
+                "example",
+                "cheap",
+                "deal",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_limits() {
+        let long_word = "a".repeat(MAX_WORD_LENGTH + 1);
+        assert!(tokenize(None, Some("not a url"), &long_word).is_empty());
+
+        let body: Vec<String> = (0..MAX_TOKENS * 2).map(|i| format!("w{}", i)).collect();
+        assert_eq!(tokenize(None, None, &body.join(" ")).len(), MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_tokenize_long_domains() {
+        let subdomain = "a".repeat(60);
+        let tokens = tokenize(
+            None,
+            Some(&format!("https://{}.b.spam.example/", subdomain)),
+            &format!("https://{}.c.spam.example/", subdomain),
+        );
+
+        assert!(tokens.iter().all(|t| t.len() <= MAX_TOKEN_BYTES));
+        assert_eq!(tokens[0], "domain:spam.example");
+        assert!(tokens[1].starts_with("domain:aaaa"));
+        assert!(tokens[2].starts_with("domain:aaaa"));
+        assert_ne!(tokens[1], tokens[2]);
+
+        // Never cut inside a character
+        let token = fit(format!("title:{}", "é".repeat(40)));
+        assert!(token.len() <= MAX_TOKEN_BYTES);
+        assert!(token.starts_with("title:éé"));
+    }
+}
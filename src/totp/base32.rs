@@ -0,0 +1,40 @@
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Unpadded RFC 4648 base32, the encoding authenticator apps expect for TOTP secrets.
+pub fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "MY");
+        assert_eq!(encode(b"fo"), "MZXQ");
+        assert_eq!(encode(b"foo"), "MZXW6");
+        assert_eq!(encode(b"foob"), "MZXW6YQ");
+        assert_eq!(encode(b"fooba"), "MZXW6YTB");
+        assert_eq!(encode(b"foobar"), "MZXW6YTBOI");
+    }
+}
@@ -0,0 +1,5 @@
+mod base32;
+pub mod qr;
+mod totp;
+
+pub use totp::{generate_secret, provisioning_uri, Totp};
@@ -0,0 +1,29 @@
+use qrcode::{render::svg, EcLevel, QrCode};
+
+/// A QR code for the text as an inline SVG, drawn on the server so the page showing a secret
+/// doesn't have to load a script from anywhere. `None` if the text is too long to fit.
+pub fn svg(text: &str) -> Option<String> {
+    let code = QrCode::with_error_correction_level(text, EcLevel::M).ok()?;
+    let image = code
+        .render::<svg::Color>()
+        .min_dimensions(192, 192)
+        .quiet_zone(true)
+        .build();
+
+    // Drops the XML declaration, which doesn't belong inside an HTML page
+    image.find("<svg").map(|start| image[start..].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg() {
+        let image = svg("otpauth://totp/effward.dev:sasquatch?secret=JBSWY3DPEHPK3PXP").unwrap();
+        assert!(image.starts_with("<svg"));
+        assert!(image.ends_with("</svg>"));
+
+        assert_eq!(svg(&"a".repeat(4000)), None);
+    }
+}
@@ -0,0 +1,136 @@
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use url::form_urlencoded;
+
+use super::base32;
+
+pub const SECRET_LENGTH: usize = 20;
+
+const ISSUER: &str = "effward.dev";
+const DIGITS: u32 = 6;
+const STEP_SECS: u64 = 30;
+// Accept codes from one step either side of now, to allow for clock drift
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+
+pub fn generate_secret() -> Vec<u8> {
+    rand::thread_rng().gen::<[u8; SECRET_LENGTH]>().to_vec()
+}
+
+/// `otpauth://` URI understood by authenticator apps, usually shown to the user as a QR code.
+pub fn provisioning_uri(secret: &[u8], account_name: &str) -> String {
+    let label: String =
+        form_urlencoded::byte_serialize(format!("{}:{}", ISSUER, account_name).as_bytes())
+            .collect();
+
+    format!(
+        "otpauth://totp/{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        label,
+        base32::encode(secret),
+        ISSUER,
+        DIGITS,
+        STEP_SECS
+    )
+}
+
+/// RFC 6238 time-based one-time passwords (HMAC-SHA1, 6 digits, 30 second steps).
+pub struct Totp<'a> {
+    secret: &'a [u8],
+}
+
+impl<'a> Totp<'a> {
+    pub fn new(secret: &'a [u8]) -> Self {
+        Self { secret }
+    }
+
+    /// The secret in the form users can type into an authenticator app by hand.
+    pub fn encoded_secret(&self) -> String {
+        base32::encode(self.secret)
+    }
+
+    pub fn step_at(unix_secs: u64) -> u64 {
+        unix_secs / STEP_SECS
+    }
+
+    pub fn code_at_step(&self, step: u64) -> String {
+        // HMAC accepts keys of any length, so this can't fail
+        let mut mac = Hmac::<Sha1>::new_from_slice(self.secret).unwrap();
+        mac.update(&step.to_be_bytes());
+        let hash = mac.finalize().into_bytes();
+
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+
+        format!(
+            "{:0width$}",
+            binary % 10_u32.pow(DIGITS),
+            width = DIGITS as usize
+        )
+    }
+
+    /// Returns the step the code belongs to if it's valid at `unix_secs`.
+    /// Callers should reject steps at or before the last one used, so a code can't be replayed.
+    pub fn verify(&self, code: &str, unix_secs: u64) -> Option<u64> {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        if code.len() != DIGITS as usize {
+            return None;
+        }
+
+        let now = Self::step_at(unix_secs);
+        let first = now.saturating_sub(ALLOWED_DRIFT_STEPS);
+        (first..=now + ALLOWED_DRIFT_STEPS)
+            .find(|step| codes_match(&self.code_at_step(*step), &code))
+    }
+}
+
+fn codes_match(expected: &str, submitted: &str) -> bool {
+    expected.len() == submitted.len()
+        && expected
+            .bytes()
+            .zip(submitted.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        let totp = Totp::new(RFC_SECRET);
+
+        assert_eq!(totp.code_at_step(Totp::step_at(59)), "287082");
+        assert_eq!(totp.code_at_step(Totp::step_at(1111111109)), "081804");
+        assert_eq!(totp.code_at_step(Totp::step_at(1234567890)), "005924");
+        assert_eq!(totp.code_at_step(Totp::step_at(20000000000)), "353130");
+    }
+
+    #[test]
+    fn test_verify_allows_drift() {
+        let totp = Totp::new(RFC_SECRET);
+        let step = Totp::step_at(1111111109);
+
+        assert_eq!(totp.verify("081804", 1111111109), Some(step));
+        assert_eq!(totp.verify("081 804", 1111111109 + 30), Some(step));
+        assert_eq!(totp.verify("081804", 1111111109 - 30), Some(step));
+        assert_eq!(totp.verify("081804", 1111111109 + 90), None);
+        assert_eq!(totp.verify("000000", 1111111109), None);
+        assert_eq!(totp.verify("81804", 1111111109), None);
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        assert_eq!(
+            provisioning_uri(b"foobar", "bigfoot"),
+            "otpauth://totp/effward.dev%3Abigfoot?secret=MZXW6YTBOI&issuer=effward.dev&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{entities::EntityError, http_client::HttpError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum UnfurlError {
+    #[error("link can't be previewed")]
+    InvalidUrl(String),
+    #[error("link points to a private address")]
+    PrivateAddress(String),
+    #[error("error fetching link")]
+    Http(String),
+    #[error("timed out fetching link")]
+    Timeout,
+    #[error("link isn't a web page")]
+    NotHtml(String),
+    #[error("page has nothing to preview")]
+    NothingToPreview,
+    #[error("link preview couldn't be stored")]
+    Entity(#[from] EntityError),
+}
+
+impl From<HttpError> for UnfurlError {
+    fn from(err: HttpError) -> Self {
+        match err {
+            HttpError::InvalidUrl(e) => UnfurlError::InvalidUrl(e),
+            HttpError::PrivateAddress(e) => UnfurlError::PrivateAddress(e),
+            HttpError::Connection(e) | HttpError::InvalidResponse(e) => UnfurlError::Http(e),
+            HttpError::Timeout => UnfurlError::Timeout,
+        }
+    }
+}
+
+impl From<url::ParseError> for UnfurlError {
+    fn from(err: url::ParseError) -> Self {
+        UnfurlError::InvalidUrl(err.to_string())
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use scraper::{Html, Selector};
+use url::Url;
+
+// Shorter than their columns, a preview only needs the start of each
+const MAX_TITLE_CHARS: usize = 300;
+const MAX_DESCRIPTION_CHARS: usize = 500;
+const MAX_SITE_NAME_CHARS: usize = 100;
+pub const MAX_URL_LEN: usize = 1024;
+
+/// What a page says about itself, from its OpenGraph and Twitter card tags or its `<title>`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Preview {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+impl Preview {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none()
+    }
+}
+
+pub fn extract(page_url: &Url, html: &str) -> Preview {
+    let document = Html::parse_document(html);
+
+    // The first of each wins, like it does for the sites that read them
+    let mut properties: HashMap<String, &str> = HashMap::new();
+    let meta = Selector::parse("meta[content]").unwrap();
+    for element in document.select(&meta) {
+        let element = element.value();
+        let key = element.attr("property").or(element.attr("name"));
+        if let (Some(key), Some(content)) = (key, element.attr("content")) {
+            properties.entry(key.to_lowercase()).or_insert(content);
+        }
+    }
+    let first = |keys: &[&str], max_chars: usize| {
+        keys.iter()
+            .find_map(|k| properties.get(*k))
+            .map(|v| clean(v, max_chars))
+            .filter(|v| !v.is_empty())
+    };
+
+    let title_selector = Selector::parse("title").unwrap();
+    let title = first(&["og:title", "twitter:title"], MAX_TITLE_CHARS).or_else(|| {
+        document
+            .select(&title_selector)
+            .next()
+            .map(|t| clean(&t.text().collect::<String>(), MAX_TITLE_CHARS))
+            .filter(|t| !t.is_empty())
+    });
+
+    let image_url = [
+        "og:image",
+        "og:image:url",
+        "og:image:secure_url",
+        "twitter:image",
+    ]
+    .iter()
+    .find_map(|k| properties.get(*k))
+    .and_then(|image| page_url.join(image.trim()).ok())
+    .filter(|image| matches!(image.scheme(), "http" | "https"))
+    .map(String::from)
+    .filter(|image| image.len() <= MAX_URL_LEN);
+
+    Preview {
+        title,
+        description: first(
+            &["og:description", "twitter:description", "description"],
+            MAX_DESCRIPTION_CHARS,
+        ),
+        image_url,
+        site_name: first(&["og:site_name"], MAX_SITE_NAME_CHARS),
+    }
+}
+
+fn clean(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let page_url = Url::parse("https://example.com/articles/1").unwrap();
+        let html = r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Fallback title</title>
+    <meta property="og:title" content="  Fish &amp;
+        chips ">
+    <meta property="og:title" content="Second title">
+    <meta name="description" content="A description">
+    <meta property="og:image" content="/images/fish.png">
+    <meta property="og:site_name" content="Example">
+</head>
+<body><p>Hello</p></body>
+</html>"#;
+
+        assert_eq!(
+            extract(&page_url, html),
+            Preview {
+                title: Some("Fish & chips".to_owned()),
+                description: Some("A description".to_owned()),
+                image_url: Some("https://example.com/images/fish.png".to_owned()),
+                site_name: Some("Example".to_owned()),
+            }
+        );
+
+        let html = r#"<title>Only a title</title><meta property="og:image" content="javascript:alert(1)">"#;
+        let preview = extract(&page_url, html);
+        assert_eq!(preview.title, Some("Only a title".to_owned()));
+        assert_eq!(preview.image_url, None);
+
+        assert!(extract(&page_url, "<p>nothing</p>").is_empty());
+        assert_eq!(clean(&"é".repeat(400), 300).chars().count(), 301);
+    }
+}
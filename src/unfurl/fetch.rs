@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use tokio::time::timeout;
+use url::Url;
+
+use super::UnfurlError;
+use crate::http_client::{self, HttpPolicy, Method, Response};
+
+const USER_AGENT: &str = "effward.dev link preview";
+
+/// Limits on fetching a page, which is on someone else's server and can be anything.
+#[derive(Clone, Debug)]
+pub struct FetchPolicy {
+    /// For the whole fetch, including redirects.
+    pub timeout: Duration,
+    /// Anything after is dropped, the metadata is in the page's head anyway.
+    pub max_bytes: u64,
+    pub max_redirects: u8,
+    /// Only for tests against a local stub, links posted by users must never reach the server's
+    /// own network.
+    pub allow_private: bool,
+}
+
+impl FetchPolicy {
+    // Each request gets the whole timeout, the fetch as a whole is limited by `fetch`
+    fn http(&self) -> HttpPolicy {
+        HttpPolicy {
+            timeout: self.timeout,
+            max_bytes: self.max_bytes,
+            allow_private: self.allow_private,
+        }
+    }
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_bytes: 512 * 1024,
+            max_redirects: 5,
+            allow_private: false,
+        }
+    }
+}
+
+/// A fetched html page, `url` is where it was found after redirects.
+#[derive(Debug)]
+pub struct Page {
+    pub url: Url,
+    pub html: String,
+}
+
+pub async fn fetch(url: &Url, policy: &FetchPolicy) -> Result<Page, UnfurlError> {
+    match timeout(policy.timeout, fetch_following(url.clone(), policy)).await {
+        Ok(result) => result,
+        Err(_) => Err(UnfurlError::Timeout),
+    }
+}
+
+async fn fetch_following(mut url: Url, policy: &FetchPolicy) -> Result<Page, UnfurlError> {
+    for _ in 0..=policy.max_redirects {
+        let response = get(&url, policy).await?;
+        match response.status {
+            200..=299 => {
+                let content_type = response.header("content-type");
+                let is_html = match content_type {
+                    Some(t) => {
+                        let t = t.to_lowercase();
+                        t.starts_with("text/html") || t.starts_with("application/xhtml+xml")
+                    }
+                    // Left for the parser to make sense of
+                    None => true,
+                };
+                if !is_html {
+                    return Err(UnfurlError::NotHtml(
+                        content_type.unwrap_or_default().to_owned(),
+                    ));
+                }
+
+                return Ok(Page {
+                    url,
+                    html: String::from_utf8_lossy(&response.body).into_owned(),
+                });
+            }
+            300..=399 => {
+                // Each hop is checked again, so a public page can't redirect to a private one
+                let location = response
+                    .header("location")
+                    .ok_or(UnfurlError::Http("redirect without a location".to_owned()))?;
+                url = url.join(location)?;
+            }
+            status => return Err(UnfurlError::Http(format!("status {}", status))),
+        }
+    }
+
+    Err(UnfurlError::Http("too many redirects".to_owned()))
+}
+
+async fn get(url: &Url, policy: &FetchPolicy) -> Result<Response, UnfurlError> {
+    let headers = [
+        ("User-Agent", USER_AGENT),
+        ("Accept", "text/html,application/xhtml+xml"),
+    ];
+
+    Ok(http_client::send(Method::GET, url, &headers, &[], &policy.http()).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    // Serves one canned response per connection, in order
+    async fn stub(responses: Vec<String>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        actix_web::rt::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        Url::parse(&format!("http://{}/", addr)).unwrap()
+    }
+
+    fn local_policy() -> FetchPolicy {
+        FetchPolicy {
+            timeout: Duration::from_secs(2),
+            allow_private: true,
+            ..FetchPolicy::default()
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_fetch_refuses_private_addresses() {
+        let url = stub(vec![]).await;
+        let result = fetch(&url, &FetchPolicy::default()).await;
+        assert!(matches!(result, Err(UnfurlError::PrivateAddress(_))));
+
+        for url in ["http://localhost:8080/", "http://[::ffff:10.0.0.1]/"] {
+            let result = fetch(&Url::parse(url).unwrap(), &FetchPolicy::default()).await;
+            assert!(
+                matches!(result, Err(UnfurlError::PrivateAddress(_))),
+                "{}",
+                url
+            );
+        }
+
+        let result = fetch(
+            &Url::parse("file:///etc/passwd").unwrap(),
+            &FetchPolicy::default(),
+        )
+        .await;
+        assert!(matches!(result, Err(UnfurlError::InvalidUrl(_))));
+    }
+
+    #[actix_web::test]
+    async fn test_fetch_follows_redirects() {
+        let url = stub(vec![
+            "HTTP/1.1 301 Moved Permanently\r\nLocation: /page?a=1\r\nContent-Length: 0\r\n\r\n"
+                .to_owned(),
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nTransfer-Encoding: chunked\r\n\r\n6\r\n<html>\r\n7\r\n</html>\r\n0\r\n\r\n"
+                .to_owned(),
+        ])
+        .await;
+
+        let page = fetch(&url, &local_policy()).await.unwrap();
+        assert_eq!(page.url.path(), "/page");
+        assert_eq!(page.html, "<html></html>");
+    }
+
+    #[actix_web::test]
+    async fn test_fetch_limits() {
+        let body = "a".repeat(4096);
+        let url = stub(vec![format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )])
+        .await;
+        let policy = FetchPolicy {
+            max_bytes: 1024,
+            ..local_policy()
+        };
+        let page = fetch(&url, &policy).await.unwrap();
+        assert_eq!(page.html.len(), 1024);
+
+        let url = stub(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\n\r\n".to_owned()
+        ])
+        .await;
+        let result = fetch(&url, &local_policy()).await;
+        assert!(matches!(result, Err(UnfurlError::NotHtml(_))));
+
+        let redirect = "HTTP/1.1 302 Found\r\nLocation: /\r\n\r\n".to_owned();
+        let url = stub(vec![redirect; 7]).await;
+        let result = fetch(&url, &local_policy()).await;
+        assert!(matches!(result, Err(UnfurlError::Http(_))));
+
+        // Accepts the connection but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let policy = FetchPolicy {
+            timeout: Duration::from_millis(200),
+            ..local_policy()
+        };
+        let result = fetch(&url, &policy).await;
+        assert!(matches!(result, Err(UnfurlError::Timeout)));
+    }
+}
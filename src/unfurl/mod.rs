@@ -0,0 +1,86 @@
+mod error;
+mod extract;
+mod fetch;
+
+use log::{info, warn};
+use url::Url;
+
+use crate::entities::{
+    link_preview::{LinkPreview, LinkPreviewStore},
+    post::Post,
+    EntityStores,
+};
+
+pub use error::UnfurlError;
+pub use fetch::FetchPolicy;
+
+/// Fetches previews for link posts in the background, so submitting a post doesn't wait on
+/// someone else's server.
+#[derive(Clone, Debug)]
+pub struct LinkPreviewer {
+    // None when previews are turned off
+    policy: Option<FetchPolicy>,
+}
+
+impl LinkPreviewer {
+    pub fn new(policy: FetchPolicy) -> Self {
+        Self {
+            policy: Some(policy),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self { policy: None }
+    }
+
+    /// Does nothing for posts without a link. Failures are only logged, the post is shown without
+    /// a preview.
+    pub fn spawn(&self, stores: &EntityStores, post: &Post) {
+        let (policy, link) = match (&self.policy, &post.link) {
+            (Some(policy), Some(link)) => (policy.clone(), link.clone()),
+            _ => return,
+        };
+        let stores = stores.clone();
+        let post_id = post.id;
+
+        actix_web::rt::spawn(async move {
+            match preview(&policy, &stores, post_id, &link).await {
+                Ok(_) => info!("🔗 Stored preview for post {}", post_id),
+                Err(e) => warn!("🔗 No preview for post {}: {} ({:?})", post_id, e, e),
+            }
+        });
+    }
+}
+
+async fn preview(
+    policy: &FetchPolicy,
+    stores: &EntityStores,
+    post_id: u64,
+    link: &str,
+) -> Result<LinkPreview, UnfurlError> {
+    let url = Url::parse(link)?;
+    let page = fetch::fetch(&url, policy).await?;
+
+    let preview = extract::extract(&page.url, &page.html);
+    if preview.is_empty() {
+        return Err(UnfurlError::NothingToPreview);
+    }
+
+    // The link itself fits, since it fit in the post
+    let url = match page.url.as_str().len() <= extract::MAX_URL_LEN {
+        true => page.url.as_str(),
+        false => link,
+    };
+
+    Ok(stores
+        .link_preview_store
+        .insert(
+            post_id,
+            url,
+            &preview.title,
+            &preview.description,
+            &preview.image_url,
+            &preview.site_name,
+        )
+        .await?)
+}
@@ -0,0 +1,298 @@
+mod payload;
+mod signature;
+
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use url::Url;
+
+use crate::{
+    entities::{
+        webhook::{Webhook, WebhookDelivery, WebhookStore},
+        EntityError, EntityStores,
+    },
+    http_client::{self, HttpError, HttpPolicy, Method},
+};
+
+pub use signature::{sign, SIGNATURE_HEADER};
+
+const USER_AGENT: &str = "effward.dev webhooks";
+const EVENT_HEADER: &str = "X-Effward-Event";
+const DELIVERY_HEADER: &str = "X-Effward-Delivery";
+
+const BATCH_SIZE: u8 = 20;
+/// Attempts before a delivery is given up on, the last one about 42 minutes after the first.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Sends queued webhook deliveries in the background, retrying failures with exponential
+/// backoff. Posts and comments queue deliveries when they're created.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    stores: EntityStores,
+    policy: HttpPolicy,
+    base_url: &'static str,
+    interval: StdDuration,
+}
+
+impl WebhookDispatcher {
+    pub fn new(
+        stores: EntityStores,
+        policy: HttpPolicy,
+        base_url: &'static str,
+        interval: StdDuration,
+    ) -> Self {
+        Self {
+            stores,
+            policy,
+            base_url,
+            interval,
+        }
+    }
+
+    pub fn spawn(self) {
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                self.run(Utc::now()).await;
+            }
+        });
+    }
+
+    async fn run(&self, now: DateTime<Utc>) {
+        let deliveries = match self
+            .stores
+            .webhook_store
+            .get_due_deliveries(now, BATCH_SIZE)
+            .await
+        {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                error!("🪝 Couldn't look for webhook deliveries: {:?}", e);
+                return;
+            }
+        };
+
+        for delivery in deliveries {
+            let id = delivery.id;
+            // Each attempt runs as its own task, so one that panics doesn't stop the others or
+            // the dispatcher
+            let dispatcher = self.clone();
+            let attempt =
+                actix_web::rt::spawn(async move { dispatcher.attempt(delivery, now).await });
+            match attempt.await {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => error!("🪝 Couldn't attempt webhook delivery {}: {:?}", id, e),
+                Err(e) => error!("🪝 Webhook delivery {} panicked: {:?}", id, e),
+            }
+        }
+    }
+
+    async fn attempt(
+        &self,
+        mut delivery: WebhookDelivery,
+        now: DateTime<Utc>,
+    ) -> Result<(), EntityError> {
+        // Long enough for the attempt to finish before anyone else may pick it up
+        let lease = Duration::from_std(self.policy.timeout * 2).unwrap_or(Duration::minutes(1));
+        let store = &self.stores.webhook_store;
+        if !store.claim_delivery(&delivery, now + lease).await? {
+            return Ok(());
+        }
+        delivery.attempts += 1;
+
+        let webhook = match store.get_by_id(delivery.webhook_id).await {
+            Ok(webhook) => webhook,
+            Err(EntityError::NotFound) => {
+                return self.give_up(delivery, "webhook was deleted").await;
+            }
+            Err(e) => return Err(e),
+        };
+        let payload = match &delivery.payload {
+            Some(payload) => payload.clone(),
+            None => match payload::build(&self.stores, self.base_url, &delivery).await {
+                Ok(payload) => payload,
+                Err(EntityError::NotFound) => {
+                    return self.give_up(delivery, "the post or comment is gone").await;
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        delivery.payload = Some(payload);
+
+        match deliver(&webhook, &delivery, &self.policy).await {
+            Ok(status) if (200..300).contains(&status) => {
+                info!("🪝 Delivered {} to {}", delivery.event, webhook.url);
+                delivery.status_code = Some(status);
+                delivery.error = None;
+                delivery.is_delivered = true;
+                delivery.next_attempt = None;
+            }
+            Ok(status) => {
+                warn!(
+                    "🪝 {} answered {} with {}",
+                    webhook.url, delivery.event, status
+                );
+                delivery.status_code = Some(status);
+                delivery.error = Some(format!("status {}", status));
+                delivery.next_attempt = retry_at(now, delivery.attempts);
+            }
+            Err(e) => {
+                warn!(
+                    "🪝 Couldn't deliver {} to {}: {:?}",
+                    delivery.event, webhook.url, e
+                );
+                delivery.status_code = None;
+                delivery.error = Some(describe(&e));
+                delivery.next_attempt = retry_at(now, delivery.attempts);
+            }
+        }
+
+        store.record_attempt(&delivery).await
+    }
+
+    async fn give_up(
+        &self,
+        mut delivery: WebhookDelivery,
+        reason: &str,
+    ) -> Result<(), EntityError> {
+        delivery.error = Some(reason.to_owned());
+        delivery.next_attempt = None;
+
+        self.stores.webhook_store.record_attempt(&delivery).await
+    }
+}
+
+/// POSTs a delivery's payload to its webhook and returns the response status. Redirects aren't
+/// followed.
+async fn deliver(
+    webhook: &Webhook,
+    delivery: &WebhookDelivery,
+    policy: &HttpPolicy,
+) -> Result<u16, HttpError> {
+    let url = Url::parse(&webhook.url).map_err(|e| HttpError::InvalidUrl(e.to_string()))?;
+    let payload = delivery.payload.as_deref().unwrap_or_default().as_bytes();
+    let signature = sign(&webhook.secret, payload);
+    let headers = [
+        ("User-Agent", USER_AGENT),
+        ("Content-Type", "application/json"),
+        (EVENT_HEADER, delivery.event.as_str()),
+        (DELIVERY_HEADER, &delivery.public_id),
+        (SIGNATURE_HEADER, &signature),
+    ];
+
+    let response = http_client::send(Method::POST, &url, &headers, payload, policy).await?;
+
+    Ok(response.status)
+}
+
+// 30 seconds, then 2, 8 and 32 minutes
+fn retry_at(now: DateTime<Utc>, attempts: u32) -> Option<DateTime<Utc>> {
+    match attempts < MAX_ATTEMPTS {
+        true => Some(now + Duration::seconds(30 * 4_i64.pow(attempts.saturating_sub(1)))),
+        false => None,
+    }
+}
+
+// For the delivery log, with the details the error's own message leaves out
+fn describe(error: &HttpError) -> String {
+    match error {
+        HttpError::InvalidUrl(e) => format!("invalid url: {}", e),
+        HttpError::PrivateAddress(e) => format!("private address: {}", e),
+        HttpError::Connection(e) | HttpError::InvalidResponse(e) => e.clone(),
+        HttpError::Timeout => "timed out".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use crate::entities::webhook::WebhookEvent;
+
+    use super::*;
+
+    #[test]
+    fn test_retry_at() {
+        let now = Utc::now();
+
+        assert_eq!(retry_at(now, 1), Some(now + Duration::seconds(30)));
+        assert_eq!(retry_at(now, 2), Some(now + Duration::minutes(2)));
+        assert_eq!(retry_at(now, 4), Some(now + Duration::minutes(32)));
+        assert_eq!(retry_at(now, MAX_ATTEMPTS), None);
+    }
+
+    #[actix_web::test]
+    async fn test_deliver_signs_payload() {
+        // A local receiver that answers 204 and hands back the request it got
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received = actix_web::rt::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // The body can arrive after the headers
+            let mut request = vec![];
+            let mut buffer = [0; 4096];
+            while !request.ends_with(b"}") {
+                let length = stream.read(&mut buffer).await.unwrap();
+                if length == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..length]);
+            }
+            let _ = stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        let now = Utc::now();
+        let webhook = Webhook {
+            id: 1,
+            public_id: "abc".to_owned(),
+            url: format!("http://{}/hook", addr),
+            secret: "secret".to_owned(),
+            events: vec![WebhookEvent::PostCreated],
+            is_deleted: false,
+            created: now,
+            updated: now,
+        };
+        let delivery = WebhookDelivery {
+            id: 1,
+            public_id: "def".to_owned(),
+            webhook_id: 1,
+            event: WebhookEvent::PostCreated,
+            subject_id: 1,
+            payload: Some(r#"{"hello":"world"}"#.to_owned()),
+            attempts: 1,
+            status_code: None,
+            error: None,
+            is_delivered: false,
+            next_attempt: None,
+            created: now,
+            updated: now,
+        };
+        let policy = HttpPolicy {
+            timeout: StdDuration::from_secs(2),
+            allow_private: true,
+            ..HttpPolicy::default()
+        };
+
+        assert_eq!(deliver(&webhook, &delivery, &policy).await.unwrap(), 204);
+
+        let request = received.await.unwrap();
+        assert!(request.starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(request.contains("x-effward-event: post.created\r\n"));
+        assert!(request.contains("x-effward-delivery: def\r\n"));
+        assert!(request.contains(
+            "x-effward-signature: sha256=2677ad3e7c090b2fa2c0fb13020d66d5420879b8316eb356a2d60fb9073bc778\r\n"
+        ));
+        assert!(request.ends_with("\r\n\r\n{\"hello\":\"world\"}"));
+
+        let result = deliver(&webhook, &delivery, &HttpPolicy::default()).await;
+        assert!(matches!(result, Err(HttpError::PrivateAddress(_))));
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    entities::{
+        comment::CommentStore,
+        post::PostStore,
+        webhook::{WebhookDelivery, WebhookEvent},
+        EntityError, EntityStores,
+    },
+    routes::models::{self, CommentModel, PostSummary, Viewer},
+};
+
+/// The JSON body of a delivery, with the post and comment in the same shape as the API.
+#[derive(Serialize)]
+struct Payload {
+    id: String,
+    event: WebhookEvent,
+    created: DateTime<Utc>,
+    url: String,
+    post: PostSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<CommentModel>,
+}
+
+pub async fn build(
+    stores: &EntityStores,
+    base_url: &str,
+    delivery: &WebhookDelivery,
+) -> Result<String, EntityError> {
+    let (post, comment) = match delivery.event {
+        WebhookEvent::PostCreated => (
+            stores.post_store.get_by_id(delivery.subject_id).await?,
+            None,
+        ),
+        WebhookEvent::CommentCreated => {
+            let comment = stores.comment_store.get_by_id(delivery.subject_id).await?;
+            let post = stores.post_store.get_by_id(comment.post_id).await?;
+            (
+                post,
+                Some(models::translate_comment(stores, &Viewer::anonymous(), &comment, 0).await?),
+            )
+        }
+    };
+
+    let payload = Payload {
+        id: delivery.public_id.clone(),
+        event: delivery.event,
+        created: delivery.created,
+        url: format!("{}/post/{}", base_url, post.public_id),
+        post: models::translate_post_summary(&post, stores, 0).await?,
+        comment,
+    };
+
+    match serde_json::to_string(&payload) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(EntityError::Internal(e.to_string())),
+    }
+}
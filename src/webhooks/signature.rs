@@ -0,0 +1,27 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &str = "X-Effward-Signature";
+
+/// The value of the signature header for a payload, which receivers recompute with the webhook's
+/// secret to check the payload came from us and wasn't changed.
+pub fn sign(secret: &str, payload: &[u8]) -> String {
+    // HMAC takes keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(payload);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("secret", br#"{"hello":"world"}"#),
+            "sha256=2677ad3e7c090b2fa2c0fb13020d66d5420879b8316eb356a2d60fb9073bc778"
+        );
+    }
+}